@@ -0,0 +1,327 @@
+//! Host-side analysis of built command streams.
+use crate::{parse, GpioByte, MpsseCmd, MpsseCmdBuilder};
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::time::Duration;
+
+/// Upper bound of the number of clock cycles of a single command.
+///
+/// Commands waiting on GPIOL1 without a clock count are unbounded and count
+/// as zero cycles.
+fn max_clock_cycles(bytes: &[u8]) -> u64 {
+    let len16 = |bytes: &[u8]| (u64::from(bytes[1]) | u64::from(bytes[2]) << 8) + 1;
+    match bytes[0] {
+        // bit mode
+        op if parse::is_clocking(op) && op & 0x02 != 0 => u64::from(bytes[1]) + 1,
+        op if parse::is_clocking(op) => len16(bytes) * 8,
+        // clock N bits
+        0x8E => u64::from(bytes[1]) + 1,
+        // clock N*8 bits, with or without waiting on GPIOL1
+        0x8F | 0x9C | 0x9D => len16(bytes) * 8,
+        _ => 0,
+    }
+}
+
+impl MpsseCmdBuilder {
+    /// GPIO reads in the command stream.
+    ///
+    /// Returns the byte read by each [`gpio_lower`] or [`gpio_upper`] command
+    /// and the offset of the read value in the device response, in stream
+    /// order.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::{ClockDataIn, GpioByte, MpsseCmdBuilder};
+    ///
+    /// let cmd = MpsseCmdBuilder::new()
+    ///     .gpio_lower()
+    ///     .clock_data_in(ClockDataIn::MsbPos, 2)
+    ///     .gpio_upper();
+    /// assert_eq!(
+    ///     cmd.gpio_reads(),
+    ///     vec![(GpioByte::Lower, 0), (GpioByte::Upper, 3)]
+    /// );
+    /// ```
+    ///
+    /// [`gpio_lower`]: MpsseCmdBuilder::gpio_lower
+    /// [`gpio_upper`]: MpsseCmdBuilder::gpio_upper
+    pub fn gpio_reads(&self) -> Vec<(GpioByte, usize)> {
+        let lower: u8 = MpsseCmd::GetDataBitsLowbyte.into();
+        let upper: u8 = MpsseCmd::GetDataBitsHighbyte.into();
+        let mut reads = Vec::new();
+        let mut offset = 0;
+        for cmd in parse::commands(self.as_slice()).map_while(Result::ok) {
+            match cmd.bytes[0] {
+                op if op == lower => reads.push((GpioByte::Lower, offset)),
+                op if op == upper => reads.push((GpioByte::Upper, offset)),
+                _ => {}
+            }
+            offset += cmd.read_len;
+        }
+        reads
+    }
+
+    /// Number of times each opcode is used in the command stream.
+    ///
+    /// Returns `(opcode, count)` pairs sorted by opcode. Opcodes can be
+    /// compared against [`MpsseCmd`] and the data clocking mode enums such as
+    /// [`ClockDataOut`](crate::ClockDataOut) after converting them with
+    /// `u8::from`. An incomplete command at the end of the stream is not
+    /// counted.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::{ClockDataOut, MpsseCmd, MpsseCmdBuilder};
+    ///
+    /// let cmd = MpsseCmdBuilder::new()
+    ///     .set_gpio_lower(0x00, 0x0B)
+    ///     .clock_data_out(ClockDataOut::MsbNeg, &[0x12])
+    ///     .clock_data_out(ClockDataOut::MsbNeg, &[0x34])
+    ///     .set_gpio_lower(0x08, 0x0B);
+    /// assert_eq!(
+    ///     cmd.opcode_histogram(),
+    ///     vec![
+    ///         (ClockDataOut::MsbNeg.into(), 2),
+    ///         (MpsseCmd::SetDataBitsLowbyte.into(), 2),
+    ///     ]
+    /// );
+    /// ```
+    pub fn opcode_histogram(&self) -> Vec<(u8, usize)> {
+        let mut histogram: BTreeMap<u8, usize> = BTreeMap::new();
+        for cmd in parse::commands(self.as_slice()).map_while(Result::ok) {
+            *histogram.entry(cmd.bytes[0]).or_default() += 1;
+        }
+        histogram.into_iter().collect()
+    }
+
+    /// Clock divisor commands in the command stream.
+    ///
+    /// Returns the offset and divisor of each
+    /// [`set_clock`](MpsseCmdBuilder::set_clock) divisor command, in stream
+    /// order. Divide by 5 commands are not listed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::MpsseCmdBuilder;
+    ///
+    /// let cmd = MpsseCmdBuilder::new()
+    ///     .gpio_lower()
+    ///     .set_clock(0x1234, Some(false));
+    /// assert_eq!(cmd.clock_changes(), vec![(2, 0x1234)]);
+    /// ```
+    pub fn clock_changes(&self) -> Vec<(usize, u32)> {
+        let set_clock: u8 = MpsseCmd::SetClockFrequency.into();
+        parse::commands(self.as_slice())
+            .filter_map(Result::ok)
+            .filter(|cmd| cmd.bytes[0] == set_clock)
+            .map(|cmd| {
+                let divisor = u32::from(cmd.bytes[1]) | u32::from(cmd.bytes[2]) << 8;
+                (cmd.offset, divisor)
+            })
+            .collect()
+    }
+
+    /// Returns `true` if the command stream changes the clock frequency.
+    ///
+    /// Both clock divisor and divide by 5 commands change the frequency.
+    /// Setting the clock is slow compared to data clocking, configure it
+    /// once rather than in a stream sent repeatedly.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::MpsseCmdBuilder;
+    ///
+    /// assert!(!MpsseCmdBuilder::new().gpio_lower().changes_clock());
+    /// assert!(MpsseCmdBuilder::new().set_clock(5, None).changes_clock());
+    /// ```
+    pub fn changes_clock(&self) -> bool {
+        let set_clock: u8 = MpsseCmd::SetClockFrequency.into();
+        let divide_on: u8 = MpsseCmd::EnableClockDivide.into();
+        let divide_off: u8 = MpsseCmd::DisableClockDivide.into();
+        parse::commands(self.as_slice())
+            .filter_map(Result::ok)
+            .any(|cmd| [set_clock, divide_on, divide_off].contains(&cmd.bytes[0]))
+    }
+
+    /// Returns `true` if the command stream cannot change the device state.
+    ///
+    /// Read-only streams only contain GPIO reads, data clocking commands that
+    /// read without driving TDI/DO or TMS, clocking commands without data,
+    /// waits and send immediate. Streams setting GPIO pins, changing the
+    /// clock or another mode, containing unrecognized opcodes or ending with
+    /// an incomplete command are not read-only. An empty stream is
+    /// read-only.
+    ///
+    /// Note that reading still toggles the clock, which may be observed by the
+    /// devices on the bus.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::MpsseCmdBuilder;
+    ///
+    /// let cmd = MpsseCmdBuilder::new().gpio_lower().gpio_upper().send_immediate();
+    /// assert!(cmd.is_read_only());
+    /// assert!(!cmd.set_gpio_lower(0x00, 0x0B).is_read_only());
+    /// ```
+    pub fn is_read_only(&self) -> bool {
+        parse::commands(self.as_slice()).all(|cmd| match cmd {
+            Ok(cmd) => match cmd.bytes[0] {
+                // write TDI/DO or TMS
+                op if parse::is_clocking(op) => op & 0x50 == 0,
+                0x81 | 0x83 | 0x87 | 0x88 | 0x89 | 0x8E | 0x8F | 0x94 | 0x95 | 0x9C | 0x9D => true,
+                _ => false,
+            },
+            Err(_) => false,
+        })
+    }
+
+    /// Returns `true` if replaying the command stream has the same effect on
+    /// the MPSSE regardless of the state left by earlier commands.
+    ///
+    /// MPSSE commands set absolute values, so the only dependency on prior
+    /// state is clocking with the lower byte GPIO state and direction left by
+    /// earlier commands, the idle level of the clock in particular. Streams
+    /// that clock data or run clock-only commands before a
+    /// [`set_gpio_lower`], or that end with an incomplete command, are not
+    /// idempotent. An empty stream is idempotent.
+    ///
+    /// This cannot tell whether the data clocked out has side effects on the
+    /// attached devices, such as writing to a FIFO, which replaying would
+    /// repeat.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::{ClockDataOut, MpsseCmdBuilder};
+    ///
+    /// let write = MpsseCmdBuilder::new().clock_data_out(ClockDataOut::MsbNeg, &[0x06]);
+    /// assert!(!write.is_idempotent());
+    ///
+    /// let cmd = MpsseCmdBuilder::new()
+    ///     .set_gpio_lower(0x08, 0x0B)
+    ///     .clock_data_out(ClockDataOut::MsbNeg, &[0x06]);
+    /// assert!(cmd.is_idempotent());
+    /// ```
+    ///
+    /// [`set_gpio_lower`]: MpsseCmdBuilder::set_gpio_lower
+    pub fn is_idempotent(&self) -> bool {
+        let set_lower: u8 = MpsseCmd::SetDataBitsLowbyte.into();
+        let mut lower_set = false;
+        for cmd in parse::commands(self.as_slice()) {
+            match cmd {
+                Ok(cmd) => match cmd.bytes[0] {
+                    op if op == set_lower => lower_set = true,
+                    op if parse::is_clocking(op) && !lower_set => return false,
+                    0x8E | 0x8F | 0x94 | 0x95 | 0x9C | 0x9D if !lower_set => return false,
+                    _ => {}
+                },
+                Err(_) => return false,
+            }
+        }
+        true
+    }
+
+    /// Worst case time to execute the command stream, including USB round
+    /// trips.
+    ///
+    /// Sums the time to clock every data and clock-only command at
+    /// `clock_hz`, with one `latency_timer` and one `usb_rtt` for each
+    /// [`send_immediate`], where the host usually waits for the device
+    /// response. Clocking until GPIOL1 counts the full clock count, while
+    /// commands waiting on GPIOL1 without a clock count are unbounded and not
+    /// counted. Assumes two phase clocking.
+    ///
+    /// This will panic if `clock_hz` is zero.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::{ClockDataOut, MpsseCmdBuilder};
+    /// use std::time::Duration;
+    ///
+    /// let cmd = MpsseCmdBuilder::new()
+    ///     .clock_data_out(ClockDataOut::MsbNeg, &[0; 125])
+    ///     .send_immediate();
+    /// let ms = Duration::from_millis(1);
+    /// assert_eq!(
+    ///     cmd.worst_case_latency(1_000_000, 2 * ms, ms),
+    ///     Duration::from_millis(4)
+    /// );
+    /// ```
+    ///
+    /// [`send_immediate`]: MpsseCmdBuilder::send_immediate
+    pub fn worst_case_latency(
+        &self,
+        clock_hz: u32,
+        latency_timer: Duration,
+        usb_rtt: Duration,
+    ) -> Duration {
+        let send_immediate: u8 = MpsseCmd::SendImmediate.into();
+        let round_trips = parse::commands(self.as_slice())
+            .filter_map(Result::ok)
+            .filter(|cmd| cmd.bytes[0] == send_immediate)
+            .count();
+        let round_trips = u32::try_from(round_trips).unwrap_or(u32::MAX);
+        self.estimate_duration(clock_hz) + (latency_timer + usb_rtt) * round_trips
+    }
+
+    /// Number of clock cycles of the command stream.
+    ///
+    /// Counts the bits clocked by data, bit and TMS clocking commands, and
+    /// the cycles of clock-only commands. Clocking until GPIOL1 counts the
+    /// full clock count, while commands waiting on GPIOL1 without a clock
+    /// count are unbounded and not counted. GPIO and configuration commands
+    /// take no clock cycles. An incomplete command at the end of the stream
+    /// is not counted.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::{ClockBitsOut, ClockDataOut, MpsseCmdBuilder};
+    ///
+    /// let cmd = MpsseCmdBuilder::new()
+    ///     .set_gpio_lower(0x00, 0x0B)
+    ///     .clock_data_out(ClockDataOut::MsbNeg, &[0; 4])
+    ///     .clock_bits_out(ClockBitsOut::MsbNeg, 0x00, 3);
+    /// assert_eq!(cmd.estimate_clock_cycles(), 35);
+    /// ```
+    pub fn estimate_clock_cycles(&self) -> u64 {
+        parse::commands(self.as_slice())
+            .filter_map(Result::ok)
+            .map(|cmd| max_clock_cycles(cmd.bytes))
+            .sum()
+    }
+
+    /// Time to clock the command stream at `clock_hz`.
+    ///
+    /// This is the [`estimate_clock_cycles`] at the clock frequency, assuming
+    /// two phase clocking, rounded up to the nanosecond. It does not include
+    /// USB transfers, see
+    /// [`worst_case_latency`](MpsseCmdBuilder::worst_case_latency).
+    ///
+    /// This will panic if `clock_hz` is zero.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::{ClockDataOut, MpsseCmdBuilder};
+    /// use std::time::Duration;
+    ///
+    /// let cmd = MpsseCmdBuilder::new().clock_data_out(ClockDataOut::MsbNeg, &[0; 125]);
+    /// assert_eq!(cmd.estimate_duration(1_000_000), Duration::from_millis(1));
+    /// ```
+    ///
+    /// [`estimate_clock_cycles`]: MpsseCmdBuilder::estimate_clock_cycles
+    pub fn estimate_duration(&self, clock_hz: u32) -> Duration {
+        assert!(clock_hz != 0, "clock frequency cannot be zero");
+        let cycles = self.estimate_clock_cycles();
+        let ns = (u128::from(cycles) * 1_000_000_000).div_ceil(u128::from(clock_hz));
+        Duration::from_nanos(u64::try_from(ns).unwrap_or(u64::MAX))
+    }
+}
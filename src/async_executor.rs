@@ -0,0 +1,50 @@
+//! Asynchronous executor trait.
+use crate::MpsseSettings;
+
+/// Asynchronous FTDI MPSSE configurator and executor
+///
+/// Counterpart of [`MpsseCmdExecutor`](crate::MpsseCmdExecutor) for
+/// asynchronous USB stacks, command streams built with
+/// [`MpsseCmdBuilder`](crate::MpsseCmdBuilder) are sent unchanged.
+///
+/// Requires the `async` feature.
+#[allow(async_fn_in_trait)]
+pub trait AsyncMpsseCmdExecutor {
+    /// Error type
+    type Error;
+
+    /// Configure FTDI MPSSE mode
+    async fn init(&mut self, settings: &MpsseSettings) -> Result<(), Self::Error>;
+
+    /// Execute MPSSE write command sequence
+    async fn send(&mut self, data: &[u8]) -> Result<(), Self::Error>;
+
+    /// Execute MPSSE read command sequence
+    async fn recv(&mut self, data: &mut [u8]) -> Result<(), Self::Error>;
+
+    /// Execute MPSSE command and read response
+    async fn xfer(&mut self, txdata: &[u8], rxdata: &mut [u8]) -> Result<(), Self::Error> {
+        self.send(txdata).await?;
+        self.recv(rxdata).await
+    }
+}
+
+impl<E: AsyncMpsseCmdExecutor> AsyncMpsseCmdExecutor for &mut E {
+    type Error = E::Error;
+
+    async fn init(&mut self, settings: &MpsseSettings) -> Result<(), Self::Error> {
+        (**self).init(settings).await
+    }
+
+    async fn send(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        (**self).send(data).await
+    }
+
+    async fn recv(&mut self, data: &mut [u8]) -> Result<(), Self::Error> {
+        (**self).recv(data).await
+    }
+
+    async fn xfer(&mut self, txdata: &[u8], rxdata: &mut [u8]) -> Result<(), Self::Error> {
+        (**self).xfer(txdata, rxdata).await
+    }
+}
@@ -0,0 +1,201 @@
+//! Throughput and latency benchmarks of an executor.
+use crate::{ClockData, MpsseCmdBuilder, MpsseCmdExecutor, MAX_CLOCK_DATA_LEN};
+use std::convert::TryFrom;
+use std::time::{Duration, Instant};
+
+/// Byte clocked through the loopback by [`measure_rtt`].
+const RTT_PATTERN: u8 = 0xA5;
+
+/// Results of [`throughput_benchmark`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ThroughputReport {
+    /// Number of bytes clocked out and back in.
+    pub bytes: usize,
+    /// Time taken by the whole benchmark.
+    pub elapsed: Duration,
+    /// Time taken by the transfer of each chunk, in order.
+    pub chunk_latencies: Vec<Duration>,
+    /// Number of bytes read back different from the bytes clocked out.
+    pub mismatches: usize,
+}
+
+impl ThroughputReport {
+    /// Measured throughput in megabytes (10<sup>6</sup> bytes) per second.
+    pub fn mbps(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 {
+            self.bytes as f64 / secs / 1e6
+        } else {
+            0.0
+        }
+    }
+
+    /// Shortest chunk transfer time, `None` if no chunk was transferred.
+    pub fn min_chunk_latency(&self) -> Option<Duration> {
+        self.chunk_latencies.iter().min().copied()
+    }
+
+    /// Longest chunk transfer time, `None` if no chunk was transferred.
+    pub fn max_chunk_latency(&self) -> Option<Duration> {
+        self.chunk_latencies.iter().max().copied()
+    }
+
+    /// Mean chunk transfer time, `None` if no chunk was transferred.
+    pub fn mean_chunk_latency(&self) -> Option<Duration> {
+        mean(&self.chunk_latencies)
+    }
+}
+
+/// Mean of durations, `None` if there are none.
+fn mean(durations: &[Duration]) -> Option<Duration> {
+    let count = u32::try_from(durations.len()).ok()?;
+    if count == 0 {
+        return None;
+    }
+    Some(durations.iter().sum::<Duration>() / count)
+}
+
+/// Results of [`measure_rtt`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RttReport {
+    /// Time taken by each round trip, in order.
+    pub round_trips: Vec<Duration>,
+}
+
+impl RttReport {
+    /// Shortest round trip, `None` if there were no iterations.
+    pub fn min(&self) -> Option<Duration> {
+        self.round_trips.iter().min().copied()
+    }
+
+    /// Longest round trip, `None` if there were no iterations.
+    pub fn max(&self) -> Option<Duration> {
+        self.round_trips.iter().max().copied()
+    }
+
+    /// Mean round trip, `None` if there were no iterations.
+    pub fn mean(&self) -> Option<Duration> {
+        mean(&self.round_trips)
+    }
+
+    /// Peak to peak jitter, the difference between the longest and the
+    /// shortest round trip, `None` if there were no iterations.
+    pub fn jitter(&self) -> Option<Duration> {
+        Some(self.max()? - self.min()?)
+    }
+}
+
+/// Measure the throughput of an executor.
+///
+/// Clocks `total_bytes` out and back in through the internal loopback, with
+/// one transfer per `chunk` bytes, and times each transfer. The MPSSE must be
+/// initialized and the clock set beforehand. Loopback is disabled at the end
+/// unless an error occurs.
+///
+/// Short chunks are dominated by the USB latency, compare several chunk
+/// sizes to tune the transfer size and latency timer of the device.
+///
+/// This will panic if `chunk` is 0 or greater than `u16::MAX + 1`.
+///
+/// # Example
+///
+/// ```no_run
+/// use ftdi_mpsse::{throughput_benchmark, MpsseCmdExecutor};
+///
+/// # fn example<E: MpsseCmdExecutor>(mut exec: E) -> Result<(), E::Error> {
+/// for chunk in [512, 4096, 65536].iter() {
+///     let report = throughput_benchmark(&mut exec, 1 << 20, *chunk)?;
+///     println!(
+///         "{} byte chunks: {:.2} MB/s, mean latency {:?}",
+///         chunk,
+///         report.mbps(),
+///         report.mean_chunk_latency()
+///     );
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn throughput_benchmark<E: MpsseCmdExecutor>(
+    exec: &mut E,
+    total_bytes: usize,
+    chunk: usize,
+) -> Result<ThroughputReport, E::Error> {
+    assert!(
+        chunk != 0 && chunk <= MAX_CLOCK_DATA_LEN,
+        "chunk length must be between 1 and u16::MAX + 1"
+    );
+    let data: Vec<u8> = (0..chunk).map(|i| i as u8).collect();
+    let mut rx = vec![0; chunk];
+    let mut chunk_latencies = Vec::with_capacity(total_bytes.div_ceil(chunk));
+    let mut mismatches = 0;
+
+    let start = Instant::now();
+    exec.send(MpsseCmdBuilder::new().enable_loopback().as_slice())?;
+    let mut remaining = total_bytes;
+    while remaining > 0 {
+        let len = remaining.min(chunk);
+        let cmd = MpsseCmdBuilder::new()
+            .clock_data(ClockData::MsbPosIn, &data[..len])
+            .send_immediate();
+        let chunk_start = Instant::now();
+        exec.xfer(cmd.as_slice(), &mut rx[..len])?;
+        chunk_latencies.push(chunk_start.elapsed());
+        mismatches += data[..len]
+            .iter()
+            .zip(&rx[..len])
+            .filter(|(tx, rx)| tx != rx)
+            .count();
+        remaining -= len;
+    }
+    exec.send(MpsseCmdBuilder::new().disable_loopback().as_slice())?;
+
+    Ok(ThroughputReport {
+        bytes: total_bytes,
+        elapsed: start.elapsed(),
+        chunk_latencies,
+        mismatches,
+    })
+}
+
+/// Measure the round trip latency of an executor.
+///
+/// Clocks a single byte through the internal loopback `iterations` times,
+/// each time with [`send_immediate`](MpsseCmdBuilder::send_immediate) and
+/// waiting for the response, and times each round trip. The time is
+/// dominated by the USB transfers and the latency timer of the device,
+/// which is what batching commands saves. The MPSSE must be initialized
+/// beforehand. Loopback is disabled at the end unless an error occurs.
+///
+/// # Example
+///
+/// ```no_run
+/// use ftdi_mpsse::{measure_rtt, MpsseCmdExecutor};
+///
+/// # fn example<E: MpsseCmdExecutor>(mut exec: E) -> Result<(), E::Error> {
+/// let report = measure_rtt(&mut exec, 100)?;
+/// println!(
+///     "round trip: mean {:?}, jitter {:?}",
+///     report.mean(),
+///     report.jitter()
+/// );
+/// # Ok(())
+/// # }
+/// ```
+pub fn measure_rtt<E: MpsseCmdExecutor>(
+    exec: &mut E,
+    iterations: usize,
+) -> Result<RttReport, E::Error> {
+    let cmd = MpsseCmdBuilder::new()
+        .clock_data(ClockData::MsbPosIn, &[RTT_PATTERN])
+        .send_immediate();
+    let mut round_trips = Vec::with_capacity(iterations);
+    exec.send(MpsseCmdBuilder::new().enable_loopback().as_slice())?;
+    for _ in 0..iterations {
+        let mut rx = [0];
+        let start = Instant::now();
+        exec.xfer(cmd.as_slice(), &mut rx)?;
+        round_trips.push(start.elapsed());
+    }
+    exec.send(MpsseCmdBuilder::new().disable_loopback().as_slice())?;
+    Ok(RttReport { round_trips })
+}
@@ -0,0 +1,91 @@
+//! Charlieplexed LED matrix driven over lower byte GPIO pins.
+use crate::{pin_mask, MpsseCmdBuilder};
+use alloc::vec::Vec;
+
+/// Charlieplexed LEDs driven by lower byte GPIO pins.
+///
+/// `n` pins drive `n * (n - 1)` LEDs, one for each ordered pair of pins.
+/// An LED is lit by driving its anode pin high and its cathode pin low, with
+/// every other pin set as an input so no other LED conducts.
+///
+/// LEDs are numbered by anode pin, then by cathode pin skipping the anode:
+/// with pins `[a, b, c]` LED 0 is `a → b`, LED 1 is `a → c`, LED 2 is
+/// `b → a` and so on.
+///
+/// # Example
+///
+/// ```
+/// use ftdi_mpsse::{Charlieplex, MpsseCmdBuilder};
+///
+/// let leds = Charlieplex::new(&[4, 5, 6]);
+/// assert_eq!(leds.led_count(), 6);
+/// // LED 2 is 5 → 4
+/// assert_eq!(leds.pattern(2), (0x20, 0x30));
+///
+/// let mut cmd = MpsseCmdBuilder::new();
+/// for led in 0..leds.led_count() {
+///     cmd = leds.light(cmd, led, 1000);
+/// }
+/// let cmd = leds.off(cmd);
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Charlieplex {
+    pins: Vec<u8>,
+}
+
+impl Charlieplex {
+    /// Create a charlieplexed matrix on lower byte GPIO pins.
+    ///
+    /// This will panic for less than 2 pins, duplicate pins, or pin numbers
+    /// greater than 7.
+    pub fn new(pins: &[u8]) -> Charlieplex {
+        assert!(pins.len() >= 2, "charlieplexing needs at least 2 pins");
+        let pins: Vec<u8> = pins.iter().map(|pin| pin_mask(*pin)).collect();
+        let direction = pins.iter().fold(0x00, |mask, pin| mask | pin);
+        assert!(
+            direction.count_ones() as usize == pins.len(),
+            "charlieplex pins must be unique"
+        );
+        Charlieplex { pins }
+    }
+
+    /// Number of LEDs in the matrix.
+    pub fn led_count(&self) -> usize {
+        self.pins.len() * (self.pins.len() - 1)
+    }
+
+    /// Lower byte GPIO `(state, direction)` lighting an LED.
+    ///
+    /// This will panic if `led` is not less than
+    /// [`led_count`](Charlieplex::led_count).
+    pub fn pattern(&self, led: usize) -> (u8, u8) {
+        assert!(led < self.led_count(), "LED index out of range");
+        let others = self.pins.len() - 1;
+        let anode = led / others;
+        let mut cathode = led % others;
+        if cathode >= anode {
+            cathode += 1;
+        }
+        let anode = self.pins[anode];
+        (anode, anode | self.pins[cathode])
+    }
+
+    /// Light a single LED for `hold_clocks` clocks.
+    ///
+    /// The LED stays lit after the command, until the next
+    /// [`light`](Charlieplex::light) or [`off`](Charlieplex::off). Other lower
+    /// byte pins are set as inputs.
+    ///
+    /// This will panic if `led` is not less than
+    /// [`led_count`](Charlieplex::led_count).
+    pub fn light(&self, cmd: MpsseCmdBuilder, led: usize, hold_clocks: usize) -> MpsseCmdBuilder {
+        let (state, direction) = self.pattern(led);
+        cmd.set_gpio_lower(state, direction)
+            .dummy_clocks(hold_clocks)
+    }
+
+    /// Turn every LED off by setting all lower byte pins as inputs.
+    pub fn off(&self, cmd: MpsseCmdBuilder) -> MpsseCmdBuilder {
+        cmd.set_gpio_lower(0x00, 0x00)
+    }
+}
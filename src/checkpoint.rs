@@ -0,0 +1,81 @@
+//! Rollback of speculatively built commands.
+use crate::MpsseCmdBuilder;
+use core::fmt;
+
+/// Point in a command stream recorded by
+/// [`checkpoint`](MpsseCmdBuilder::checkpoint).
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Checkpoint {
+    len: usize,
+}
+
+impl Checkpoint {
+    /// Length of the command stream in bytes at the checkpoint.
+    pub fn stream_len(&self) -> usize {
+        self.len
+    }
+}
+
+/// Error returned by [`rollback`](MpsseCmdBuilder::rollback) when the
+/// command stream is shorter than at the checkpoint.
+///
+/// The checkpoint was recorded by another builder, or the builder was
+/// already rolled back to an earlier checkpoint.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct RollbackError {
+    /// Length of the command stream in bytes at the checkpoint.
+    pub checkpoint_len: usize,
+    /// Length of the command stream in bytes.
+    pub stream_len: usize,
+}
+
+impl fmt::Display for RollbackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "checkpoint at {} bytes is beyond the {} byte command stream",
+            self.checkpoint_len, self.stream_len
+        )
+    }
+}
+
+impl core::error::Error for RollbackError {}
+
+impl MpsseCmdBuilder {
+    /// Record the current end of the command stream.
+    ///
+    /// [`rollback`](MpsseCmdBuilder::rollback) discards the commands added
+    /// after the checkpoint.
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint { len: self.0.len() }
+    }
+
+    /// Discard the commands added after a checkpoint.
+    ///
+    /// Returns an error and leaves the stream unchanged if the stream is
+    /// shorter than at the checkpoint.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::{ClockDataOut, MpsseCmdBuilder};
+    ///
+    /// let mut cmd = MpsseCmdBuilder::new().set_gpio_lower(0x00, 0x0B);
+    /// let checkpoint = cmd.checkpoint();
+    /// cmd = cmd.clock_data_out(ClockDataOut::MsbNeg, &[0x06]);
+    /// // the device is not ready after all
+    /// cmd.rollback(checkpoint)?;
+    /// assert_eq!(cmd.as_slice(), [0x80, 0x00, 0x0B]);
+    /// # Ok::<(), ftdi_mpsse::RollbackError>(())
+    /// ```
+    pub fn rollback(&mut self, checkpoint: Checkpoint) -> Result<(), RollbackError> {
+        if self.0.len() < checkpoint.len {
+            return Err(RollbackError {
+                checkpoint_len: checkpoint.len,
+                stream_len: self.0.len(),
+            });
+        }
+        self.0.truncate(checkpoint.len);
+        Ok(())
+    }
+}
@@ -0,0 +1,173 @@
+//! Splitting of large transfers.
+use crate::{parse, MpsseCmdExecutor, MpsseSettings, MAX_CLOCK_DATA_LEN};
+use core::convert::TryFrom;
+use core::time::Duration;
+
+/// Pick the chunk size for splitting a transfer of `total` bytes.
+///
+/// Each chunk takes roughly `target_latency` to clock at `clock_hz`, so
+/// progress can be observed and a transfer cancelled at that interval. The
+/// chunk size is at least one byte, and at most `in_transfer_size`, the
+/// length of a single data clocking command and `total`.
+///
+/// Returns `0` if `total` is `0`.
+///
+/// # Arguments
+///
+/// * `total` - Length of the whole transfer in bytes.
+/// * `in_transfer_size` - USB in transfer size, see
+///   [`MpsseSettings::in_transfer_size`](crate::MpsseSettings::in_transfer_size).
+/// * `target_latency` - Time to transfer each chunk.
+/// * `clock_hz` - Frequency of the MPSSE clock.
+///
+/// # Example
+///
+/// ```
+/// use ftdi_mpsse::optimal_chunk_size;
+/// use std::time::Duration;
+///
+/// // 1 ms at 1 MHz
+/// assert_eq!(
+///     optimal_chunk_size(100_000, 4096, Duration::from_millis(1), 1_000_000),
+///     125
+/// );
+/// // limited by the USB transfer size
+/// assert_eq!(
+///     optimal_chunk_size(100_000, 4096, Duration::from_millis(10), 30_000_000),
+///     4096
+/// );
+/// ```
+pub fn optimal_chunk_size(
+    total: usize,
+    in_transfer_size: u32,
+    target_latency: Duration,
+    clock_hz: u32,
+) -> usize {
+    if total == 0 {
+        return 0;
+    }
+    let bytes = target_latency.as_nanos() * u128::from(clock_hz) / 8 / 1_000_000_000;
+    let bytes = usize::try_from(bytes).unwrap_or(usize::MAX);
+    let limit = usize::try_from(in_transfer_size)
+        .unwrap_or(usize::MAX)
+        .min(MAX_CLOCK_DATA_LEN)
+        .min(total)
+        .max(1);
+    bytes.clamp(1, limit)
+}
+
+/// Executor decorator splitting transfers into USB transfer sized pieces.
+///
+/// [`recv`] reads the response in pieces of at most
+/// [`in_transfer_size`](MpsseSettings::in_transfer_size) bytes, taken from
+/// the settings passed to [`init`]. [`send`] splits the command stream at
+/// command boundaries into pieces of at most that size. A single command
+/// longer than the transfer size is never split and is sent on its own.
+///
+/// The transfer size of [`MpsseSettings::default`] is used until [`init`] is
+/// called.
+///
+/// # Example
+///
+/// ```no_run
+/// use ftdi_mpsse::{ChunkedExecutor, ClockDataIn, MpsseCmdBuilder, MpsseCmdExecutor, MpsseSettings};
+///
+/// # fn example<E: MpsseCmdExecutor>(exec: E) -> Result<(), E::Error> {
+/// let mut exec = ChunkedExecutor::new(exec);
+/// exec.init(&MpsseSettings::default())?;
+/// let cmd = MpsseCmdBuilder::new()
+///     .clock_data_in(ClockDataIn::MsbPos, 65536)
+///     .send_immediate();
+/// // received with 16 reads of 4096 bytes
+/// let mut data = vec![0; 65536];
+/// exec.xfer(cmd.as_slice(), &mut data)?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`init`]: MpsseCmdExecutor::init
+/// [`send`]: MpsseCmdExecutor::send
+/// [`recv`]: MpsseCmdExecutor::recv
+#[derive(Debug)]
+pub struct ChunkedExecutor<E> {
+    inner: E,
+    chunk_size: usize,
+}
+
+impl<E> ChunkedExecutor<E> {
+    /// Wrap an executor.
+    pub fn new(inner: E) -> ChunkedExecutor<E> {
+        ChunkedExecutor {
+            inner,
+            chunk_size: transfer_size(&MpsseSettings::default()),
+        }
+    }
+
+    /// Maximum length of each piece in bytes.
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+
+    /// Get a reference to the wrapped executor.
+    pub fn inner(&self) -> &E {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the wrapped executor.
+    pub fn inner_mut(&mut self) -> &mut E {
+        &mut self.inner
+    }
+
+    /// Unwrap the executor.
+    pub fn into_inner(self) -> E {
+        self.inner
+    }
+}
+
+/// Transfer size of the settings, at least one byte.
+fn transfer_size(settings: &MpsseSettings) -> usize {
+    usize::try_from(settings.in_transfer_size)
+        .unwrap_or(usize::MAX)
+        .max(1)
+}
+
+impl<E: MpsseCmdExecutor> MpsseCmdExecutor for ChunkedExecutor<E> {
+    type Error = E::Error;
+
+    fn init(&mut self, settings: &MpsseSettings) -> Result<(), Self::Error> {
+        self.inner.init(settings)?;
+        self.chunk_size = transfer_size(settings);
+        Ok(())
+    }
+
+    fn send(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        let mut start = 0;
+        for cmd in parse::commands(data) {
+            // an incomplete command is left in the last piece as is
+            let cmd = match cmd {
+                Ok(cmd) => cmd,
+                Err(_) => break,
+            };
+            let end = cmd.offset + cmd.bytes.len();
+            if cmd.offset > start && end - start > self.chunk_size {
+                self.inner.send(&data[start..cmd.offset])?;
+                start = cmd.offset;
+            }
+        }
+        if start < data.len() {
+            self.inner.send(&data[start..])?;
+        }
+        Ok(())
+    }
+
+    fn recv(&mut self, data: &mut [u8]) -> Result<(), Self::Error> {
+        for chunk in data.chunks_mut(self.chunk_size) {
+            self.inner.recv(chunk)?;
+        }
+        Ok(())
+    }
+
+    fn purge(&mut self) -> Result<(), Self::Error> {
+        self.inner.purge()
+    }
+}
@@ -0,0 +1,132 @@
+//! Conformance tests for [`MpsseCmdExecutor`] implementations.
+//!
+//! [`test_suite`] runs the same checks against any executor, so backends
+//! built on different FTDI drivers, and mock executors, can be validated the
+//! same way.
+//!
+//! The suite drives every lower byte GPIO pin, nothing may be connected to
+//! them while it runs.
+use crate::{ClockData, MpsseCmdBuilder, MpsseCmdExecutor, MpsseSettings, BAD_COMMAND_RESPONSE};
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Data clocked out and back in by the loopback check.
+const LOOPBACK_PATTERN: [u8; 4] = [0x00, 0xFF, 0xA5, 0x3C];
+/// Lower byte GPIO state written and read back by the GPIO check.
+const GPIO_PATTERN: u8 = 0xA5;
+/// Unrecognized opcode sent by the bad command check.
+const BAD_OPCODE: u8 = 0xAB;
+
+/// Outcome of a conformance check.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum CheckResult<E> {
+    /// The check passed.
+    Passed,
+    /// The device responded with unexpected data.
+    Failed {
+        /// Expected response.
+        expected: Vec<u8>,
+        /// Actual response.
+        actual: Vec<u8>,
+    },
+    /// The executor returned an error.
+    Error(E),
+}
+
+impl<E> CheckResult<E> {
+    /// Returns `true` if the check passed.
+    pub fn is_passed(&self) -> bool {
+        matches!(self, CheckResult::Passed)
+    }
+}
+
+/// Results of [`test_suite`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Report<E> {
+    /// Name and outcome of every check, in the order they ran.
+    pub checks: Vec<(&'static str, CheckResult<E>)>,
+}
+
+impl<E> Report<E> {
+    /// Returns `true` if every check passed.
+    pub fn passed(&self) -> bool {
+        self.checks.iter().all(|(_, result)| result.is_passed())
+    }
+}
+
+fn compare<E>(expected: &[u8], actual: Vec<u8>) -> CheckResult<E> {
+    if expected == actual.as_slice() {
+        CheckResult::Passed
+    } else {
+        CheckResult::Failed {
+            expected: expected.to_vec(),
+            actual,
+        }
+    }
+}
+
+fn check_xfer<E: MpsseCmdExecutor>(
+    exec: &mut E,
+    cmd: MpsseCmdBuilder,
+    expected: &[u8],
+) -> CheckResult<E::Error> {
+    let mut actual = vec![0; expected.len()];
+    match exec.xfer(cmd.as_slice(), &mut actual) {
+        Ok(()) => compare(expected, actual),
+        Err(e) => CheckResult::Error(e),
+    }
+}
+
+/// Run the conformance checks against an executor.
+///
+/// The checks run in order, and later checks run even if earlier ones fail:
+///
+/// * `init` - Initializes the MPSSE with the default [`MpsseSettings`].
+/// * `loopback` - Clocks data through the internal loopback.
+/// * `gpio` - Drives the lower byte GPIO pins and reads them back.
+/// * `bad_command` - Sends an unrecognized opcode and expects its echo.
+///
+/// # Example
+///
+/// ```no_run
+/// use ftdi_mpsse::{conformance, MpsseCmdExecutor};
+///
+/// # fn example<E: MpsseCmdExecutor>(mut exec: E) where E::Error: std::fmt::Debug {
+/// let report = conformance::test_suite(&mut exec);
+/// for (name, result) in &report.checks {
+///     println!("{}: {:?}", name, result);
+/// }
+/// assert!(report.passed());
+/// # }
+/// ```
+pub fn test_suite<E: MpsseCmdExecutor>(exec: &mut E) -> Report<E::Error> {
+    let mut checks = Vec::new();
+
+    let init = match exec.init(&MpsseSettings::default()) {
+        Ok(()) => CheckResult::Passed,
+        Err(e) => CheckResult::Error(e),
+    };
+    checks.push(("init", init));
+
+    let cmd = MpsseCmdBuilder::new()
+        .enable_loopback()
+        .clock_data(ClockData::MsbPosIn, &LOOPBACK_PATTERN)
+        .disable_loopback()
+        .send_immediate();
+    checks.push(("loopback", check_xfer(exec, cmd, &LOOPBACK_PATTERN)));
+
+    let cmd = MpsseCmdBuilder::new()
+        .set_gpio_lower(GPIO_PATTERN, 0xFF)
+        .gpio_lower()
+        .set_gpio_lower(0x00, 0x00)
+        .send_immediate();
+    checks.push(("gpio", check_xfer(exec, cmd, &[GPIO_PATTERN])));
+
+    let cmd = MpsseCmdBuilder::with_vec(vec![BAD_OPCODE]).send_immediate();
+    checks.push((
+        "bad_command",
+        check_xfer(exec, cmd, &[BAD_COMMAND_RESPONSE, BAD_OPCODE]),
+    ));
+
+    Report { checks }
+}
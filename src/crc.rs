@@ -0,0 +1,143 @@
+//! CRC checked SPI transactions.
+use crate::{MpsseCmdBuilder, MpsseCmdExecutor, RetryError, SpiDevice};
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// CRC appended to the frames of a [`spi_checked_transaction`].
+///
+/// CRCs are computed MSB first without reflection or final XOR, and sent
+/// most significant byte first.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum CrcKind {
+    /// CRC-8/SMBUS, polynomial `0x07` with an initial value of `0x00`.
+    Crc8,
+    /// CRC-16/CCITT-FALSE, polynomial `0x1021` with an initial value of
+    /// `0xFFFF`.
+    Crc16Ccitt,
+}
+
+impl CrcKind {
+    /// Length of the CRC in bytes.
+    pub const fn byte_len(self) -> usize {
+        match self {
+            CrcKind::Crc8 => 1,
+            CrcKind::Crc16Ccitt => 2,
+        }
+    }
+
+    /// CRC of `data`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::CrcKind;
+    ///
+    /// assert_eq!(CrcKind::Crc8.checksum(b"123456789"), 0xF4);
+    /// assert_eq!(CrcKind::Crc16Ccitt.checksum(b"123456789"), 0x29B1);
+    /// ```
+    pub fn checksum(self, data: &[u8]) -> u16 {
+        let (poly, init, width): (u32, u32, u32) = match self {
+            CrcKind::Crc8 => (0x07, 0x00, 8),
+            CrcKind::Crc16Ccitt => (0x1021, 0xFFFF, 16),
+        };
+        let top = 1 << (width - 1);
+        let mask = (1 << width) - 1;
+        let crc = data.iter().fold(init, |mut crc, byte| {
+            crc ^= u32::from(*byte) << (width - 8);
+            for _ in 0..8 {
+                crc = if crc & top != 0 {
+                    (crc << 1) ^ poly
+                } else {
+                    crc << 1
+                };
+            }
+            crc & mask
+        });
+        crc as u16
+    }
+
+    /// CRC of `data` as the bytes sent on the wire.
+    fn to_bytes(self, data: &[u8]) -> Vec<u8> {
+        let crc = self.checksum(data).to_be_bytes();
+        crc[crc.len() - self.byte_len()..].to_vec()
+    }
+}
+
+/// Transfer a CRC checked SPI frame, retrying on a CRC mismatch.
+///
+/// Appends the CRC of `request` and clocks the frame out and in with a
+/// [`SpiDevice::transfer`], in the clocking modes of the device. The device
+/// answers with a frame of the same length, the response followed by its
+/// CRC; pad `request` to the response length for devices answering with more
+/// data than they receive. The transaction is repeated when the CRC of the
+/// response does not match, and the response is returned without its CRC.
+///
+/// The device must have been set up with [`SpiDevice::init`] so that SCK is
+/// at its idle level before chip select is first asserted.
+///
+/// # Arguments
+///
+/// * `device` - SPI device the frames are exchanged with.
+/// * `request` - Frame data, without the CRC.
+/// * `crc` - CRC of the request and response frames.
+/// * `retries` - Number of times the transaction is retried after the first
+///   attempt.
+///
+/// Executor errors are returned immediately, and
+/// [`RetryError::CrcMismatch`] once every attempt failed the CRC check.
+///
+/// This will panic for frames longer than `u16::MAX + 1` bytes with the CRC.
+///
+/// # Example
+///
+/// ```no_run
+/// use ftdi_mpsse::{
+///     spi_checked_transaction, CrcKind, MpsseCmdBuilder, MpsseCmdExecutor, RetryError, SpiDevice,
+///     SpiMode,
+/// };
+///
+/// # fn example<E: MpsseCmdExecutor>(mut exec: E) -> Result<(), RetryError<E::Error>> {
+/// let device = SpiDevice::new(3, SpiMode::Mode3);
+/// exec.send(device.init(MpsseCmdBuilder::new()).as_slice())
+///     .map_err(RetryError::Executor)?;
+/// // read command followed by 4 padding bytes for the response
+/// let response = spi_checked_transaction(
+///     &mut exec,
+///     &device,
+///     &[0x0B, 0x00, 0x00, 0x00, 0x00],
+///     CrcKind::Crc8,
+///     3,
+/// )?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn spi_checked_transaction<E: MpsseCmdExecutor>(
+    exec: &mut E,
+    device: &SpiDevice,
+    request: &[u8],
+    crc: CrcKind,
+    retries: usize,
+) -> Result<Vec<u8>, RetryError<E::Error>> {
+    let mut frame = request.to_vec();
+    frame.extend_from_slice(&crc.to_bytes(request));
+    assert!(
+        frame.len() <= 65536,
+        "frame length cannot exceed u16::MAX + 1"
+    );
+    let (cmd, _) = device.transfer(MpsseCmdBuilder::new(), &frame);
+    let cmd = cmd.send_immediate();
+
+    let mut response = vec![0; frame.len()];
+    for _ in 0..=retries {
+        exec.xfer(cmd.as_slice(), &mut response)
+            .map_err(RetryError::Executor)?;
+        let (data, received) = response.split_at(request.len());
+        if crc.to_bytes(data) == received {
+            response.truncate(request.len());
+            return Ok(response);
+        }
+    }
+    Err(RetryError::CrcMismatch {
+        attempts: retries + 1,
+    })
+}
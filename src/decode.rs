@@ -0,0 +1,584 @@
+//! Command stream disassembler.
+use crate::{
+    parse, ClockBits, ClockBitsIn, ClockBitsOut, ClockData, ClockDataIn, ClockDataOut, ClockTMS,
+    ClockTMSOut, MpsseCmdBuilder,
+};
+use alloc::vec::Vec;
+use core::fmt;
+use core::ops::Range;
+
+/// Command decoded by [`decode`].
+///
+/// Variants mirror the [`MpsseCmdBuilder`](crate::MpsseCmdBuilder) methods.
+/// Lengths are the number of bits, bytes or clock cycles clocked, not the
+/// encoded length minus one. Bit lengths are decoded as given even beyond
+/// the 8 bits a command can clock. Byte data is returned as the range of the
+/// data in the decoded stream.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum MpsseOp {
+    /// [`clock_data_out`](crate::MpsseCmdBuilder::clock_data_out).
+    ClockDataOut {
+        /// Clocking mode.
+        mode: ClockDataOut,
+        /// Range of the data clocked out.
+        data: Range<usize>,
+    },
+    /// [`clock_data_in`](crate::MpsseCmdBuilder::clock_data_in).
+    ClockDataIn {
+        /// Clocking mode.
+        mode: ClockDataIn,
+        /// Number of bytes clocked in.
+        len: usize,
+    },
+    /// [`clock_data`](crate::MpsseCmdBuilder::clock_data).
+    ClockData {
+        /// Clocking mode.
+        mode: ClockData,
+        /// Range of the data clocked out.
+        data: Range<usize>,
+    },
+    /// [`clock_bits_out`](crate::MpsseCmdBuilder::clock_bits_out).
+    ClockBitsOut {
+        /// Clocking mode.
+        mode: ClockBitsOut,
+        /// Data byte.
+        data: u8,
+        /// Number of bits clocked out.
+        len: u16,
+    },
+    /// [`clock_bits_in`](crate::MpsseCmdBuilder::clock_bits_in).
+    ClockBitsIn {
+        /// Clocking mode.
+        mode: ClockBitsIn,
+        /// Number of bits clocked in.
+        len: u16,
+    },
+    /// [`clock_bits`](crate::MpsseCmdBuilder::clock_bits).
+    ClockBits {
+        /// Clocking mode.
+        mode: ClockBits,
+        /// Data byte.
+        data: u8,
+        /// Number of bits clocked.
+        len: u16,
+    },
+    /// [`clock_tms_out`](crate::MpsseCmdBuilder::clock_tms_out).
+    ClockTmsOut {
+        /// Clocking mode.
+        mode: ClockTMSOut,
+        /// TMS bits.
+        data: u8,
+        /// Value placed on TDI.
+        tdi: bool,
+        /// Number of bits clocked out.
+        len: u16,
+    },
+    /// [`clock_tms`](crate::MpsseCmdBuilder::clock_tms).
+    ClockTms {
+        /// Clocking mode.
+        mode: ClockTMS,
+        /// TMS bits.
+        data: u8,
+        /// Value placed on TDI.
+        tdi: bool,
+        /// Number of bits clocked.
+        len: u16,
+    },
+    /// [`set_gpio_lower`](crate::MpsseCmdBuilder::set_gpio_lower).
+    SetGpioLower {
+        /// Pin state.
+        state: u8,
+        /// Pin direction.
+        direction: u8,
+    },
+    /// [`set_gpio_upper`](crate::MpsseCmdBuilder::set_gpio_upper).
+    SetGpioUpper {
+        /// Pin state.
+        state: u8,
+        /// Pin direction.
+        direction: u8,
+    },
+    /// [`gpio_lower`](crate::MpsseCmdBuilder::gpio_lower).
+    GpioLower,
+    /// [`gpio_upper`](crate::MpsseCmdBuilder::gpio_upper).
+    GpioUpper,
+    /// [`enable_loopback`](crate::MpsseCmdBuilder::enable_loopback).
+    EnableLoopback,
+    /// [`disable_loopback`](crate::MpsseCmdBuilder::disable_loopback).
+    DisableLoopback,
+    /// Clock divisor of [`set_clock`](crate::MpsseCmdBuilder::set_clock).
+    SetClock {
+        /// Clock divisor.
+        divisor: u16,
+    },
+    /// [`send_immediate`](crate::MpsseCmdBuilder::send_immediate).
+    SendImmediate,
+    /// [`wait_on_io_high`](crate::MpsseCmdBuilder::wait_on_io_high).
+    WaitOnIOHigh,
+    /// [`wait_on_io_low`](crate::MpsseCmdBuilder::wait_on_io_low).
+    WaitOnIOLow,
+    /// Divide by 5 disabled by [`set_clock`](crate::MpsseCmdBuilder::set_clock).
+    DisableClockDivide,
+    /// Divide by 5 enabled by [`set_clock`](crate::MpsseCmdBuilder::set_clock).
+    EnableClockDivide,
+    /// [`enable_3phase_data_clocking`](crate::MpsseCmdBuilder::enable_3phase_data_clocking).
+    Enable3PhaseClocking,
+    /// [`disable_3phase_data_clocking`](crate::MpsseCmdBuilder::disable_3phase_data_clocking).
+    Disable3PhaseClocking,
+    /// [`clock_cycles_bits`](crate::MpsseCmdBuilder::clock_cycles_bits).
+    ClockCyclesBits {
+        /// Number of clock cycles.
+        len: u16,
+    },
+    /// [`clock_cycles_bytes`](crate::MpsseCmdBuilder::clock_cycles_bytes).
+    ClockCyclesBytes {
+        /// Number of 8 clock cycle groups.
+        len: usize,
+    },
+    /// [`clock_until_io_high`](crate::MpsseCmdBuilder::clock_until_io_high).
+    ClockUntilIOHigh,
+    /// [`clock_until_io_low`](crate::MpsseCmdBuilder::clock_until_io_low).
+    ClockUntilIOLow,
+    /// [`MpsseCmd::EnableAdaptiveClocking`](crate::MpsseCmd::EnableAdaptiveClocking).
+    EnableAdaptiveClocking,
+    /// [`MpsseCmd::DisableAdaptiveClocking`](crate::MpsseCmd::DisableAdaptiveClocking).
+    DisableAdaptiveClocking,
+    /// [`clock_n8_until_io_high`](crate::MpsseCmdBuilder::clock_n8_until_io_high).
+    ClockN8UntilIOHigh {
+        /// Maximum number of 8 clock cycle groups.
+        len: usize,
+    },
+    /// [`clock_n8_until_io_low`](crate::MpsseCmdBuilder::clock_n8_until_io_low).
+    ClockN8UntilIOLow {
+        /// Maximum number of 8 clock cycle groups.
+        len: usize,
+    },
+    /// [`enable_drive_only_zero`](crate::MpsseCmdBuilder::enable_drive_only_zero).
+    EnableDriveOnlyZero {
+        /// Lower byte pins only driven low.
+        lower_mask: u8,
+        /// Upper byte pins only driven low.
+        upper_mask: u8,
+    },
+    /// Opcode the device does not recognize, answered with
+    /// [`BAD_COMMAND_RESPONSE`](crate::BAD_COMMAND_RESPONSE) and the opcode.
+    BadCommand {
+        /// Unrecognized opcode.
+        opcode: u8,
+    },
+}
+
+/// Error returned by [`decode`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum DecodeError {
+    /// The command at `offset` is cut short by the end of the stream.
+    Truncated {
+        /// Offset of the opcode.
+        offset: usize,
+    },
+    /// The data clocking opcode at `offset` has a combination of mode bits
+    /// with no clocking mode type variant.
+    UnsupportedClocking {
+        /// Offset of the opcode.
+        offset: usize,
+        /// Opcode.
+        opcode: u8,
+    },
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::Truncated { offset } => {
+                write!(f, "command at offset {} is truncated", offset)
+            }
+            DecodeError::UnsupportedClocking { offset, opcode } => write!(
+                f,
+                "unsupported data clocking opcode {:#04x} at offset {}",
+                opcode, offset
+            ),
+        }
+    }
+}
+
+impl core::error::Error for DecodeError {}
+
+fn clock_data_out(opcode: u8) -> Option<ClockDataOut> {
+    Some(match opcode {
+        0x10 => ClockDataOut::MsbPos,
+        0x11 => ClockDataOut::MsbNeg,
+        0x18 => ClockDataOut::LsbPos,
+        0x19 => ClockDataOut::LsbNeg,
+        _ => return None,
+    })
+}
+
+fn clock_bits_out(opcode: u8) -> Option<ClockBitsOut> {
+    Some(match opcode {
+        0x12 => ClockBitsOut::MsbPos,
+        0x13 => ClockBitsOut::MsbNeg,
+        0x1A => ClockBitsOut::LsbPos,
+        0x1B => ClockBitsOut::LsbNeg,
+        _ => return None,
+    })
+}
+
+fn clock_data_in(opcode: u8) -> Option<ClockDataIn> {
+    Some(match opcode {
+        0x20 => ClockDataIn::MsbPos,
+        0x24 => ClockDataIn::MsbNeg,
+        0x28 => ClockDataIn::LsbPos,
+        0x2C => ClockDataIn::LsbNeg,
+        _ => return None,
+    })
+}
+
+fn clock_bits_in(opcode: u8) -> Option<ClockBitsIn> {
+    Some(match opcode {
+        0x22 => ClockBitsIn::MsbPos,
+        0x26 => ClockBitsIn::MsbNeg,
+        0x2A => ClockBitsIn::LsbPos,
+        0x2E => ClockBitsIn::LsbNeg,
+        _ => return None,
+    })
+}
+
+fn clock_data(opcode: u8) -> Option<ClockData> {
+    Some(match opcode {
+        0x31 => ClockData::MsbPosIn,
+        0x34 => ClockData::MsbNegIn,
+        0x39 => ClockData::LsbPosIn,
+        0x3C => ClockData::LsbNegIn,
+        _ => return None,
+    })
+}
+
+fn clock_bits(opcode: u8) -> Option<ClockBits> {
+    Some(match opcode {
+        0x33 => ClockBits::MsbPosIn,
+        0x36 => ClockBits::MsbNegIn,
+        0x3B => ClockBits::LsbPosIn,
+        0x3E => ClockBits::LsbNegIn,
+        _ => return None,
+    })
+}
+
+fn clock_tms_out(opcode: u8) -> Option<ClockTMSOut> {
+    Some(match opcode {
+        0x4A => ClockTMSOut::PosEdge,
+        0x4B => ClockTMSOut::NegEdge,
+        _ => return None,
+    })
+}
+
+fn clock_tms(opcode: u8) -> Option<ClockTMS> {
+    Some(match opcode {
+        0x6A => ClockTMS::PosTMSPosTDO,
+        0x6E => ClockTMS::PosTMSNegTDO,
+        0x6B => ClockTMS::NegTMSPosTDO,
+        0x6F => ClockTMS::NegTMSNegTDO,
+        _ => return None,
+    })
+}
+
+/// Decode a data clocking command.
+fn decode_clocking(offset: usize, bytes: &[u8]) -> Option<MpsseOp> {
+    let opcode = bytes[0];
+    if opcode & 0x02 != 0 {
+        let len = u16::from(bytes[1]) + 1;
+        let data = bytes.get(2).copied().unwrap_or(0);
+        let (tms, tdi) = (data & 0x7F, data & 0x80 != 0);
+        clock_bits_out(opcode)
+            .map(|mode| MpsseOp::ClockBitsOut { mode, data, len })
+            .or_else(|| clock_bits_in(opcode).map(|mode| MpsseOp::ClockBitsIn { mode, len }))
+            .or_else(|| clock_bits(opcode).map(|mode| MpsseOp::ClockBits { mode, data, len }))
+            .or_else(|| {
+                clock_tms_out(opcode).map(|mode| MpsseOp::ClockTmsOut {
+                    mode,
+                    data: tms,
+                    tdi,
+                    len,
+                })
+            })
+            .or_else(|| {
+                clock_tms(opcode).map(|mode| MpsseOp::ClockTms {
+                    mode,
+                    data: tms,
+                    tdi,
+                    len,
+                })
+            })
+    } else {
+        let len = (usize::from(bytes[1]) | usize::from(bytes[2]) << 8) + 1;
+        let data = offset + 3..offset + bytes.len();
+        clock_data_out(opcode)
+            .map(|mode| MpsseOp::ClockDataOut {
+                mode,
+                data: data.clone(),
+            })
+            .or_else(|| clock_data_in(opcode).map(|mode| MpsseOp::ClockDataIn { mode, len }))
+            .or_else(|| clock_data(opcode).map(|mode| MpsseOp::ClockData { mode, data }))
+    }
+}
+
+/// Decode a command stream.
+///
+/// Returns the commands of the stream in order. Data clocked out is returned
+/// as ranges of `bytes`. Opcodes the device does not recognize are decoded
+/// as [`MpsseOp::BadCommand`], as streams synchronizing with the device send
+/// them deliberately.
+///
+/// # Example
+///
+/// ```
+/// use ftdi_mpsse::{decode, ClockDataOut, MpsseCmdBuilder, MpsseOp};
+///
+/// let cmd = MpsseCmdBuilder::new()
+///     .set_gpio_lower(0x00, 0x0B)
+///     .clock_data_out(ClockDataOut::MsbNeg, &[0x12, 0x34])
+///     .send_immediate();
+/// assert_eq!(
+///     decode(cmd.as_slice()),
+///     Ok(vec![
+///         MpsseOp::SetGpioLower {
+///             state: 0x00,
+///             direction: 0x0B,
+///         },
+///         MpsseOp::ClockDataOut {
+///             mode: ClockDataOut::MsbNeg,
+///             data: 6..8,
+///         },
+///         MpsseOp::SendImmediate,
+///     ])
+/// );
+/// ```
+pub fn decode(bytes: &[u8]) -> Result<Vec<MpsseOp>, DecodeError> {
+    let len16 = |bytes: &[u8]| (usize::from(bytes[1]) | usize::from(bytes[2]) << 8) + 1;
+    let mut ops = Vec::new();
+    for cmd in parse::commands(bytes) {
+        let cmd = cmd.map_err(|truncated| DecodeError::Truncated {
+            offset: truncated.offset,
+        })?;
+        let opcode = cmd.bytes[0];
+        let op = match opcode {
+            op if parse::is_clocking(op) => {
+                decode_clocking(cmd.offset, cmd.bytes).ok_or(DecodeError::UnsupportedClocking {
+                    offset: cmd.offset,
+                    opcode,
+                })?
+            }
+            op if parse::is_bad_command(op) => MpsseOp::BadCommand { opcode },
+            0x80 => MpsseOp::SetGpioLower {
+                state: cmd.bytes[1],
+                direction: cmd.bytes[2],
+            },
+            0x81 => MpsseOp::GpioLower,
+            0x82 => MpsseOp::SetGpioUpper {
+                state: cmd.bytes[1],
+                direction: cmd.bytes[2],
+            },
+            0x83 => MpsseOp::GpioUpper,
+            0x84 => MpsseOp::EnableLoopback,
+            0x85 => MpsseOp::DisableLoopback,
+            0x86 => MpsseOp::SetClock {
+                divisor: u16::from(cmd.bytes[1]) | u16::from(cmd.bytes[2]) << 8,
+            },
+            0x87 => MpsseOp::SendImmediate,
+            0x88 => MpsseOp::WaitOnIOHigh,
+            0x89 => MpsseOp::WaitOnIOLow,
+            0x8A => MpsseOp::DisableClockDivide,
+            0x8B => MpsseOp::EnableClockDivide,
+            0x8C => MpsseOp::Enable3PhaseClocking,
+            0x8D => MpsseOp::Disable3PhaseClocking,
+            0x8E => MpsseOp::ClockCyclesBits {
+                len: u16::from(cmd.bytes[1]) + 1,
+            },
+            0x8F => MpsseOp::ClockCyclesBytes {
+                len: len16(cmd.bytes),
+            },
+            0x94 => MpsseOp::ClockUntilIOHigh,
+            0x95 => MpsseOp::ClockUntilIOLow,
+            0x96 => MpsseOp::EnableAdaptiveClocking,
+            0x97 => MpsseOp::DisableAdaptiveClocking,
+            0x9C => MpsseOp::ClockN8UntilIOHigh {
+                len: len16(cmd.bytes),
+            },
+            0x9D => MpsseOp::ClockN8UntilIOLow {
+                len: len16(cmd.bytes),
+            },
+            0x9E => MpsseOp::EnableDriveOnlyZero {
+                lower_mask: cmd.bytes[1],
+                upper_mask: cmd.bytes[2],
+            },
+            _ => unreachable!("opcode {:#04x} is a bad command", opcode),
+        };
+        ops.push(op);
+    }
+    Ok(ops)
+}
+
+/// Write bytes as space separated hex.
+fn write_hex(f: &mut fmt::Formatter<'_>, bytes: &[u8]) -> fmt::Result {
+    for (i, byte) in bytes.iter().enumerate() {
+        if i > 0 {
+            f.write_str(" ")?;
+        }
+        write!(f, "{:02X}", byte)?;
+    }
+    Ok(())
+}
+
+/// Write byte data clocked out, with its length.
+fn write_data(f: &mut fmt::Formatter<'_>, data: &[u8]) -> fmt::Result {
+    write!(f, "{} bytes: ", data.len())?;
+    write_hex(f, data)
+}
+
+impl MpsseOp {
+    /// Write the operation as a builder method call.
+    ///
+    /// `bytes` is the stream the operation was decoded from.
+    fn write(&self, f: &mut fmt::Formatter<'_>, bytes: &[u8]) -> fmt::Result {
+        match self {
+            MpsseOp::ClockDataOut { mode, data } => {
+                write!(f, "clock_data_out({:?}, ", mode)?;
+                write_data(f, &bytes[data.clone()])?;
+                f.write_str(")")
+            }
+            MpsseOp::ClockDataIn { mode, len } => {
+                write!(f, "clock_data_in({:?}, {} bytes)", mode, len)
+            }
+            MpsseOp::ClockData { mode, data } => {
+                write!(f, "clock_data({:?}, ", mode)?;
+                write_data(f, &bytes[data.clone()])?;
+                f.write_str(")")
+            }
+            MpsseOp::ClockBitsOut { mode, data, len } => write!(
+                f,
+                "clock_bits_out({:?}, data={:#04X}, {} bits)",
+                mode, data, len
+            ),
+            MpsseOp::ClockBitsIn { mode, len } => {
+                write!(f, "clock_bits_in({:?}, {} bits)", mode, len)
+            }
+            MpsseOp::ClockBits { mode, data, len } => write!(
+                f,
+                "clock_bits({:?}, data={:#04X}, {} bits)",
+                mode, data, len
+            ),
+            MpsseOp::ClockTmsOut {
+                mode,
+                data,
+                tdi,
+                len,
+            } => write!(
+                f,
+                "clock_tms_out({:?}, data={:#04X}, tdi={}, {} bits)",
+                mode, data, tdi, len
+            ),
+            MpsseOp::ClockTms {
+                mode,
+                data,
+                tdi,
+                len,
+            } => write!(
+                f,
+                "clock_tms({:?}, data={:#04X}, tdi={}, {} bits)",
+                mode, data, tdi, len
+            ),
+            MpsseOp::SetGpioLower { state, direction } => write!(
+                f,
+                "set_gpio_lower(state={:#04X}, dir={:#04X})",
+                state, direction
+            ),
+            MpsseOp::SetGpioUpper { state, direction } => write!(
+                f,
+                "set_gpio_upper(state={:#04X}, dir={:#04X})",
+                state, direction
+            ),
+            MpsseOp::GpioLower => f.write_str("gpio_lower()"),
+            MpsseOp::GpioUpper => f.write_str("gpio_upper()"),
+            MpsseOp::EnableLoopback => f.write_str("enable_loopback()"),
+            MpsseOp::DisableLoopback => f.write_str("disable_loopback()"),
+            MpsseOp::SetClock { divisor } => write!(f, "set_clock(divisor={})", divisor),
+            MpsseOp::SendImmediate => f.write_str("send_immediate()"),
+            MpsseOp::WaitOnIOHigh => f.write_str("wait_on_io_high()"),
+            MpsseOp::WaitOnIOLow => f.write_str("wait_on_io_low()"),
+            MpsseOp::DisableClockDivide => f.write_str("disable_clock_divide()"),
+            MpsseOp::EnableClockDivide => f.write_str("enable_clock_divide()"),
+            MpsseOp::Enable3PhaseClocking => f.write_str("enable_3phase_data_clocking()"),
+            MpsseOp::Disable3PhaseClocking => f.write_str("disable_3phase_data_clocking()"),
+            MpsseOp::ClockCyclesBits { len } => write!(f, "clock_cycles_bits({} cycles)", len),
+            MpsseOp::ClockCyclesBytes { len } => {
+                write!(f, "clock_cycles_bytes({} x 8 cycles)", len)
+            }
+            MpsseOp::ClockUntilIOHigh => f.write_str("clock_until_io_high()"),
+            MpsseOp::ClockUntilIOLow => f.write_str("clock_until_io_low()"),
+            MpsseOp::EnableAdaptiveClocking => f.write_str("enable_adaptive_data_clocking()"),
+            MpsseOp::DisableAdaptiveClocking => f.write_str("disable_adaptive_data_clocking()"),
+            MpsseOp::ClockN8UntilIOHigh { len } => {
+                write!(f, "clock_n8_until_io_high(max {} x 8 cycles)", len)
+            }
+            MpsseOp::ClockN8UntilIOLow { len } => {
+                write!(f, "clock_n8_until_io_low(max {} x 8 cycles)", len)
+            }
+            MpsseOp::EnableDriveOnlyZero {
+                lower_mask,
+                upper_mask,
+            } => write!(
+                f,
+                "enable_drive_only_zero(lower={:#04X}, upper={:#04X})",
+                lower_mask, upper_mask
+            ),
+            MpsseOp::BadCommand { opcode } => write!(f, "bad_command({:#04X})", opcode),
+        }
+    }
+}
+
+/// Lists the commands of the stream, one per line, as builder method calls.
+///
+/// Bytes that cannot be decoded, such as a truncated command at the end of
+/// the stream, are listed as raw hex.
+///
+/// # Example
+///
+/// ```
+/// use ftdi_mpsse::{ClockDataOut, MpsseCmdBuilder};
+///
+/// let cmd = MpsseCmdBuilder::new()
+///     .set_gpio_lower(0xFA, 0xFB)
+///     .clock_data_out(ClockDataOut::MsbNeg, &[0x12, 0x34, 0x56])
+///     .append_slice(&[0x80, 0x00]);
+/// assert_eq!(
+///     cmd.to_string(),
+///     "set_gpio_lower(state=0xFA, dir=0xFB)\n\
+///      clock_data_out(MsbNeg, 3 bytes: 12 34 56)\n\
+///      raw(80 00)"
+/// );
+/// ```
+impl fmt::Display for MpsseCmdBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, cmd) in parse::commands(self.as_slice()).enumerate() {
+            if i > 0 {
+                f.write_str("\n")?;
+            }
+            let raw = match cmd {
+                Ok(cmd) => match decode(cmd.bytes) {
+                    Ok(ops) => {
+                        for op in ops {
+                            op.write(f, cmd.bytes)?;
+                        }
+                        continue;
+                    }
+                    Err(_) => cmd.bytes,
+                },
+                Err(truncated) => &self.as_slice()[truncated.offset..],
+            };
+            f.write_str("raw(")?;
+            write_hex(f, raw)?;
+            f.write_str(")")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,109 @@
+//! Default data clocking modes of a command builder.
+use crate::{ClockData, ClockDataIn, ClockDataOut, MpsseCmdBuilder};
+
+/// Command builder with default data clocking modes, returned by
+/// [`with_default_mode`](MpsseCmdBuilder::with_default_mode).
+///
+/// Other commands are added to the wrapped [`MpsseCmdBuilder`] with
+/// [`map`](DefaultModeBuilder::map).
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct DefaultModeBuilder {
+    cmd: MpsseCmdBuilder,
+    out: ClockDataOut,
+    in_: ClockDataIn,
+    both: ClockData,
+}
+
+impl MpsseCmdBuilder {
+    /// Set the modes used by the data clocking methods without a mode
+    /// argument.
+    ///
+    /// The defaults do not emit any bytes, they only parameterize
+    /// [`clock_data_out_default`], [`clock_data_in_default`] and
+    /// [`clock_data_default`] of the returned builder.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::{ClockData, ClockDataIn, ClockDataOut, MpsseCmdBuilder};
+    ///
+    /// let cmd = MpsseCmdBuilder::new()
+    ///     .with_default_mode(ClockDataOut::MsbNeg, ClockDataIn::MsbPos, ClockData::MsbPosIn)
+    ///     .clock_data_out_default(&[0x9F])
+    ///     .clock_data_in_default(3);
+    /// assert_eq!(
+    ///     cmd.inner().as_slice(),
+    ///     MpsseCmdBuilder::new()
+    ///         .clock_data_out(ClockDataOut::MsbNeg, &[0x9F])
+    ///         .clock_data_in(ClockDataIn::MsbPos, 3)
+    ///         .as_slice()
+    /// );
+    /// ```
+    ///
+    /// [`clock_data_out_default`]: DefaultModeBuilder::clock_data_out_default
+    /// [`clock_data_in_default`]: DefaultModeBuilder::clock_data_in_default
+    /// [`clock_data_default`]: DefaultModeBuilder::clock_data_default
+    pub fn with_default_mode(
+        self,
+        out: ClockDataOut,
+        in_: ClockDataIn,
+        both: ClockData,
+    ) -> DefaultModeBuilder {
+        DefaultModeBuilder {
+            cmd: self,
+            out,
+            in_,
+            both,
+        }
+    }
+}
+
+impl DefaultModeBuilder {
+    /// Replace the default modes for the following commands.
+    pub fn with_default_mode(self, out: ClockDataOut, in_: ClockDataIn, both: ClockData) -> Self {
+        self.cmd.with_default_mode(out, in_, both)
+    }
+
+    /// Add commands with the methods of the wrapped builder.
+    pub fn map(mut self, f: impl FnOnce(MpsseCmdBuilder) -> MpsseCmdBuilder) -> Self {
+        self.cmd = f(self.cmd);
+        self
+    }
+
+    /// [`clock_data_out`](MpsseCmdBuilder::clock_data_out) with the default
+    /// mode.
+    pub fn clock_data_out_default(mut self, data: &[u8]) -> Self {
+        self.cmd = self.cmd.clock_data_out(self.out, data);
+        self
+    }
+
+    /// [`clock_data_in`](MpsseCmdBuilder::clock_data_in) with the default
+    /// mode.
+    pub fn clock_data_in_default(mut self, len: usize) -> Self {
+        self.cmd = self.cmd.clock_data_in(self.in_, len);
+        self
+    }
+
+    /// [`clock_data`](MpsseCmdBuilder::clock_data) with the default mode.
+    pub fn clock_data_default(mut self, data: &[u8]) -> Self {
+        self.cmd = self.cmd.clock_data(self.both, data);
+        self
+    }
+
+    /// Get a reference to the wrapped builder.
+    pub fn inner(&self) -> &MpsseCmdBuilder {
+        &self.cmd
+    }
+
+    /// Unwrap the builder, dropping the default modes.
+    pub fn into_inner(self) -> MpsseCmdBuilder {
+        self.cmd
+    }
+}
+
+/// Drops the default modes.
+impl From<DefaultModeBuilder> for MpsseCmdBuilder {
+    fn from(cmd: DefaultModeBuilder) -> Self {
+        cmd.cmd
+    }
+}
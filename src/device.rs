@@ -0,0 +1,97 @@
+//! Capabilities of the FTDI devices with an MPSSE.
+use crate::MpsseCmdBuilder;
+
+/// FTDI device with an MPSSE.
+///
+/// Not every MPSSE command is supported by every device, and unsupported
+/// commands are often silently ignored. The capability methods tell which
+/// optional features a device has.
+///
+/// | Device  | Upper GPIO | 3-phase clocking | Adaptive clocking | Base clock |
+/// |---------|------------|------------------|-------------------|------------|
+/// | FT232H  | yes        | yes              | yes               | 60 MHz     |
+/// | FT2232H | yes        | yes              | yes               | 60 MHz     |
+/// | FT4232H | no         | yes              | yes               | 60 MHz     |
+/// | FT2232D | yes        | no               | no                | 12 MHz     |
+///
+/// # Example
+///
+/// ```
+/// use ftdi_mpsse::{FtdiDevice, MpsseCmdBuilder};
+///
+/// let device = FtdiDevice::Ft2232d;
+/// let mut cmd = MpsseCmdBuilder::new().set_clock_hz_for(1_000_000, device);
+/// if device.supports_3phase() {
+///     cmd = cmd.enable_3phase_data_clocking();
+/// }
+/// assert_eq!(cmd.as_slice(), [0x86, 0x05, 0x00]);
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum FtdiDevice {
+    /// FT232H, single channel.
+    Ft232h,
+    /// FT2232H, MPSSE on channels A and B.
+    Ft2232h,
+    /// FT4232H, MPSSE on channels A and B.
+    Ft4232h,
+    /// FT2232D, MPSSE on channel A.
+    Ft2232d,
+}
+
+impl FtdiDevice {
+    /// Returns `true` if the MPSSE has upper byte GPIO pins, see
+    /// [`set_gpio_upper`](MpsseCmdBuilder::set_gpio_upper).
+    ///
+    /// The FT4232H channels only have the lower byte pins, and the FT2232D
+    /// only has the lower 4 pins of the upper byte.
+    pub const fn has_upper_gpio(self) -> bool {
+        !matches!(self, FtdiDevice::Ft4232h)
+    }
+
+    /// Returns `true` if the MPSSE supports 3-phase data clocking, see
+    /// [`enable_3phase_data_clocking`](MpsseCmdBuilder::enable_3phase_data_clocking).
+    pub const fn supports_3phase(self) -> bool {
+        !matches!(self, FtdiDevice::Ft2232d)
+    }
+
+    /// Returns `true` if the MPSSE supports adaptive clocking, see
+    /// [`enable_adaptive_data_clocking`](MpsseCmdBuilder::enable_adaptive_data_clocking).
+    pub const fn supports_adaptive(self) -> bool {
+        !matches!(self, FtdiDevice::Ft2232d)
+    }
+
+    /// Base clock of the MPSSE in Hz, before the clock divisor.
+    pub const fn base_clock_hz(self) -> u32 {
+        match self {
+            FtdiDevice::Ft2232d => 12_000_000,
+            _ => 60_000_000,
+        }
+    }
+
+    /// Highest MPSSE clock frequency in Hz, with a clock divisor of 0.
+    pub const fn max_clock_hz(self) -> u32 {
+        self.base_clock_hz() / 2
+    }
+}
+
+impl MpsseCmdBuilder {
+    /// Set the MPSSE clock frequency nearest to a target frequency, for the
+    /// base clock of `device`.
+    ///
+    /// See [`set_clock_hz`](MpsseCmdBuilder::set_clock_hz).
+    ///
+    /// This will panic if `target_hz` is zero.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::{FtdiDevice, MpsseCmdBuilder};
+    ///
+    /// let cmd = MpsseCmdBuilder::new().set_clock_hz_for(10_000_000, FtdiDevice::Ft232h);
+    /// assert_eq!(cmd.as_slice(), [0x8A, 0x86, 0x02, 0x00]);
+    /// ```
+    pub fn set_clock_hz_for(self, target_hz: u32, device: FtdiDevice) -> Self {
+        self.set_clock_hz(target_hz, device.base_clock_hz())
+    }
+}
@@ -0,0 +1,117 @@
+//! Executor decorator framing every command stream.
+use crate::{parse, MpsseCmdBuilder, MpsseCmdExecutor, MpsseSettings};
+use alloc::vec::Vec;
+
+/// Executor decorator wrapping every command stream in a prologue and an
+/// epilogue.
+///
+/// This centralizes per-transaction framing such as chip select management,
+/// so individual command streams do not have to repeat it.
+///
+/// # Example
+///
+/// ```no_run
+/// use ftdi_mpsse::{ClockData, FramedExecutor, MpsseCmdBuilder, MpsseCmdExecutor};
+///
+/// # fn example<E: MpsseCmdExecutor>(exec: E) -> Result<(), E::Error> {
+/// let mut spi = FramedExecutor::new(
+///     exec,
+///     MpsseCmdBuilder::new().set_gpio_lower(0x00, 0x0B),
+///     MpsseCmdBuilder::new().set_gpio_lower(0x08, 0x0B),
+/// );
+/// let cmd = MpsseCmdBuilder::new()
+///     .clock_data(ClockData::MsbPosIn, &[0x9F, 0x00, 0x00])
+///     .send_immediate();
+/// let mut id = [0; 3];
+/// spi.xfer(cmd.as_slice(), &mut id)?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct FramedExecutor<E> {
+    inner: E,
+    prologue: MpsseCmdBuilder,
+    epilogue: MpsseCmdBuilder,
+}
+
+impl<E> FramedExecutor<E> {
+    /// Wrap an executor.
+    ///
+    /// This will panic if the prologue or epilogue read data from the device,
+    /// since that would shift the response of every transaction.
+    pub fn new(
+        inner: E,
+        prologue: MpsseCmdBuilder,
+        epilogue: MpsseCmdBuilder,
+    ) -> FramedExecutor<E> {
+        assert!(
+            parse::read_len(prologue.as_slice()) == 0 && parse::read_len(epilogue.as_slice()) == 0,
+            "prologue and epilogue cannot read data"
+        );
+        FramedExecutor {
+            inner,
+            prologue,
+            epilogue,
+        }
+    }
+
+    /// Commands sent before every command stream.
+    pub fn prologue(&self) -> &MpsseCmdBuilder {
+        &self.prologue
+    }
+
+    /// Commands sent after every command stream.
+    pub fn epilogue(&self) -> &MpsseCmdBuilder {
+        &self.epilogue
+    }
+
+    /// Get a reference to the wrapped executor.
+    pub fn inner(&self) -> &E {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the wrapped executor.
+    pub fn inner_mut(&mut self) -> &mut E {
+        &mut self.inner
+    }
+
+    /// Unwrap the executor.
+    pub fn into_inner(self) -> E {
+        self.inner
+    }
+
+    fn frame(&self, data: &[u8]) -> Vec<u8> {
+        let mut framed = Vec::with_capacity(
+            self.prologue.as_slice().len() + data.len() + self.epilogue.as_slice().len(),
+        );
+        framed.extend_from_slice(self.prologue.as_slice());
+        framed.extend_from_slice(data);
+        framed.extend_from_slice(self.epilogue.as_slice());
+        framed
+    }
+}
+
+impl<E: MpsseCmdExecutor> MpsseCmdExecutor for FramedExecutor<E> {
+    type Error = E::Error;
+
+    fn init(&mut self, settings: &MpsseSettings) -> Result<(), Self::Error> {
+        self.inner.init(settings)
+    }
+
+    fn send(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        let framed = self.frame(data);
+        self.inner.send(&framed)
+    }
+
+    fn recv(&mut self, data: &mut [u8]) -> Result<(), Self::Error> {
+        self.inner.recv(data)
+    }
+
+    fn xfer(&mut self, txdata: &[u8], rxdata: &mut [u8]) -> Result<(), Self::Error> {
+        let framed = self.frame(txdata);
+        self.inner.xfer(&framed, rxdata)
+    }
+
+    fn purge(&mut self) -> Result<(), Self::Error> {
+        self.inner.purge()
+    }
+}
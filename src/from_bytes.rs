@@ -0,0 +1,73 @@
+//! Conversion of data read from the device into fixed layout types.
+
+/// Types that can be built from the raw bytes read from the device.
+///
+/// Used by [`read_struct`](crate::MpsseCmdExecutor::read_struct). Bytes are
+/// reinterpreted in host byte order.
+///
+/// Implemented for the primitive integer types and byte arrays. With the
+/// `bytemuck` feature enabled, any `bytemuck::Pod` type can be read through
+/// the `PodValue` wrapper.
+pub trait FromBytes: Sized {
+    /// Number of bytes needed to build a value.
+    const SIZE: usize;
+
+    /// Build a value from exactly [`SIZE`](FromBytes::SIZE) bytes.
+    ///
+    /// This will panic if `bytes` has the wrong length.
+    fn from_bytes(bytes: &[u8]) -> Self;
+}
+
+macro_rules! impl_from_bytes {
+    ($($ty:ty),*) => {
+        $(
+            impl FromBytes for $ty {
+                const SIZE: usize = core::mem::size_of::<$ty>();
+
+                fn from_bytes(bytes: &[u8]) -> Self {
+                    let mut raw = [0; core::mem::size_of::<$ty>()];
+                    raw.copy_from_slice(bytes);
+                    <$ty>::from_ne_bytes(raw)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_bytes!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+impl<const N: usize> FromBytes for [u8; N] {
+    const SIZE: usize = N;
+
+    fn from_bytes(bytes: &[u8]) -> Self {
+        let mut raw = [0; N];
+        raw.copy_from_slice(bytes);
+        raw
+    }
+}
+
+/// Wrapper reading any [`bytemuck::Pod`] type with
+/// [`read_struct`](crate::MpsseCmdExecutor::read_struct).
+///
+/// # Example
+///
+/// ```no_run
+/// use ftdi_mpsse::{ClockDataIn, MpsseCmdExecutor, PodValue};
+/// # fn f<E: MpsseCmdExecutor>(exec: &mut E) -> Result<(), E::Error> {
+/// let PodValue(samples) = exec.read_struct::<PodValue<[u16; 4]>>(ClockDataIn::MsbNeg)?;
+/// # let _ = samples;
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "bytemuck")]
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub struct PodValue<T>(pub T);
+
+#[cfg(feature = "bytemuck")]
+impl<T: bytemuck::Pod> FromBytes for PodValue<T> {
+    const SIZE: usize = core::mem::size_of::<T>();
+
+    fn from_bytes(bytes: &[u8]) -> Self {
+        PodValue(bytemuck::pod_read_unaligned(bytes))
+    }
+}
@@ -0,0 +1,140 @@
+//! GPIO state tracking and test patterns.
+use crate::{pin_mask, MpsseCmdBuilder, ReadToken};
+use alloc::vec::Vec;
+
+/// Pin numbers of the bits set in a lower byte GPIO mask.
+fn pins(mask: u8) -> impl Iterator<Item = u8> {
+    (0..8).filter(move |pin| mask >> pin & 0x01 != 0)
+}
+
+impl MpsseCmdBuilder {
+    /// Drive a walking ones pattern on the lower byte GPIO pins.
+    ///
+    /// For each pin of `pin_mask`, from the lowest, drives that pin high and
+    /// the other pins of the mask low, then reads the pins back. The pins
+    /// are driven low at the end. Returns a token per step, check the
+    /// readings with [`gpio_walking_ones_failures`].
+    ///
+    /// Pins outside of `pin_mask` are set as inputs.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::{gpio_walking_ones_failures, MpsseCmdBuilder};
+    ///
+    /// let (cmd, tokens) = MpsseCmdBuilder::new().gpio_walking_ones(0xF0);
+    /// let cmd = cmd.send_immediate();
+    /// assert_eq!(tokens.len(), 4);
+    ///
+    /// // pin 6 stuck low
+    /// let response = [0x10, 0x20, 0x00, 0x80];
+    /// assert_eq!(gpio_walking_ones_failures(0xF0, &response), vec![6]);
+    /// ```
+    ///
+    /// [`gpio_walking_ones_failures`]: crate::gpio_walking_ones_failures
+    pub fn gpio_walking_ones(mut self, pin_mask: u8) -> (Self, Vec<ReadToken>) {
+        let mut tokens = Vec::with_capacity(pin_mask.count_ones() as usize);
+        for pin in pins(pin_mask) {
+            self = self.set_gpio_lower(1 << pin, pin_mask);
+            tokens.push(self.read_token(1));
+            self = self.gpio_lower();
+        }
+        (self.set_gpio_lower(0x00, pin_mask), tokens)
+    }
+}
+
+/// Check the readings of
+/// [`gpio_walking_ones`](MpsseCmdBuilder::gpio_walking_ones).
+///
+/// `readings` holds the byte covered by each token, in order. Only the pins
+/// of `pin_mask` are compared. Returns the pin numbers of the steps where
+/// the pins did not read back as driven, or that have no reading.
+pub fn gpio_walking_ones_failures(pin_mask: u8, readings: &[u8]) -> Vec<u8> {
+    pins(pin_mask)
+        .enumerate()
+        .filter(|&(step, pin)| {
+            readings.get(step).map(|reading| reading & pin_mask) != Some(1 << pin)
+        })
+        .map(|(_, pin)| pin)
+        .collect()
+}
+
+/// Value and direction of a byte of GPIO pins.
+///
+/// [`set_gpio_lower`](MpsseCmdBuilder::set_gpio_lower) and
+/// [`set_gpio_upper`](MpsseCmdBuilder::set_gpio_upper) set all 8 pins at
+/// once. Keeping the port state in a `GpioState` allows changing a single
+/// pin and then applying the whole byte.
+///
+/// Pin numbers are 0-7, the methods taking a pin will panic for greater
+/// pin numbers.
+///
+/// # Example
+///
+/// ```
+/// use ftdi_mpsse::{GpioState, MpsseCmdBuilder};
+///
+/// let mut gpio = GpioState::default();
+/// gpio.set_output(3);
+/// gpio.write_pin(3, true);
+/// gpio.set_output(4);
+/// let cmd = gpio.apply_lower(MpsseCmdBuilder::new());
+///
+/// gpio.write_pin(3, false);
+/// let cmd = gpio.apply_lower(cmd);
+/// assert_eq!(cmd.as_slice(), [0x80, 0x08, 0x18, 0x80, 0x00, 0x18]);
+/// ```
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub struct GpioState {
+    /// Pin levels, bit `n` for pin `n`.
+    pub value: u8,
+    /// Pin directions, `1` for output.
+    pub direction: u8,
+}
+
+impl GpioState {
+    /// Create a GPIO state.
+    pub const fn new(value: u8, direction: u8) -> GpioState {
+        GpioState { value, direction }
+    }
+
+    /// Set a pin as an output.
+    pub fn set_output(&mut self, pin: u8) {
+        self.direction |= pin_mask(pin);
+    }
+
+    /// Set a pin as an input.
+    pub fn set_input(&mut self, pin: u8) {
+        self.direction &= !pin_mask(pin);
+    }
+
+    /// Set the level of a pin.
+    ///
+    /// The level is only driven while the pin is an output.
+    pub fn write_pin(&mut self, pin: u8, high: bool) {
+        let mask = pin_mask(pin);
+        if high {
+            self.value |= mask;
+        } else {
+            self.value &= !mask;
+        }
+    }
+
+    /// Level of a pin in the tracked state.
+    ///
+    /// This is the value written, not a reading of the device, see
+    /// [`gpio_lower`](MpsseCmdBuilder::gpio_lower) to read the pins.
+    pub fn read_pin(&self, pin: u8) -> bool {
+        self.value & pin_mask(pin) != 0
+    }
+
+    /// Set the lower byte GPIO pins to the state.
+    pub fn apply_lower(&self, cmd: MpsseCmdBuilder) -> MpsseCmdBuilder {
+        cmd.set_gpio_lower(self.value, self.direction)
+    }
+
+    /// Set the upper byte GPIO pins to the state.
+    pub fn apply_upper(&self, cmd: MpsseCmdBuilder) -> MpsseCmdBuilder {
+        cmd.set_gpio_upper(self.value, self.direction)
+    }
+}
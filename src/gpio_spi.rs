@@ -0,0 +1,174 @@
+//! SPI bit-banged over lower byte GPIO pins.
+use crate::{pin_mask, MpsseCmdBuilder, ReadToken, SpiMode};
+use alloc::vec::Vec;
+
+/// SPI master bit-banged over arbitrary lower byte GPIO pins.
+///
+/// Every clock edge is a [`set_gpio_lower`] command and every MISO sample a
+/// [`gpio_lower`] read, so this works with any wiring, at a cost: each bit
+/// takes around 10 bytes of commands and each byte read returns 8 bytes of
+/// samples. Expect throughput in the tens of kbit/s, orders of magnitude
+/// below the hardware clocking used by [`SpiDevice`](crate::SpiDevice),
+/// which should be preferred whenever SCK, MOSI and MISO are on AD0, AD1 and
+/// AD2.
+///
+/// Chip select is active low.
+///
+/// # Example
+///
+/// ```
+/// use ftdi_mpsse::{GpioSpi, MpsseCmdBuilder, SpiMode};
+///
+/// let spi = GpioSpi::new(4, 5, 6, 7, SpiMode::Mode0);
+/// let cmd = spi.init(MpsseCmdBuilder::new());
+/// let (cmd, token) = spi.transfer(cmd, &[0x9F, 0x00]);
+/// let cmd = cmd.send_immediate();
+///
+/// // 8 samples per byte
+/// let response = vec![0x40; token.len()];
+/// assert_eq!(spi.decode(token.get(&response)), [0xFF, 0xFF]);
+/// ```
+///
+/// [`set_gpio_lower`]: MpsseCmdBuilder::set_gpio_lower
+/// [`gpio_lower`]: MpsseCmdBuilder::gpio_lower
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct GpioSpi {
+    sck: u8,
+    mosi: u8,
+    miso: u8,
+    cs: u8,
+    mode: SpiMode,
+    lsb_first: bool,
+}
+
+impl GpioSpi {
+    /// Create a bit-banged SPI master, MSB first.
+    ///
+    /// # Arguments
+    ///
+    /// * `sck` - Lower byte GPIO pin number of SCK.
+    /// * `mosi` - Lower byte GPIO pin number of MOSI.
+    /// * `miso` - Lower byte GPIO pin number of MISO.
+    /// * `cs` - Lower byte GPIO pin number of the chip select.
+    /// * `mode` - SPI mode of the device.
+    ///
+    /// This will panic for pin numbers greater than 7.
+    pub fn new(sck: u8, mosi: u8, miso: u8, cs: u8, mode: SpiMode) -> GpioSpi {
+        GpioSpi {
+            sck: pin_mask(sck),
+            mosi: pin_mask(mosi),
+            miso: pin_mask(miso),
+            cs: pin_mask(cs),
+            mode,
+            lsb_first: false,
+        }
+    }
+
+    /// Set the bit order, MSB first by default.
+    pub fn lsb_first(mut self, lsb_first: bool) -> GpioSpi {
+        self.lsb_first = lsb_first;
+        self
+    }
+
+    /// SPI mode of the device.
+    pub fn mode(&self) -> SpiMode {
+        self.mode
+    }
+
+    fn direction(&self) -> u8 {
+        self.sck | self.mosi | self.cs
+    }
+
+    fn clock(&self, active: bool) -> u8 {
+        if active != self.mode.idle_clock_high() {
+            self.sck
+        } else {
+            0x00
+        }
+    }
+
+    /// Bits of a byte in the order they are clocked.
+    fn bits(&self, byte: u8) -> impl Iterator<Item = bool> {
+        let lsb_first = self.lsb_first;
+        (0..8).map(move |i| {
+            let bit = if lsb_first { i } else { 7 - i };
+            byte >> bit & 0x01 != 0
+        })
+    }
+
+    /// Deassert chip select and set SCK to the idle level of the mode.
+    ///
+    /// This must be done before the first transfer.
+    pub fn init(&self, cmd: MpsseCmdBuilder) -> MpsseCmdBuilder {
+        cmd.set_gpio_lower(self.cs | self.clock(false), self.direction())
+    }
+
+    fn clock_byte(&self, mut cmd: MpsseCmdBuilder, byte: u8, read: bool) -> MpsseCmdBuilder {
+        let dir = self.direction();
+        let cpha = matches!(self.mode, SpiMode::Mode1 | SpiMode::Mode3);
+        for bit in self.bits(byte) {
+            let mosi = if bit { self.mosi } else { 0x00 };
+            if cpha {
+                // drive on the leading edge, sample on the trailing edge
+                cmd = cmd.set_gpio_lower(mosi | self.clock(true), dir);
+                if read {
+                    cmd = cmd.gpio_lower();
+                }
+                cmd = cmd.set_gpio_lower(mosi | self.clock(false), dir);
+            } else {
+                // drive before the leading edge, sample on the leading edge
+                cmd = cmd
+                    .set_gpio_lower(mosi | self.clock(false), dir)
+                    .set_gpio_lower(mosi | self.clock(true), dir);
+                if read {
+                    cmd = cmd.gpio_lower();
+                }
+            }
+        }
+        cmd
+    }
+
+    fn clock_data(&self, cmd: MpsseCmdBuilder, data: &[u8], read: bool) -> MpsseCmdBuilder {
+        let mut cmd = cmd.set_gpio_lower(self.clock(false), self.direction());
+        for &byte in data {
+            cmd = self.clock_byte(cmd, byte, read);
+        }
+        cmd.set_gpio_lower(self.clock(false), self.direction())
+            .set_gpio_lower(self.cs | self.clock(false), self.direction())
+    }
+
+    /// Clock data out and in with chip select asserted.
+    ///
+    /// The returned token covers 8 MISO samples per byte, decode them with
+    /// [`decode`](GpioSpi::decode).
+    pub fn transfer(&self, cmd: MpsseCmdBuilder, data: &[u8]) -> (MpsseCmdBuilder, ReadToken) {
+        let token = cmd.read_token(data.len() * 8);
+        (self.clock_data(cmd, data, true), token)
+    }
+
+    /// Clock data out with chip select asserted.
+    pub fn write(&self, cmd: MpsseCmdBuilder, data: &[u8]) -> MpsseCmdBuilder {
+        self.clock_data(cmd, data, false)
+    }
+
+    /// Decode the MISO samples read by [`transfer`](GpioSpi::transfer).
+    ///
+    /// `samples` is the part of the response covered by the token. Trailing
+    /// samples not making up a whole byte are ignored.
+    pub fn decode(&self, samples: &[u8]) -> Vec<u8> {
+        samples
+            .chunks_exact(8)
+            .map(|byte| {
+                byte.iter().enumerate().fold(0, |acc, (i, sample)| {
+                    if sample & self.miso == 0 {
+                        acc
+                    } else if self.lsb_first {
+                        acc | 1 << i
+                    } else {
+                        acc | 0x80 >> i
+                    }
+                })
+            })
+            .collect()
+    }
+}
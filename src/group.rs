@@ -0,0 +1,137 @@
+//! Named spans of commands for diagnostics.
+use crate::MpsseCmdBuilder;
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+/// Named span of commands recorded by [`group`](GroupedBuilder::group).
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct CommandGroup {
+    /// Name of the group.
+    pub name: String,
+    /// Byte range of the commands in the command stream.
+    pub range: Range<usize>,
+    /// Number of groups enclosing this group.
+    pub depth: usize,
+}
+
+/// Command builder recording named groups of commands.
+///
+/// Groups are annotations for tooling, they do not change the command
+/// stream. Commands are added to the wrapped [`MpsseCmdBuilder`] with
+/// [`map`](GroupedBuilder::map).
+///
+/// # Example
+///
+/// ```
+/// use ftdi_mpsse::{ClockDataOut, GroupedBuilder, MpsseCmdBuilder};
+///
+/// let cmd = GroupedBuilder::new(MpsseCmdBuilder::new()).group("SPI transaction", |cmd| {
+///     cmd.map(|cmd| cmd.set_gpio_lower(0x00, 0x0B))
+///         .group("write enable", |cmd| {
+///             cmd.map(|cmd| cmd.clock_data_out(ClockDataOut::MsbNeg, &[0x06]))
+///         })
+///         .map(|cmd| cmd.set_gpio_lower(0x08, 0x0B))
+/// });
+/// let groups = cmd.groups();
+/// assert_eq!(groups[0].name, "SPI transaction");
+/// assert_eq!(groups[0].range, 0..10);
+/// assert_eq!(groups[1].name, "write enable");
+/// assert_eq!(groups[1].range, 3..7);
+/// assert_eq!(groups[1].depth, 1);
+/// ```
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct GroupedBuilder {
+    cmd: MpsseCmdBuilder,
+    groups: Vec<CommandGroup>,
+}
+
+impl GroupedBuilder {
+    /// Record groups of the commands added to `cmd`.
+    pub fn new(cmd: MpsseCmdBuilder) -> Self {
+        GroupedBuilder {
+            cmd,
+            groups: Vec::new(),
+        }
+    }
+
+    /// Add commands with the methods of the wrapped builder.
+    pub fn map(mut self, f: impl FnOnce(MpsseCmdBuilder) -> MpsseCmdBuilder) -> Self {
+        self.cmd = f(self.cmd);
+        self
+    }
+
+    /// Record the commands emitted by `f` as a named group.
+    ///
+    /// Groups can be nested by calling `group` within `f`.
+    pub fn group(mut self, name: &str, f: impl FnOnce(Self) -> Self) -> Self {
+        let start = self.cmd.0.len();
+        let depth = self
+            .groups
+            .iter()
+            .filter(|group| group.range.start <= start && group.range.end == usize::MAX)
+            .count();
+        let index = self.groups.len();
+        self.groups.push(CommandGroup {
+            name: name.to_string(),
+            range: start..usize::MAX,
+            depth,
+        });
+        let mut cmd = f(self);
+        let end = cmd.cmd.0.len();
+        // the closure may have replaced the builder, only close the group
+        // opened above
+        if let Some(group) = cmd.groups.get_mut(index).filter(|group| {
+            group.name == name && group.range.start == start && group.range.end == usize::MAX
+        }) {
+            group.range.end = end.max(start);
+        }
+        cmd
+    }
+
+    /// Append the commands and groups of another grouped builder.
+    ///
+    /// Groups of `other` are kept, at their position in the combined stream.
+    pub fn append(mut self, other: GroupedBuilder) -> Self {
+        let offset = self.cmd.0.len();
+        self.cmd = self.cmd.append(other.cmd);
+        self.groups
+            .extend(other.groups.into_iter().map(|mut group| {
+                group.range = group.range.start + offset..group.range.end + offset;
+                group
+            }));
+        self
+    }
+
+    /// Groups recorded with [`group`](GroupedBuilder::group).
+    ///
+    /// Groups are in the order they were opened, so an enclosing group comes
+    /// before the groups nested within it.
+    pub fn groups(&self) -> &[CommandGroup] {
+        &self.groups
+    }
+
+    /// Get a reference to the wrapped builder.
+    pub fn inner(&self) -> &MpsseCmdBuilder {
+        &self.cmd
+    }
+
+    /// Unwrap the builder and its groups.
+    pub fn into_parts(self) -> (MpsseCmdBuilder, Vec<CommandGroup>) {
+        (self.cmd, self.groups)
+    }
+}
+
+impl From<MpsseCmdBuilder> for GroupedBuilder {
+    fn from(cmd: MpsseCmdBuilder) -> Self {
+        GroupedBuilder::new(cmd)
+    }
+}
+
+/// Discards the groups.
+impl From<GroupedBuilder> for MpsseCmdBuilder {
+    fn from(cmd: GroupedBuilder) -> Self {
+        cmd.cmd
+    }
+}
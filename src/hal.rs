@@ -0,0 +1,430 @@
+//! `embedded-hal` interoperability.
+use crate::{
+    pin_mask, GpioByte, MpsseCmdBuilder, MpsseCmdExecutor, ReadToken, SpiDevice, SpiMode,
+    MAX_CLOCK_DATA_LEN,
+};
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::fmt;
+use embedded_hal::digital::{self, InputPin, OutputPin, StatefulOutputPin};
+use embedded_hal::spi::{self, ErrorKind, ErrorType, Operation, SpiBus};
+
+/// Error returned by the `embedded-hal` adapters, wrapping an executor
+/// error.
+///
+/// Requires the `embedded-hal` feature.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct HalError<E>(pub E);
+
+impl<E: fmt::Display> fmt::Display for HalError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<E: core::error::Error + 'static> core::error::Error for HalError<E> {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        Some(&self.0)
+    }
+}
+
+impl<E: fmt::Debug> spi::Error for HalError<E> {
+    fn kind(&self) -> ErrorKind {
+        ErrorKind::Other
+    }
+}
+
+impl<E: fmt::Debug> digital::Error for HalError<E> {
+    fn kind(&self) -> digital::ErrorKind {
+        digital::ErrorKind::Other
+    }
+}
+
+impl SpiDevice {
+    /// Build an `embedded-hal` SPI transaction.
+    ///
+    /// Asserts chip select, clocks every operation in order and deasserts chip
+    /// select, like [`embedded_hal::spi::SpiDevice::transaction`]. Returns one
+    /// token for each `Read`, `Transfer` and `TransferInPlace` operation, in
+    /// order, covering the data to copy into the operation read buffer.
+    ///
+    /// `Transfer` operations with a write buffer shorter than the read buffer
+    /// clock out zeros for the missing bytes.
+    ///
+    /// A command stream cannot wait without clocking the bus, so `DelayNs`
+    /// operations are skipped. Split the operations at the delays and wait
+    /// between sending the command streams when the timing matters.
+    ///
+    /// Requires the `embedded-hal` feature.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use embedded_hal::spi::Operation;
+    /// use ftdi_mpsse::{MpsseCmdBuilder, SpiDevice, SpiMode};
+    ///
+    /// let flash = SpiDevice::new(3, SpiMode::Mode0);
+    /// let mut id = [0; 3];
+    /// let (cmd, tokens) = flash.transaction(
+    ///     MpsseCmdBuilder::new(),
+    ///     &[Operation::Write(&[0x9F]), Operation::Read(&mut id)],
+    /// );
+    /// let cmd = cmd.send_immediate();
+    /// assert_eq!(tokens[0].range(), 0..3);
+    /// ```
+    pub fn transaction(
+        &self,
+        cmd: MpsseCmdBuilder,
+        operations: &[Operation<'_, u8>],
+    ) -> (MpsseCmdBuilder, Vec<ReadToken>) {
+        let mode = self.mode();
+        let mut cmd = self.assert_cs(cmd);
+        let mut tokens = Vec::new();
+        for operation in operations {
+            match operation {
+                Operation::Read(read) => {
+                    tokens.push(cmd.read_token(read.len()));
+                    cmd = cmd.clock_data_in(mode.data_in(), read.len());
+                }
+                Operation::Write(write) => {
+                    for chunk in write.chunks(MAX_CLOCK_DATA_LEN) {
+                        cmd = cmd.clock_data_out(mode.data_out(), chunk);
+                    }
+                }
+                Operation::Transfer(read, write) => {
+                    tokens.push(cmd.read_token(read.len()));
+                    let mut data = write.to_vec();
+                    if data.len() < read.len() {
+                        data.resize(read.len(), 0x00);
+                    }
+                    for chunk in data.chunks(MAX_CLOCK_DATA_LEN) {
+                        cmd = cmd.clock_data(mode.data(), chunk);
+                    }
+                }
+                Operation::TransferInPlace(data) => {
+                    tokens.push(cmd.read_token(data.len()));
+                    for chunk in data.chunks(MAX_CLOCK_DATA_LEN) {
+                        cmd = cmd.clock_data(mode.data(), chunk);
+                    }
+                }
+                Operation::DelayNs(_) => {}
+            }
+        }
+        (self.init(cmd), tokens)
+    }
+}
+
+/// `embedded-hal` SPI bus over an executor.
+///
+/// Implements [`SpiBus`] with the MPSSE clocking SCK on AD0, MOSI on AD1 and
+/// MISO on AD2, MSB first with the clocking modes matching the [`SpiMode`].
+/// Each operation is sent as a command stream, reads wait for the response.
+///
+/// The bus does not configure the pins, set SCK to the idle level of the
+/// mode and the pin directions first, for example with [`SpiDevice::init`].
+/// Chip select is left to the caller, for example with
+/// `embedded_hal_bus::spi::ExclusiveDevice`.
+///
+/// Requires the `embedded-hal` feature.
+///
+/// # Example
+///
+/// ```no_run
+/// use embedded_hal::spi::SpiBus;
+/// use ftdi_mpsse::{MpsseCmdExecutor, MpsseSpi, SpiMode};
+///
+/// # fn example<E: MpsseCmdExecutor>(exec: E) -> Result<(), ftdi_mpsse::HalError<E::Error>>
+/// # where E::Error: std::fmt::Debug {
+/// let mut spi = MpsseSpi::new(exec, SpiMode::Mode0);
+/// let mut id = [0x9F, 0x00, 0x00, 0x00];
+/// spi.transfer_in_place(&mut id)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct MpsseSpi<E> {
+    exec: E,
+    mode: SpiMode,
+}
+
+impl<E: MpsseCmdExecutor> MpsseSpi<E> {
+    /// Create a SPI bus.
+    pub fn new(exec: E, mode: SpiMode) -> MpsseSpi<E> {
+        MpsseSpi { exec, mode }
+    }
+
+    /// SPI mode of the bus.
+    pub fn mode(&self) -> SpiMode {
+        self.mode
+    }
+
+    /// Get a mutable reference to the executor.
+    pub fn exec_mut(&mut self) -> &mut E {
+        &mut self.exec
+    }
+
+    /// Get the executor back.
+    pub fn into_inner(self) -> E {
+        self.exec
+    }
+
+    /// Clock `data` out and in, reading the response into `data`.
+    fn clock(&mut self, data: &mut [u8]) -> Result<(), HalError<E::Error>> {
+        if data.is_empty() {
+            return Ok(());
+        }
+        let mut cmd = MpsseCmdBuilder::new();
+        for chunk in data.chunks(MAX_CLOCK_DATA_LEN) {
+            cmd = cmd.clock_data(self.mode.data(), chunk);
+        }
+        let cmd = cmd.send_immediate();
+        self.exec.xfer(cmd.as_slice(), data).map_err(HalError)
+    }
+}
+
+impl<E: MpsseCmdExecutor> ErrorType for MpsseSpi<E>
+where
+    E::Error: fmt::Debug,
+{
+    type Error = HalError<E::Error>;
+}
+
+impl<E: MpsseCmdExecutor> SpiBus<u8> for MpsseSpi<E>
+where
+    E::Error: fmt::Debug,
+{
+    fn read(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+        if words.is_empty() {
+            return Ok(());
+        }
+        let cmd = MpsseCmdBuilder::new()
+            .clock_data_in(self.mode.data_in(), words.len())
+            .send_immediate();
+        self.exec.xfer(cmd.as_slice(), words).map_err(HalError)
+    }
+
+    fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+        if words.is_empty() {
+            return Ok(());
+        }
+        let mut cmd = MpsseCmdBuilder::new();
+        for chunk in words.chunks(MAX_CLOCK_DATA_LEN) {
+            cmd = cmd.clock_data_out(self.mode.data_out(), chunk);
+        }
+        self.exec.send(cmd.as_slice()).map_err(HalError)
+    }
+
+    /// Clock out `write` padded with zeros to the longest of the buffers,
+    /// extra bytes read are discarded.
+    fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), Self::Error> {
+        let mut data = write.to_vec();
+        data.resize(read.len().max(write.len()), 0x00);
+        self.clock(&mut data)?;
+        read.copy_from_slice(&data[..read.len()]);
+        Ok(())
+    }
+
+    fn transfer_in_place(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+        self.clock(words)
+    }
+
+    /// Every operation is sent when it is called, there is nothing to flush.
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// Tracked state and direction of the GPIO bytes of [`MpsseGpio`].
+#[derive(Debug)]
+struct GpioPort<E> {
+    exec: E,
+    lower: (u8, u8),
+    upper: (u8, u8),
+}
+
+impl<E: MpsseCmdExecutor> GpioPort<E> {
+    fn byte(&mut self, byte: GpioByte) -> &mut (u8, u8) {
+        match byte {
+            GpioByte::Lower => &mut self.lower,
+            GpioByte::Upper => &mut self.upper,
+        }
+    }
+
+    /// Drive a pin and send the new state of its byte.
+    fn set(&mut self, byte: GpioByte, mask: u8, high: bool) -> Result<(), HalError<E::Error>> {
+        let (state, direction) = self.byte(byte);
+        if high {
+            *state |= mask;
+        } else {
+            *state &= !mask;
+        }
+        *direction |= mask;
+        let (state, direction) = (*state, *direction);
+        let cmd = match byte {
+            GpioByte::Lower => MpsseCmdBuilder::new().set_gpio_lower(state, direction),
+            GpioByte::Upper => MpsseCmdBuilder::new().set_gpio_upper(state, direction),
+        };
+        self.exec.send(cmd.as_slice()).map_err(HalError)
+    }
+
+    /// Read the pin levels of a byte.
+    fn read(&mut self, byte: GpioByte) -> Result<u8, HalError<E::Error>> {
+        let cmd = match byte {
+            GpioByte::Lower => MpsseCmdBuilder::new().gpio_lower(),
+            GpioByte::Upper => MpsseCmdBuilder::new().gpio_upper(),
+        };
+        let cmd = cmd.send_immediate();
+        let mut data = [0];
+        self.exec
+            .xfer(cmd.as_slice(), &mut data)
+            .map_err(HalError)?;
+        Ok(data[0])
+    }
+}
+
+/// `embedded-hal` GPIO pins over an executor.
+///
+/// Keeps track of the state and direction of both GPIO bytes, so that each
+/// [`MpsseGpioPin`] changes a single pin with one
+/// [`set_gpio_lower`](MpsseCmdBuilder::set_gpio_lower) or
+/// [`set_gpio_upper`](MpsseCmdBuilder::set_gpio_upper) without clobbering the
+/// others. Pins are inputs until they are driven, and stay outputs after.
+///
+/// The tracked state is only updated by the pins, so the GPIO must not be
+/// changed through the executor by other means while pins are in use.
+///
+/// The upper byte pins depend on the device, see
+/// [`set_gpio_upper`](MpsseCmdBuilder::set_gpio_upper). On the FT232H only
+/// CBUS5, CBUS6, CBUS8 and CBUS9 can be controlled, as upper byte pins 0 to
+/// 3, and the FT4232H has no upper byte pins at all.
+///
+/// Requires the `embedded-hal` feature.
+///
+/// # Example
+///
+/// ```no_run
+/// use embedded_hal::digital::{InputPin, OutputPin};
+/// use ftdi_mpsse::{GpioByte, MpsseCmdExecutor, MpsseGpio};
+///
+/// # fn example<E: MpsseCmdExecutor>(exec: E) -> Result<(), ftdi_mpsse::HalError<E::Error>>
+/// # where E::Error: std::fmt::Debug {
+/// let gpio = MpsseGpio::new(exec);
+/// let mut led = gpio.pin(GpioByte::Lower, 4);
+/// let mut button = gpio.pin(GpioByte::Upper, 0);
+/// led.set_high()?;
+/// if button.is_low()? {
+///     led.set_low()?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct MpsseGpio<E> {
+    port: Rc<RefCell<GpioPort<E>>>,
+}
+
+impl<E: MpsseCmdExecutor> MpsseGpio<E> {
+    /// Create the GPIO pins of an executor, all pins being inputs.
+    ///
+    /// Use [`with_state`](MpsseGpio::with_state) if some pins were already
+    /// configured, the first pin driven otherwise turns them into inputs.
+    pub fn new(exec: E) -> MpsseGpio<E> {
+        MpsseGpio::with_state(exec, 0x00, 0x00, 0x00, 0x00)
+    }
+
+    /// Create the GPIO pins of an executor with the state and direction last
+    /// set on the device.
+    ///
+    /// Pins are set with one command per byte, keeping the given state of
+    /// the pins not being changed.
+    pub fn with_state(
+        exec: E,
+        lower_value: u8,
+        lower_dir: u8,
+        upper_value: u8,
+        upper_dir: u8,
+    ) -> MpsseGpio<E> {
+        MpsseGpio {
+            port: Rc::new(RefCell::new(GpioPort {
+                exec,
+                lower: (lower_value, lower_dir),
+                upper: (upper_value, upper_dir),
+            })),
+        }
+    }
+
+    /// Get a pin.
+    ///
+    /// This will panic for pin numbers greater than 7.
+    pub fn pin(&self, byte: GpioByte, pin: u8) -> MpsseGpioPin<E> {
+        MpsseGpioPin {
+            port: Rc::clone(&self.port),
+            byte,
+            mask: pin_mask(pin),
+        }
+    }
+
+    /// Tracked state and direction of a GPIO byte.
+    pub fn state(&self, byte: GpioByte) -> (u8, u8) {
+        *self.port.borrow_mut().byte(byte)
+    }
+}
+
+/// Single GPIO pin of [`MpsseGpio`].
+///
+/// Requires the `embedded-hal` feature.
+#[derive(Debug)]
+pub struct MpsseGpioPin<E> {
+    port: Rc<RefCell<GpioPort<E>>>,
+    byte: GpioByte,
+    mask: u8,
+}
+
+impl<E: MpsseCmdExecutor> digital::ErrorType for MpsseGpioPin<E>
+where
+    E::Error: fmt::Debug,
+{
+    type Error = HalError<E::Error>;
+}
+
+impl<E: MpsseCmdExecutor> OutputPin for MpsseGpioPin<E>
+where
+    E::Error: fmt::Debug,
+{
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.port.borrow_mut().set(self.byte, self.mask, false)
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.port.borrow_mut().set(self.byte, self.mask, true)
+    }
+}
+
+impl<E: MpsseCmdExecutor> StatefulOutputPin for MpsseGpioPin<E>
+where
+    E::Error: fmt::Debug,
+{
+    /// Returns the tracked state, without reading the pin.
+    fn is_set_high(&mut self) -> Result<bool, Self::Error> {
+        Ok(self.port.borrow_mut().byte(self.byte).0 & self.mask != 0)
+    }
+
+    /// Returns the tracked state, without reading the pin.
+    fn is_set_low(&mut self) -> Result<bool, Self::Error> {
+        self.is_set_high().map(|high| !high)
+    }
+}
+
+impl<E: MpsseCmdExecutor> InputPin for MpsseGpioPin<E>
+where
+    E::Error: fmt::Debug,
+{
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        Ok(self.port.borrow_mut().read(self.byte)? & self.mask != 0)
+    }
+
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        self.is_high().map(|high| !high)
+    }
+}
@@ -0,0 +1,219 @@
+//! I2C helpers.
+//!
+//! The MPSSE clocks I2C data with SCL on AD0, SDA driven from AD1 and SDA
+//! sampled on AD2, with AD1 and AD2 tied together. START and STOP conditions
+//! are generated by setting the lower byte GPIO pins directly, and the bus is
+//! released by switching SDA to an input.
+use crate::{pin_mask, ClockBitsIn, ClockBitsOut, MpsseCmdBuilder, ReadToken};
+use alloc::vec::Vec;
+
+/// First 7-bit address probed by [`i2c_scan`](MpsseCmdBuilder::i2c_scan).
+const SCAN_FIRST: u8 = 0x08;
+/// End of the 7-bit address range probed by
+/// [`i2c_scan`](MpsseCmdBuilder::i2c_scan), exclusive.
+const SCAN_END: u8 = 0x78;
+
+impl MpsseCmdBuilder {
+    /// Generate an I2C START condition, leaving SCL low and SDA driven low.
+    pub(crate) fn i2c_start_masks(self, sda: u8, scl: u8) -> Self {
+        let dir = sda | scl;
+        self.set_gpio_lower(sda | scl, dir)
+            .set_gpio_lower(scl, dir)
+            .set_gpio_lower(0x00, dir)
+    }
+
+    /// Generate an I2C STOP condition, leaving SCL and SDA driven high.
+    pub(crate) fn i2c_stop_masks(self, sda: u8, scl: u8) -> Self {
+        let dir = sda | scl;
+        self.set_gpio_lower(0x00, dir)
+            .set_gpio_lower(scl, dir)
+            .set_gpio_lower(sda | scl, dir)
+    }
+
+    /// Clock a byte out, then release SDA and clock in the acknowledge bit.
+    ///
+    /// The acknowledge is in bit 0 of the byte covered by the returned token,
+    /// `0` for ACK and `1` for NACK.
+    pub(crate) fn i2c_write_byte_masks(self, sda: u8, scl: u8, byte: u8) -> (Self, ReadToken) {
+        let cmd = self
+            .clock_bits_out(ClockBitsOut::MsbNeg, byte, 8)
+            .set_gpio_lower(0x00, scl);
+        let token = cmd.read_token(1);
+        let cmd = cmd
+            .clock_bits_in(ClockBitsIn::MsbPos, 1)
+            .set_gpio_lower(0x00, sda | scl);
+        (cmd, token)
+    }
+
+    /// Probe every 7-bit I2C address from 0x08 to 0x77.
+    ///
+    /// Each address is sent with the write bit in its own START/STOP framed
+    /// transaction. The returned tokens cover the acknowledge bit of each
+    /// address, in address order; decode the data they cover with
+    /// [`i2c_present_addresses`].
+    ///
+    /// This is the equivalent of `i2cdetect` and builds a command stream of
+    /// several kilobytes, longer than a single USB transfer.
+    ///
+    /// # Arguments
+    ///
+    /// * `sda` - Lower byte GPIO pin number driving SDA, usually 1.
+    /// * `scl` - Lower byte GPIO pin number driving SCL, usually 0.
+    ///
+    /// This will panic for pin numbers greater than 7.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::{i2c_present_addresses, MpsseCmdBuilder};
+    ///
+    /// let (cmd, tokens) = MpsseCmdBuilder::new()
+    ///     .enable_3phase_data_clocking()
+    ///     .i2c_scan(1, 0);
+    /// let cmd = cmd.send_immediate();
+    /// assert_eq!(tokens.len(), 112);
+    ///
+    /// // a device answered at 0x50
+    /// let mut response = vec![0x01; 112];
+    /// response[0x50 - 0x08] = 0x00;
+    /// assert_eq!(i2c_present_addresses(&response), vec![0x50]);
+    /// ```
+    pub fn i2c_scan(mut self, sda: u8, scl: u8) -> (Self, Vec<ReadToken>) {
+        let sda = pin_mask(sda);
+        let scl = pin_mask(scl);
+        let mut tokens = Vec::with_capacity(usize::from(SCAN_END - SCAN_FIRST));
+        for addr in SCAN_FIRST..SCAN_END {
+            let (cmd, token) =
+                self.i2c_start_masks(sda, scl)
+                    .i2c_write_byte_masks(sda, scl, addr << 1);
+            self = cmd.i2c_stop_masks(sda, scl);
+            tokens.push(token);
+        }
+        (self, tokens)
+    }
+}
+
+/// Decode the acknowledge bits read by
+/// [`i2c_scan`](MpsseCmdBuilder::i2c_scan).
+///
+/// `readings` is the part of the response covered by the scan tokens, one
+/// byte per address starting at 0x08. Returns the addresses that
+/// acknowledged.
+pub fn i2c_present_addresses(readings: &[u8]) -> Vec<u8> {
+    (SCAN_FIRST..SCAN_END)
+        .zip(readings)
+        .filter(|(_, &ack)| I2c::is_ack(ack))
+        .map(|(addr, _)| addr)
+        .collect()
+}
+
+/// I2C bus master on lower byte GPIO pins.
+///
+/// Produces the command fragments of an I2C transaction. The commands
+/// assume 3-phase data clocking is enabled with
+/// [`enable_3phase_data_clocking`](MpsseCmdBuilder::enable_3phase_data_clocking),
+/// so that data is stable while SCL is high. SDA is released by switching it
+/// to an input, the bus pull-ups providing the high level.
+///
+/// Between fragments SCL is low, so [`start`](I2c::start) must come first
+/// and [`stop`](I2c::stop) last.
+///
+/// # Example
+///
+/// ```
+/// use ftdi_mpsse::{I2c, MpsseCmdBuilder};
+///
+/// // read a register of the device at 0x50
+/// let i2c = I2c::new(1, 0);
+/// let cmd = i2c.start(MpsseCmdBuilder::new().enable_3phase_data_clocking());
+/// let (cmd, addr_ack) = i2c.write_byte_read_ack(cmd, 0x50 << 1);
+/// let (cmd, reg_ack) = i2c.write_byte_read_ack(cmd, 0x10);
+/// let cmd = i2c.repeated_start(cmd);
+/// let (cmd, read_ack) = i2c.write_byte_read_ack(cmd, 0x50 << 1 | 1);
+/// let (cmd, value) = i2c.read_byte_send_ack(cmd, true);
+/// let cmd = i2c.stop(cmd).send_immediate();
+///
+/// let response = [0x00, 0x00, 0x00, 0x42];
+/// assert!(I2c::is_ack(response[addr_ack.offset()]));
+/// assert_eq!(response[value.offset()], 0x42);
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct I2c {
+    sda: u8,
+    scl: u8,
+}
+
+impl I2c {
+    /// Create an I2C bus master.
+    ///
+    /// # Arguments
+    ///
+    /// * `sda` - Lower byte GPIO pin number driving SDA, usually 1.
+    /// * `scl` - Lower byte GPIO pin number driving SCL, usually 0.
+    ///
+    /// This will panic for pin numbers greater than 7.
+    pub fn new(sda: u8, scl: u8) -> I2c {
+        I2c {
+            sda: pin_mask(sda),
+            scl: pin_mask(scl),
+        }
+    }
+
+    /// Returns `true` if an acknowledge bit read by
+    /// [`write_byte_read_ack`](I2c::write_byte_read_ack) is an ACK.
+    ///
+    /// The acknowledge bit is in bit 0 of the byte read, low for ACK and
+    /// high for NACK.
+    pub const fn is_ack(reading: u8) -> bool {
+        reading & 0x01 == 0
+    }
+
+    /// Generate a START condition, leaving SCL low.
+    pub fn start(&self, cmd: MpsseCmdBuilder) -> MpsseCmdBuilder {
+        cmd.i2c_start_masks(self.sda, self.scl)
+    }
+
+    /// Generate a repeated START condition, leaving SCL low.
+    ///
+    /// SDA is raised while SCL is low before the START condition.
+    pub fn repeated_start(&self, cmd: MpsseCmdBuilder) -> MpsseCmdBuilder {
+        cmd.set_gpio_lower(self.sda, self.sda | self.scl)
+            .i2c_start_masks(self.sda, self.scl)
+    }
+
+    /// Generate a STOP condition, leaving SCL and SDA high.
+    pub fn stop(&self, cmd: MpsseCmdBuilder) -> MpsseCmdBuilder {
+        cmd.i2c_stop_masks(self.sda, self.scl)
+    }
+
+    /// Clock a byte out, then release SDA and clock in the acknowledge bit.
+    ///
+    /// The returned token covers one byte, decode it with
+    /// [`is_ack`](I2c::is_ack).
+    pub fn write_byte_read_ack(
+        &self,
+        cmd: MpsseCmdBuilder,
+        byte: u8,
+    ) -> (MpsseCmdBuilder, ReadToken) {
+        cmd.i2c_write_byte_masks(self.sda, self.scl, byte)
+    }
+
+    /// Release SDA and clock a byte in, then clock out the acknowledge bit.
+    ///
+    /// The master sends a NACK instead of an ACK if `nack` is `true`, for the
+    /// last byte of a read. The returned token covers the byte read.
+    pub fn read_byte_send_ack(
+        &self,
+        cmd: MpsseCmdBuilder,
+        nack: bool,
+    ) -> (MpsseCmdBuilder, ReadToken) {
+        let cmd = cmd.set_gpio_lower(0x00, self.scl);
+        let token = cmd.read_token(1);
+        let ack = if nack { 0x80 } else { 0x00 };
+        let cmd = cmd
+            .clock_bits_in(ClockBitsIn::MsbPos, 8)
+            .set_gpio_lower(0x00, self.sda | self.scl)
+            .clock_bits_out(ClockBitsOut::MsbNeg, ack, 1);
+        (cmd, token)
+    }
+}
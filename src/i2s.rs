@@ -0,0 +1,112 @@
+//! I2S-like audio output.
+//!
+//! BCLK is the MPSSE clock on AD0 and the data is clocked out on AD1, with
+//! LRCLK on a lower byte GPIO pin.
+use crate::{pin_mask, ClockDataOut, MpsseCmdBuilder};
+
+/// BCLK and data pin mask.
+const BCLK_DATA: u8 = 0x03;
+/// Maximum frequency of the MPSSE clock.
+const MAX_CLOCK_HZ: u32 = 30_000_000;
+/// Bits of each sample.
+const BITS_PER_SAMPLE: u32 = 16;
+/// Samples of each frame, left and right.
+const CHANNELS: u32 = 2;
+
+/// Nominal sample rate of [`i2s_frame`](MpsseCmdBuilder::i2s_frame) for a
+/// BCLK of `clock_hz`.
+///
+/// Each stereo frame is 2 samples of 16 bits, so the sample rate is
+/// `clock_hz / 32`, and 48 kHz audio needs a 1.536 MHz BCLK. This is an
+/// upper bound, the GPIO commands setting LRCLK between the samples stretch
+/// every frame.
+///
+/// # Example
+///
+/// ```
+/// use ftdi_mpsse::i2s_sample_rate;
+///
+/// assert_eq!(i2s_sample_rate(1_536_000), 48_000);
+/// ```
+pub const fn i2s_sample_rate(clock_hz: u32) -> u32 {
+    clock_hz / (CHANNELS * BITS_PER_SAMPLE)
+}
+
+impl MpsseCmdBuilder {
+    /// Clock out 16-bit audio samples with an LRCLK channel select.
+    ///
+    /// Samples are interleaved, left channel first. LRCLK is set low before
+    /// each left sample and high before each right sample, then the 16 bits
+    /// of the sample are clocked out, MSB first for MSB first modes and LSB
+    /// first otherwise.
+    ///
+    /// BCLK is the MPSSE clock, so `clock_hz` must match the frequency set
+    /// with [`set_clock`](MpsseCmdBuilder::set_clock). Each stereo frame is
+    /// 32 BCLK cycles, so the sample rate is at most
+    /// [`i2s_sample_rate(clock_hz)`](i2s_sample_rate). BCLK stops while the
+    /// GPIO command setting LRCLK runs before each sample, which stretches
+    /// every frame and lowers the actual sample rate, so this suits DACs
+    /// that tolerate a gapped BCLK. Unlike I2S, the first bit is
+    /// not delayed by one BCLK cycle after LRCLK changes, which matches the
+    /// left-justified format.
+    ///
+    /// # Arguments
+    ///
+    /// * `data_mode` - Clocking mode of the sample bits.
+    /// * `samples` - Interleaved left and right samples.
+    /// * `lrclk_pin` - Lower byte GPIO pin number of LRCLK, 2-7.
+    /// * `clock_hz` - Frequency of the MPSSE clock.
+    ///
+    /// This will panic for pin numbers outside 2-7, as pins 0 and 1 are BCLK
+    /// and data, and for clock frequencies of 0 or above 30 MHz.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::{i2s_sample_rate, ClockDataOut, MpsseCmdBuilder};
+    ///
+    /// // 1.5 MHz BCLK, 46.875 kHz sample rate at most
+    /// assert_eq!(i2s_sample_rate(1_500_000), 46_875);
+    /// let cmd = MpsseCmdBuilder::new()
+    ///     .set_clock(3, Some(true))
+    ///     .i2s_frame(ClockDataOut::MsbNeg, &[0x1234, -2], 3, 1_500_000);
+    /// assert_eq!(
+    ///     &cmd.as_slice()[4..],
+    ///     [
+    ///         0x80, 0x00, 0x0B, 0x11, 0x01, 0x00, 0x12, 0x34, // left
+    ///         0x80, 0x08, 0x0B, 0x11, 0x01, 0x00, 0xFF, 0xFE, // right
+    ///     ]
+    /// );
+    /// ```
+    pub fn i2s_frame(
+        mut self,
+        data_mode: ClockDataOut,
+        samples: &[i16],
+        lrclk_pin: u8,
+        clock_hz: u32,
+    ) -> Self {
+        assert!(
+            (2..=7).contains(&lrclk_pin),
+            "LRCLK pin must be between 2 and 7"
+        );
+        assert!(
+            clock_hz > 0 && clock_hz <= MAX_CLOCK_HZ,
+            "clock frequency must be between 1 Hz and 30 MHz"
+        );
+        let lrclk = pin_mask(lrclk_pin);
+        let dir = BCLK_DATA | lrclk;
+        let lsb_first = u8::from(data_mode) & 0x08 != 0;
+        for (i, sample) in samples.iter().enumerate() {
+            let state = if i % 2 == 0 { 0x00 } else { lrclk };
+            let bytes = if lsb_first {
+                sample.to_le_bytes()
+            } else {
+                sample.to_be_bytes()
+            };
+            self = self
+                .set_gpio_lower(state, dir)
+                .clock_data_out(data_mode, &bytes);
+        }
+        self
+    }
+}
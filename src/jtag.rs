@@ -0,0 +1,269 @@
+//! JTAG TAP controller navigation helpers.
+//!
+//! These helpers assume the usual MPSSE wiring, with TCK on AD0, TDI on AD1,
+//! TDO on AD2 and TMS on AD3. TDI and TMS change on the falling edge of TCK
+//! and TDO is sampled on the rising edge.
+use crate::{ClockBits, ClockData, ClockTMS, ClockTMSOut, MpsseCmdBuilder, ReadToken};
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Number of TAP controller states.
+const STATE_COUNT: usize = 16;
+
+/// Maximum number of TMS bits of a single TMS clocking command.
+const MAX_TMS_BITS: u8 = 7;
+
+/// JTAG TAP controller state.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum TapState {
+    /// Test-Logic-Reset.
+    TestLogicReset,
+    /// Run-Test/Idle.
+    RunTestIdle,
+    /// Select-DR-Scan.
+    SelectDr,
+    /// Capture-DR.
+    CaptureDr,
+    /// Shift-DR.
+    ShiftDr,
+    /// Exit1-DR.
+    Exit1Dr,
+    /// Pause-DR.
+    PauseDr,
+    /// Exit2-DR.
+    Exit2Dr,
+    /// Update-DR.
+    UpdateDr,
+    /// Select-IR-Scan.
+    SelectIr,
+    /// Capture-IR.
+    CaptureIr,
+    /// Shift-IR.
+    ShiftIr,
+    /// Exit1-IR.
+    Exit1Ir,
+    /// Pause-IR.
+    PauseIr,
+    /// Exit2-IR.
+    Exit2Ir,
+    /// Update-IR.
+    UpdateIr,
+}
+
+impl TapState {
+    /// State entered on a rising edge of TCK with the given TMS level.
+    pub const fn next(self, tms: bool) -> TapState {
+        use TapState::*;
+        match (self, tms) {
+            (TestLogicReset, false) => RunTestIdle,
+            (TestLogicReset, true) => TestLogicReset,
+            (RunTestIdle, false) => RunTestIdle,
+            (RunTestIdle, true) => SelectDr,
+            (SelectDr, false) => CaptureDr,
+            (SelectDr, true) => SelectIr,
+            (CaptureDr, false) | (ShiftDr, false) | (Exit2Dr, false) => ShiftDr,
+            (CaptureDr, true) | (ShiftDr, true) => Exit1Dr,
+            (Exit1Dr, false) | (PauseDr, false) => PauseDr,
+            (Exit1Dr, true) | (Exit2Dr, true) => UpdateDr,
+            (PauseDr, true) => Exit2Dr,
+            (UpdateDr, false) | (UpdateIr, false) => RunTestIdle,
+            (UpdateDr, true) | (UpdateIr, true) => SelectDr,
+            (SelectIr, false) => CaptureIr,
+            (SelectIr, true) => TestLogicReset,
+            (CaptureIr, false) | (ShiftIr, false) | (Exit2Ir, false) => ShiftIr,
+            (CaptureIr, true) | (ShiftIr, true) => Exit1Ir,
+            (Exit1Ir, false) | (PauseIr, false) => PauseIr,
+            (Exit1Ir, true) | (Exit2Ir, true) => UpdateIr,
+            (PauseIr, true) => Exit2Ir,
+        }
+    }
+
+    /// Shortest TMS sequence moving from `self` to `to`.
+    ///
+    /// Returns the TMS bits, first bit in bit 0, and the number of bits.
+    fn path(self, to: TapState) -> (u32, u8) {
+        // breadth first search, recording the TMS bits reaching each state
+        let mut paths: [Option<(u32, u8)>; STATE_COUNT] = [None; STATE_COUNT];
+        paths[self as usize] = Some((0, 0));
+        let mut queue = vec![self];
+        let mut head = 0;
+        while let Some(&state) = queue.get(head) {
+            head += 1;
+            let (bits, len) = paths[state as usize].unwrap();
+            if state == to {
+                return (bits, len);
+            }
+            for tms in [false, true] {
+                let next = state.next(tms);
+                if paths[next as usize].is_none() {
+                    paths[next as usize] = Some((bits | u32::from(tms) << len, len + 1));
+                    queue.push(next);
+                }
+            }
+        }
+        unreachable!("every TAP state is reachable from every other state")
+    }
+}
+
+/// JTAG TAP controller navigation.
+///
+/// Moves the TAP controller between states with the shortest TMS sequences,
+/// and shifts data and instruction registers. Shifts start and end in the
+/// idle state of the helper. Data is shifted LSB first, and the last bit is
+/// clocked with TMS high to leave the shift state for the Exit1 state, from
+/// where the TAP goes back to the idle state through the Update state.
+///
+/// # Example
+///
+/// ```
+/// use ftdi_mpsse::{jtag_decode_shift, JtagTap, MpsseCmdBuilder, TapState};
+///
+/// let tap = JtagTap::new(TapState::RunTestIdle);
+/// let cmd = tap.reset(MpsseCmdBuilder::new());
+/// let cmd = tap.goto_state(cmd, TapState::TestLogicReset, TapState::RunTestIdle);
+/// // IDCODE instruction, then read the 32-bit ID register
+/// let (cmd, _) = tap.shift_ir(cmd, &[0x01]);
+/// let (cmd, id) = tap.shift_dr(cmd, &[0x00; 4]);
+/// let cmd = cmd.send_immediate();
+/// assert_eq!(id.len(), 5);
+///
+/// let response = [0x00, 0x80, 0x77, 0x04, 0xA0, 0x06, 0x80];
+/// assert_eq!(
+///     jtag_decode_shift(&response[id.offset()..][..id.len()]),
+///     vec![0x77, 0x04, 0xA0, 0x83]
+/// );
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct JtagTap {
+    idle: TapState,
+}
+
+impl JtagTap {
+    /// Create a TAP controller helper.
+    ///
+    /// `idle` is the state shifts start and end in, usually
+    /// [`TapState::RunTestIdle`].
+    ///
+    /// This will panic if `idle` is not a stable state: Test-Logic-Reset,
+    /// Run-Test/Idle, Pause-DR or Pause-IR.
+    pub fn new(idle: TapState) -> JtagTap {
+        assert!(
+            matches!(
+                idle,
+                TapState::TestLogicReset
+                    | TapState::RunTestIdle
+                    | TapState::PauseDr
+                    | TapState::PauseIr
+            ),
+            "idle state must be a stable TAP state"
+        );
+        JtagTap { idle }
+    }
+
+    /// Idle state shifts start and end in.
+    pub fn idle(&self) -> TapState {
+        self.idle
+    }
+
+    /// Move to Test-Logic-Reset from any state by clocking TMS high 5 times.
+    pub fn reset(&self, cmd: MpsseCmdBuilder) -> MpsseCmdBuilder {
+        cmd.clock_tms_out(ClockTMSOut::NegEdge, 0x1F, false, 5)
+    }
+
+    /// Move from `from` to `to` with the shortest TMS sequence.
+    ///
+    /// The sequence is split into TMS clocking commands of at most 7 bits.
+    /// Nothing is clocked if `from` and `to` are the same state.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::{JtagTap, MpsseCmdBuilder, TapState};
+    ///
+    /// let tap = JtagTap::new(TapState::RunTestIdle);
+    /// let cmd = tap.goto_state(MpsseCmdBuilder::new(), TapState::RunTestIdle, TapState::ShiftDr);
+    /// // TMS 1, 0, 0
+    /// assert_eq!(cmd.as_slice(), [0x4B, 0x02, 0x01]);
+    /// ```
+    pub fn goto_state(
+        &self,
+        mut cmd: MpsseCmdBuilder,
+        from: TapState,
+        to: TapState,
+    ) -> MpsseCmdBuilder {
+        let (mut bits, mut len) = from.path(to);
+        while len > 0 {
+            let chunk = len.min(MAX_TMS_BITS);
+            cmd = cmd.clock_tms_out(ClockTMSOut::NegEdge, bits as u8 & 0x7F, false, chunk);
+            bits >>= chunk;
+            len -= chunk;
+        }
+        cmd
+    }
+
+    /// Shift the data register, from and back to the idle state.
+    ///
+    /// Returns a token covering `data.len() + 1` bytes, decode them with
+    /// [`jtag_decode_shift`].
+    ///
+    /// This will panic if `data` is empty or longer than `u16::MAX + 2`
+    /// bytes.
+    pub fn shift_dr(&self, cmd: MpsseCmdBuilder, data: &[u8]) -> (MpsseCmdBuilder, ReadToken) {
+        self.shift(cmd, TapState::ShiftDr, data)
+    }
+
+    /// Shift the instruction register, from and back to the idle state.
+    ///
+    /// Returns a token covering `data.len() + 1` bytes, decode them with
+    /// [`jtag_decode_shift`].
+    ///
+    /// This will panic if `data` is empty or longer than `u16::MAX + 2`
+    /// bytes.
+    pub fn shift_ir(&self, cmd: MpsseCmdBuilder, data: &[u8]) -> (MpsseCmdBuilder, ReadToken) {
+        self.shift(cmd, TapState::ShiftIr, data)
+    }
+
+    /// Shift `data` through TDI in `state`, LSB first, while reading TDO.
+    ///
+    /// Every bit but the last is clocked with TMS low. The last bit is
+    /// clocked with a TMS clocking command, with TMS high to move to the
+    /// Exit1 state.
+    fn shift(
+        &self,
+        cmd: MpsseCmdBuilder,
+        state: TapState,
+        data: &[u8],
+    ) -> (MpsseCmdBuilder, ReadToken) {
+        let (&last, bytes) = data.split_last().expect("shift data cannot be empty");
+        let mut cmd = self.goto_state(cmd, self.idle, state);
+        let token = cmd.read_token(data.len() + 1);
+        if !bytes.is_empty() {
+            cmd = cmd.clock_data(ClockData::LsbPosIn, bytes);
+        }
+        let cmd = cmd.clock_bits(ClockBits::LsbPosIn, last, 7).clock_tms(
+            ClockTMS::NegTMSPosTDO,
+            0x01,
+            last & 0x80 != 0,
+            1,
+        );
+        let exit1 = state.next(true);
+        (self.goto_state(cmd, exit1, self.idle), token)
+    }
+}
+
+/// Decode the TDO data read by [`JtagTap::shift_dr`] or
+/// [`JtagTap::shift_ir`].
+///
+/// `readings` is the part of the response covered by the shift token. The
+/// first bytes are the TDO bytes, followed by the first 7 bits of the last
+/// byte in bits 1-7 and the last bit in bit 7 of the final byte.
+/// Returns one byte per byte shifted, LSB first like the data shifted out.
+///
+/// This will panic if `readings` is shorter than 2 bytes.
+pub fn jtag_decode_shift(readings: &[u8]) -> Vec<u8> {
+    assert!(readings.len() >= 2, "shift readings are at least 2 bytes");
+    let (bytes, last) = readings.split_at(readings.len() - 2);
+    let mut data = bytes.to_vec();
+    data.push(last[0] >> 1 | last[1] & 0x80);
+    data
+}
@@ -0,0 +1,42 @@
+//! LED driver helpers.
+use crate::{ClockDataOut, MpsseCmdBuilder};
+
+/// Gamma-corrected brightness for step `index` of a ramp of `steps` values.
+fn ramp_value(index: usize, steps: usize, gamma: f32) -> u8 {
+    let linear = if steps > 1 {
+        index as f32 / (steps - 1) as f32
+    } else {
+        1.0
+    };
+    (linear.powf(gamma) * 255.0).round() as u8
+}
+
+impl MpsseCmdBuilder {
+    /// Clock out a gamma-corrected brightness ramp.
+    ///
+    /// Generates one byte per channel, going from 0 for the first channel to
+    /// 255 for the last, with each value corrected as
+    /// `255 * (i / (channels - 1)) ^ gamma`. This is useful to check every
+    /// channel of an LED driver chain at a glance.
+    ///
+    /// Ramps longer than `u16::MAX + 1` channels are split into several
+    /// commands.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::{ClockDataOut, MpsseCmdBuilder};
+    ///
+    /// let cmd = MpsseCmdBuilder::new().clock_led_ramp(ClockDataOut::MsbNeg, 5, 2.2);
+    /// assert_eq!(&cmd.as_slice()[3..], [0, 12, 55, 135, 255]);
+    /// ```
+    pub fn clock_led_ramp(mut self, mode: ClockDataOut, channels: usize, gamma: f32) -> Self {
+        let ramp: Vec<u8> = (0..channels)
+            .map(|i| ramp_value(i, channels, gamma))
+            .collect();
+        for chunk in ramp.chunks(65536) {
+            self = self.clock_data_out(mode, chunk);
+        }
+        self
+    }
+}
@@ -1,10 +1,102 @@
 //! Multi-protocol synchronous serial engine utilities for FTDI devices.
 #![doc(html_root_url = "https://docs.rs/ftdi-mpsse/0.1.0")]
 #![deny(unsafe_code)]
-
-use std::convert::From;
-use std::result::Result;
-use std::time::Duration;
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::convert::{From, TryFrom};
+use core::fmt;
+use core::result::Result;
+use core::time::Duration;
+
+mod analysis;
+#[cfg(feature = "async")]
+mod async_executor;
+#[cfg(feature = "std")]
+mod bench;
+mod charlieplex;
+mod checkpoint;
+mod chunk;
+pub mod conformance;
+mod crc;
+mod decode;
+mod default_mode;
+mod device;
+mod framed;
+mod from_bytes;
+mod gpio;
+mod gpio_spi;
+mod group;
+#[cfg(feature = "embedded-hal")]
+mod hal;
+mod i2c;
+mod i2s;
+mod jtag;
+#[cfg(feature = "std")]
+mod led;
+mod loopback;
+#[cfg(feature = "mock")]
+mod mock;
+mod onewire;
+mod optimize;
+mod parity;
+mod parse;
+mod retry;
+mod spi;
+mod spi_builder;
+mod spi_id;
+mod state;
+mod stepper;
+mod swd;
+mod sweep;
+mod sync;
+#[cfg(feature = "std")]
+mod trace;
+mod validate;
+mod waveform;
+
+#[cfg(feature = "async")]
+pub use async_executor::AsyncMpsseCmdExecutor;
+#[cfg(feature = "std")]
+pub use bench::{measure_rtt, throughput_benchmark, RttReport, ThroughputReport};
+pub use charlieplex::Charlieplex;
+pub use checkpoint::{Checkpoint, RollbackError};
+pub use chunk::{optimal_chunk_size, ChunkedExecutor};
+pub use crc::{spi_checked_transaction, CrcKind};
+pub use decode::{decode, DecodeError, MpsseOp};
+pub use default_mode::DefaultModeBuilder;
+pub use device::FtdiDevice;
+pub use framed::FramedExecutor;
+pub use from_bytes::FromBytes;
+#[cfg(feature = "bytemuck")]
+pub use from_bytes::PodValue;
+pub use gpio::{gpio_walking_ones_failures, GpioState};
+pub use gpio_spi::GpioSpi;
+pub use group::{CommandGroup, GroupedBuilder};
+#[cfg(feature = "embedded-hal")]
+pub use hal::{HalError, MpsseGpio, MpsseGpioPin, MpsseSpi};
+pub use i2c::{i2c_present_addresses, I2c};
+pub use i2s::i2s_sample_rate;
+pub use jtag::{jtag_decode_shift, JtagTap, TapState};
+#[cfg(feature = "mock")]
+pub use mock::{LoopbackExecutor, MockError, MockMpsseExecutor};
+pub use onewire::onewire_decode_byte;
+pub use parity::check_parity;
+pub use retry::{RetryError, RetryingExecutor};
+pub use spi::{DemuxCs, MisoIdle, SpiDevice, SpiMode, SPI_PAD_BYTE};
+pub use spi_builder::{CsAsserted, Idle, SpiBuilder, SpiTransaction};
+pub use spi_id::{verify_spi_id, SpiIdError};
+pub use state::{DeviceState, StateToken};
+pub use stepper::{StepMode, Stepper};
+pub use swd::{swd_decode_data, SwdAck};
+pub use sweep::spi_clock_sweep;
+pub use sync::{find_bad_command_echo, verify_sync_response, SyncError, BAD_COMMAND_RESPONSE};
+#[cfg(feature = "std")]
+pub use trace::{RecordingExecutor, ReplayError, ReplayExecutor, Trace, TraceEvent, TraceOp};
+pub use validate::{CommandBufferError, ValidationWarning};
 
 /// MPSSE opcodes.
 ///
@@ -16,7 +108,7 @@ use std::time::Duration;
 /// * [`ClockDataIn`]
 /// * [`ClockBitsIn`]
 /// * [`ClockData`]
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 #[repr(u8)]
 #[non_exhaustive]
 pub enum MpsseCmd {
@@ -48,11 +140,24 @@ pub enum MpsseCmd {
     Enable3PhaseClocking = 0x8C,
     /// Used by [`disable_3phase_data_clocking`][`MpsseCmdBuilder::disable_3phase_data_clocking`].
     Disable3PhaseClocking = 0x8D,
-    /// Used by [`disable_adaptive_data_clocking`][`MpsseCmdBuilder::disable_adaptive_data_clocking`].
-    EnableAdaptiveClocking = 0x96,
+    /// Used by [`clock_cycles_bits`][`MpsseCmdBuilder::clock_cycles_bits`].
+    ClockNBits = 0x8E,
+    /// Used by [`clock_cycles_bytes`][`MpsseCmdBuilder::clock_cycles_bytes`].
+    ClockNx8Bits = 0x8F,
+    /// Used by [`clock_until_io_high`][`MpsseCmdBuilder::clock_until_io_high`].
+    ClockUntilIOHigh = 0x94,
+    /// Used by [`clock_until_io_low`][`MpsseCmdBuilder::clock_until_io_low`].
+    ClockUntilIOLow = 0x95,
     /// Used by [`enable_adaptive_data_clocking`][`MpsseCmdBuilder::enable_adaptive_data_clocking`].
+    EnableAdaptiveClocking = 0x96,
+    /// Used by [`disable_adaptive_data_clocking`][`MpsseCmdBuilder::disable_adaptive_data_clocking`].
     DisableAdaptiveClocking = 0x97,
-    // EnableDriveOnlyZero = 0x9E,
+    /// Used by [`clock_n8_until_io_high`][`MpsseCmdBuilder::clock_n8_until_io_high`].
+    ClockNx8BitsUntilIOHigh = 0x9C,
+    /// Used by [`clock_n8_until_io_low`][`MpsseCmdBuilder::clock_n8_until_io_low`].
+    ClockNx8BitsUntilIOLow = 0x9D,
+    /// Used by [`enable_drive_only_zero`][`MpsseCmdBuilder::enable_drive_only_zero`].
+    EnableDriveOnlyZero = 0x9E,
 }
 
 /// Modes for clocking data out of the FTDI device.
@@ -281,6 +386,55 @@ impl From<MpsseCmd> for u8 {
     }
 }
 
+/// Decode an opcode, returning the opcode back as the error if it is not an
+/// [`MpsseCmd`].
+///
+/// Data clocking opcodes are not `MpsseCmd` variants, see [`ClockData`] and
+/// the other clocking mode types.
+///
+/// # Example
+///
+/// ```
+/// use ftdi_mpsse::MpsseCmd;
+/// use std::convert::TryFrom;
+///
+/// assert_eq!(MpsseCmd::try_from(0x87), Ok(MpsseCmd::SendImmediate));
+/// assert_eq!(MpsseCmd::try_from(0xAA), Err(0xAA));
+/// ```
+impl TryFrom<u8> for MpsseCmd {
+    type Error = u8;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        let cmd = match value {
+            0x80 => MpsseCmd::SetDataBitsLowbyte,
+            0x81 => MpsseCmd::GetDataBitsLowbyte,
+            0x82 => MpsseCmd::SetDataBitsHighbyte,
+            0x83 => MpsseCmd::GetDataBitsHighbyte,
+            0x84 => MpsseCmd::EnableLoopback,
+            0x85 => MpsseCmd::DisableLoopback,
+            0x86 => MpsseCmd::SetClockFrequency,
+            0x87 => MpsseCmd::SendImmediate,
+            0x88 => MpsseCmd::WaitOnIOHigh,
+            0x89 => MpsseCmd::WaitOnIOLow,
+            0x8A => MpsseCmd::DisableClockDivide,
+            0x8B => MpsseCmd::EnableClockDivide,
+            0x8C => MpsseCmd::Enable3PhaseClocking,
+            0x8D => MpsseCmd::Disable3PhaseClocking,
+            0x8E => MpsseCmd::ClockNBits,
+            0x8F => MpsseCmd::ClockNx8Bits,
+            0x94 => MpsseCmd::ClockUntilIOHigh,
+            0x95 => MpsseCmd::ClockUntilIOLow,
+            0x96 => MpsseCmd::EnableAdaptiveClocking,
+            0x97 => MpsseCmd::DisableAdaptiveClocking,
+            0x9C => MpsseCmd::ClockNx8BitsUntilIOHigh,
+            0x9D => MpsseCmd::ClockNx8BitsUntilIOLow,
+            0x9E => MpsseCmd::EnableDriveOnlyZero,
+            _ => return Err(value),
+        };
+        Ok(cmd)
+    }
+}
+
 /// Modes for clocking bits out on TMS for JTAG mode.
 ///
 /// This is an argument to the [`clock_tms_out`] method.
@@ -325,23 +479,41 @@ impl From<ClockTMS> for u8 {
     }
 }
 
+/// GPIO byte of the MPSSE interface.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum GpioByte {
+    /// Lower byte (0-7) GPIO pins.
+    Lower,
+    /// Upper byte (8-15) GPIO pins.
+    Upper,
+}
+
 /// Initialization settings for the MPSSE.
 ///
 /// Settings can be written to the device with the appropriate
 /// implementation of [`init`] method.
 ///
+/// With the `serde` feature enabled settings can be serialized. Durations
+/// are serialized as integer milliseconds, and missing fields take their
+/// [`Default`] value.
+///
 /// [`init`]: MpsseCmdExecutor::init
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct MpsseSettings {
     /// Reset the MPSSE on initialization.
     pub reset: bool,
     /// USB in transfer size in bytes.
     pub in_transfer_size: u32,
     /// Read timeout.
+    #[cfg_attr(feature = "serde", serde(with = "duration_millis"))]
     pub read_timeout: Duration,
     /// Write timeout.
+    #[cfg_attr(feature = "serde", serde(with = "duration_millis"))]
     pub write_timeout: Duration,
     /// Latency timer.
+    #[cfg_attr(feature = "serde", serde(with = "duration_millis"))]
     pub latency_timer: Duration,
     /// Bitmode mask.
     ///
@@ -354,7 +526,25 @@ pub struct MpsseSettings {
     pub clock_frequency: Option<u32>,
 }
 
-impl std::default::Default for MpsseSettings {
+/// Serialization of [`Duration`] as integer milliseconds.
+///
+/// Durations longer than `u64::MAX` milliseconds saturate.
+#[cfg(feature = "serde")]
+mod duration_millis {
+    use core::convert::TryFrom;
+    use core::time::Duration;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(u64::try_from(duration.as_millis()).unwrap_or(u64::MAX))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        u64::deserialize(deserializer).map(Duration::from_millis)
+    }
+}
+
+impl core::default::Default for MpsseSettings {
     fn default() -> Self {
         MpsseSettings {
             reset: true,
@@ -368,6 +558,59 @@ impl std::default::Default for MpsseSettings {
     }
 }
 
+impl MpsseSettings {
+    /// MPSSE commands run by [`init`] once the device is in MPSSE mode.
+    ///
+    /// Enables loopback, sends a bad command to synchronize with the device,
+    /// disables loopback and sets the clock frequency, if any. The device
+    /// responds with [`BAD_COMMAND_RESPONSE`] followed by the bad opcode.
+    ///
+    /// The clock divisor is computed for the 60 MHz base clock of the
+    /// FT232H, FT2232H and FT4232H. USB settings such as the transfer size,
+    /// timeouts and latency timer, the reset and the bitmode mask are not
+    /// MPSSE commands and are left to [`init`].
+    ///
+    /// The commands do not depend on the device state, so they can be built
+    /// once and sent again to reinitialize the MPSSE.
+    ///
+    /// This will panic if the clock frequency is not between 92 Hz and 30 MHz.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::MpsseSettings;
+    ///
+    /// let settings = MpsseSettings {
+    ///     clock_frequency: Some(1_000_000),
+    ///     ..MpsseSettings::default()
+    /// };
+    /// assert_eq!(
+    ///     settings.to_init_commands().as_slice(),
+    ///     [0x84, 0xAB, 0x87, 0x85, 0x8B, 0x86, 0x05, 0x00]
+    /// );
+    /// ```
+    ///
+    /// [`init`]: MpsseCmdExecutor::init
+    pub fn to_init_commands(&self) -> MpsseCmdBuilder {
+        let (cmd, _) = MpsseCmdBuilder::new().enable_loopback().sync_marker();
+        let cmd = cmd.send_immediate().disable_loopback();
+        match self.clock_frequency {
+            Some(frequency) => {
+                assert!(
+                    (92..=30_000_000).contains(&frequency),
+                    "clock frequency must be between 92 Hz and 30 MHz"
+                );
+                if frequency <= 6_000_000 {
+                    cmd.set_clock(6_000_000 / frequency - 1, Some(true))
+                } else {
+                    cmd.set_clock(30_000_000 / frequency - 1, Some(false))
+                }
+            }
+            None => cmd,
+        }
+    }
+}
+
 /// FTDI MPSSE configurator and executor
 pub trait MpsseCmdExecutor {
     /// Error type
@@ -387,6 +630,427 @@ pub trait MpsseCmdExecutor {
         self.send(txdata)?;
         self.recv(rxdata)
     }
+
+    /// Discard data received from the device and not read yet
+    ///
+    /// Used to drop the rest of a response that fell out of sync. The
+    /// default implementation does nothing.
+    fn purge(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Clock in a fixed layout value
+    ///
+    /// Reads `T::SIZE` bytes with the given clocking mode and builds the
+    /// value from them, see [`FromBytes`].
+    fn read_struct<T: FromBytes>(&mut self, mode: ClockDataIn) -> Result<T, Self::Error> {
+        let cmd = MpsseCmdBuilder::new()
+            .clock_data_in(mode, T::SIZE)
+            .send_immediate();
+        let mut data = vec![0; T::SIZE];
+        self.xfer(cmd.as_slice(), &mut data)?;
+        Ok(T::from_bytes(&data))
+    }
+
+    /// Execute MPSSE command and read response, reporting progress
+    ///
+    /// The response is received in chunks of up to 4096 bytes, and
+    /// `progress` is called with the number of bytes received so far and
+    /// the length of `rxdata` after each chunk.
+    fn xfer_with_progress(
+        &mut self,
+        txdata: &[u8],
+        rxdata: &mut [u8],
+        mut progress: impl FnMut(usize, usize),
+    ) -> Result<(), Self::Error> {
+        self.send(txdata)?;
+        let total = rxdata.len();
+        let mut done = 0;
+        for chunk in rxdata.chunks_mut(PROGRESS_CHUNK_LEN) {
+            self.recv(chunk)?;
+            done += chunk.len();
+            progress(done, total);
+        }
+        Ok(())
+    }
+
+    /// Execute MPSSE command and read response, checking the response length
+    ///
+    /// Nothing is sent if the length of `rxdata` does not match the number
+    /// of bytes the command stream makes the device return, see
+    /// [`MpsseCmdBuilder::read_len`]. A mismatch would otherwise hang until
+    /// the read times out, or leave stale data for the next transfer.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ftdi_mpsse::{MpsseCmdBuilder, MpsseCmdExecutor, XferError};
+    ///
+    /// # fn example<E: MpsseCmdExecutor>(mut exec: E) -> Result<(), XferError<E::Error>> {
+    /// let cmd = MpsseCmdBuilder::new().gpio_lower().send_immediate();
+    /// let mut gpio = [0; 1];
+    /// exec.xfer_checked(&cmd, &mut gpio)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn xfer_checked(
+        &mut self,
+        cmd: &MpsseCmdBuilder,
+        rxdata: &mut [u8],
+    ) -> Result<(), XferError<Self::Error>> {
+        let expected = cmd.read_len();
+        if rxdata.len() != expected {
+            return Err(XferError::ReadLenMismatch {
+                expected,
+                got: rxdata.len(),
+            });
+        }
+        self.xfer(cmd.as_slice(), rxdata)
+            .map_err(XferError::Executor)
+    }
+
+    /// Clock out data from a stream of chunks
+    ///
+    /// Each chunk is sent as soon as the iterator yields it, split in
+    /// [`clock_data_out`](MpsseCmdBuilder::clock_data_out) commands of up to
+    /// `u16::MAX + 1` bytes, so only one chunk is held in memory at a time.
+    /// Empty chunks are skipped.
+    fn stream_out(
+        &mut self,
+        mode: ClockDataOut,
+        chunks: impl IntoIterator<Item = Vec<u8>>,
+    ) -> Result<(), Self::Error> {
+        for chunk in chunks {
+            if chunk.is_empty() {
+                continue;
+            }
+            let mut cmd = MpsseCmdBuilder::new();
+            for data in chunk.chunks(MAX_CLOCK_DATA_LEN) {
+                cmd = cmd.clock_data_out(mode, data);
+            }
+            self.send(cmd.as_slice())?;
+        }
+        Ok(())
+    }
+}
+
+impl<E: MpsseCmdExecutor> MpsseCmdExecutor for &mut E {
+    type Error = E::Error;
+
+    fn init(&mut self, settings: &MpsseSettings) -> Result<(), Self::Error> {
+        (**self).init(settings)
+    }
+
+    fn send(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        (**self).send(data)
+    }
+
+    fn recv(&mut self, data: &mut [u8]) -> Result<(), Self::Error> {
+        (**self).recv(data)
+    }
+
+    fn xfer(&mut self, txdata: &[u8], rxdata: &mut [u8]) -> Result<(), Self::Error> {
+        (**self).xfer(txdata, rxdata)
+    }
+
+    fn purge(&mut self) -> Result<(), Self::Error> {
+        (**self).purge()
+    }
+}
+
+/// Location of data clocked in by a command within the device response.
+///
+/// Builder methods that read data from the device return a token recording
+/// where that data will be in the buffer read back after sending the whole
+/// command stream.
+///
+/// # Example
+///
+/// ```
+/// use ftdi_mpsse::{MpsseCmdBuilder, ReadToken};
+///
+/// let cmd = MpsseCmdBuilder::new().gpio_lower();
+/// let (cmd, token) = cmd.swd_read_ack();
+/// assert_eq!(token, ReadToken::new(1, 1));
+/// assert_eq!(token.get(&[0x00, 0x20]), &[0x20]);
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct ReadToken {
+    offset: usize,
+    len: usize,
+}
+
+impl ReadToken {
+    /// Create a token for `len` bytes starting at `offset`.
+    pub const fn new(offset: usize, len: usize) -> ReadToken {
+        ReadToken { offset, len }
+    }
+
+    /// Offset of the first byte in the response.
+    pub const fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Number of bytes in the response.
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the token does not cover any bytes.
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Range of the response covered by the token.
+    pub fn range(&self) -> core::ops::Range<usize> {
+        self.offset..self.offset + self.len
+    }
+
+    /// Get the bytes covered by the token from the device response.
+    ///
+    /// This will panic if the response is too short.
+    pub fn get<'a>(&self, response: &'a [u8]) -> &'a [u8] {
+        &response[self.range()]
+    }
+
+    /// Merge adjacent tokens into a single token.
+    ///
+    /// Returns `None` if `tokens` is empty, or if a token does not start
+    /// where the previous one ends.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::ReadToken;
+    ///
+    /// let tokens = [ReadToken::new(2, 1), ReadToken::new(3, 4)];
+    /// assert_eq!(ReadToken::merge(&tokens), Some(ReadToken::new(2, 5)));
+    /// assert_eq!(ReadToken::merge(&[tokens[1], tokens[0]]), None);
+    /// ```
+    pub fn merge(tokens: &[ReadToken]) -> Option<ReadToken> {
+        let (first, rest) = tokens.split_first()?;
+        rest.iter().try_fold(*first, |merged, token| {
+            if token.offset == merged.offset + merged.len {
+                Some(ReadToken::new(merged.offset, merged.len + token.len))
+            } else {
+                None
+            }
+        })
+    }
+}
+
+/// Maximum number of bytes clocked by a single data clocking command.
+const MAX_CLOCK_DATA_LEN: usize = 65536;
+
+/// Number of bytes received between progress reports of
+/// [`MpsseCmdExecutor::xfer_with_progress`].
+const PROGRESS_CHUNK_LEN: usize = 4096;
+
+/// Lower byte GPIO mask of a pin number.
+///
+/// This will panic for pin numbers greater than 7.
+fn pin_mask(pin: u8) -> u8 {
+    assert!(pin <= 7, "pin number cannot exceed 7");
+    1 << pin
+}
+
+/// Reverse the bit order of a byte.
+///
+/// Converts a byte between MSB first and LSB first bit order, for data that
+/// has to be clocked with a fixed bit order. Being a `const fn`, it can
+/// build the data of [`mpsse`] macro commands.
+/// [`clock_data_out_bitswap`](MpsseCmdBuilder::clock_data_out_bitswap)
+/// reverses every byte of the data it clocks out.
+///
+/// # Example
+///
+/// ```
+/// use ftdi_mpsse::{mpsse, reverse_bits, ClockDataOut};
+///
+/// assert_eq!(reverse_bits(0x01), 0x80);
+/// assert_eq!(reverse_bits(0x3A), 0x5C);
+///
+/// mpsse! {
+///     const (CMD, CMD_READ_LEN) = {
+///         clock_data_out(ClockDataOut::MsbNeg, [reverse_bits(0x01), reverse_bits(0x30)]);
+///     };
+/// }
+/// assert_eq!(CMD, [0x11, 0x01, 0x00, 0x80, 0x0C]);
+/// ```
+pub const fn reverse_bits(byte: u8) -> u8 {
+    byte.reverse_bits()
+}
+
+/// Base clock of the devices without a divide by 5 prescaler.
+const BASE_CLOCK_NO_PRESCALER_HZ: u32 = 12_000_000;
+
+/// Largest clock divisor.
+const MAX_CLOCK_DIVISOR: u32 = 0xFFFF;
+
+/// Clock divisor and divide by 5 setting for the frequency nearest to
+/// `target_hz`, see [`MpsseCmdBuilder::set_clock_hz`].
+fn clock_divisor(target_hz: u32, base_hz: u32) -> (u32, Option<bool>) {
+    let divisor = |base: u32| {
+        let period = 2 * u64::from(target_hz);
+        let divisor = ((u64::from(base) + period / 2) / period).saturating_sub(1);
+        divisor.min(u64::from(MAX_CLOCK_DIVISOR)) as u32
+    };
+    if base_hz <= BASE_CLOCK_NO_PRESCALER_HZ {
+        return (divisor(base_hz), None);
+    }
+    let undivided = divisor(base_hz);
+    if undivided < MAX_CLOCK_DIVISOR {
+        (undivided, Some(false))
+    } else {
+        (divisor(base_hz / 5), Some(true))
+    }
+}
+
+/// Error returned by [`patch`](MpsseCmdBuilder::patch) when the bytes do
+/// not fit within the command stream.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct PatchError {
+    /// Offset of the patch.
+    pub offset: usize,
+    /// Length of the patch in bytes.
+    pub len: usize,
+    /// Length of the command stream in bytes.
+    pub stream_len: usize,
+}
+
+impl fmt::Display for PatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "patch of {} bytes at offset {} exceeds the {} byte command stream",
+            self.len, self.offset, self.stream_len
+        )
+    }
+}
+
+impl core::error::Error for PatchError {}
+
+/// Reason a `try_` builder method, such as
+/// [`try_clock_data_out`](MpsseCmdBuilder::try_clock_data_out), failed, see
+/// [`TryBuildError`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum MpsseBuildError {
+    /// The data or clock length exceeds what a single command can clock.
+    DataTooLong {
+        /// Requested length.
+        got: usize,
+        /// Maximum length of the command.
+        max: usize,
+    },
+}
+
+impl fmt::Display for MpsseBuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MpsseBuildError::DataTooLong { got, max } => {
+                write!(f, "length {} exceeds the maximum of {}", got, max)
+            }
+        }
+    }
+}
+
+impl core::error::Error for MpsseBuildError {}
+
+/// Error returned by the `try_` builder methods, holding the builder as it
+/// was before the failed call.
+///
+/// # Example
+///
+/// ```
+/// use ftdi_mpsse::{ClockBitsOut, MpsseBuildError, MpsseCmdBuilder};
+///
+/// let cmd = MpsseCmdBuilder::new().gpio_lower();
+/// let err = cmd.try_clock_bits_out(ClockBitsOut::MsbNeg, 0x00, 9).unwrap_err();
+/// assert_eq!(err.error(), MpsseBuildError::DataTooLong { got: 9, max: 8 });
+/// // continue building with the commands added so far
+/// let cmd = err.into_inner().clock_bits_out(ClockBitsOut::MsbNeg, 0x00, 8);
+/// assert_eq!(cmd.as_slice(), [0x81, 0x13, 0x07, 0x00]);
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct TryBuildError {
+    cmd: MpsseCmdBuilder,
+    error: MpsseBuildError,
+}
+
+impl TryBuildError {
+    /// Reason the command could not be added.
+    pub fn error(&self) -> MpsseBuildError {
+        self.error
+    }
+
+    /// Get a reference to the builder.
+    pub fn inner(&self) -> &MpsseCmdBuilder {
+        &self.cmd
+    }
+
+    /// Unwrap the builder.
+    pub fn into_inner(self) -> MpsseCmdBuilder {
+        self.cmd
+    }
+}
+
+impl fmt::Display for TryBuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+impl core::error::Error for TryBuildError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// Error returned by [`MpsseCmdExecutor::xfer_checked`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum XferError<E> {
+    /// Error from the executor.
+    Executor(E),
+    /// The receive buffer length does not match the response length of the
+    /// command stream.
+    ReadLenMismatch {
+        /// Number of bytes the command stream makes the device return.
+        expected: usize,
+        /// Length of the receive buffer.
+        got: usize,
+    },
+}
+
+impl<E: fmt::Display> fmt::Display for XferError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            XferError::Executor(e) => e.fmt(f),
+            XferError::ReadLenMismatch { expected, got } => write!(
+                f,
+                "receive buffer of {} bytes for a response of {} bytes",
+                got, expected
+            ),
+        }
+    }
+}
+
+impl<E: core::error::Error + 'static> core::error::Error for XferError<E> {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            XferError::Executor(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Check a command length against the maximum of the command.
+fn check_len(got: usize, max: usize) -> Result<(), MpsseBuildError> {
+    if got <= max {
+        Ok(())
+    } else {
+        Err(MpsseBuildError::DataTooLong { got, max })
+    }
 }
 
 /// FTDI Multi-Protocol Synchronous Serial Engine (MPSSE) command builder.
@@ -407,8 +1071,93 @@ pub trait MpsseCmdExecutor {
 /// [FTDI MPSSE Basics]: https://www.ftdichip.com/Support/Documents/AppNotes/AN_135_MPSSE_Basics.pdf
 /// [`send`]: MpsseCmdExecutor::send
 /// [`xfer`]: MpsseCmdExecutor::xfer
+#[derive(Clone, Eq, PartialEq)]
 pub struct MpsseCmdBuilder(pub Vec<u8>);
 
+impl Default for MpsseCmdBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Hex dump of the command bytes for [`Debug`](fmt::Debug).
+struct HexBytes<'a>(&'a [u8]);
+
+impl fmt::Debug for HexBytes<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[")?;
+        for (i, byte) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{:02X}", byte)?;
+        }
+        f.write_str("]")
+    }
+}
+
+/// Shows the command bytes as a hex dump.
+///
+/// # Example
+///
+/// ```
+/// use ftdi_mpsse::MpsseCmdBuilder;
+///
+/// let cmd = MpsseCmdBuilder::new().set_gpio_lower(0x08, 0x0B);
+/// assert_eq!(
+///     format!("{:?}", cmd),
+///     "MpsseCmdBuilder { bytes: [80 08 0B] }"
+/// );
+/// ```
+impl fmt::Debug for MpsseCmdBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MpsseCmdBuilder")
+            .field("bytes", &HexBytes(&self.0))
+            .finish()
+    }
+}
+
+impl AsRef<[u8]> for MpsseCmdBuilder {
+    fn as_ref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+/// Dereferences to the command bytes, like
+/// [`as_slice`](MpsseCmdBuilder::as_slice).
+///
+/// # Example
+///
+/// ```
+/// use ftdi_mpsse::MpsseCmdBuilder;
+///
+/// let cmd = MpsseCmdBuilder::new().set_gpio_lower(0x08, 0x0B).send_immediate();
+/// assert_eq!(cmd.len(), 4);
+/// assert_eq!(&cmd[..3], [0x80, 0x08, 0x0B]);
+/// ```
+impl core::ops::Deref for MpsseCmdBuilder {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl IntoIterator for MpsseCmdBuilder {
+    type Item = u8;
+    type IntoIter = vec::IntoIter<u8>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl From<MpsseCmdBuilder> for Vec<u8> {
+    fn from(cmd: MpsseCmdBuilder) -> Vec<u8> {
+        cmd.0
+    }
+}
+
 impl MpsseCmdBuilder {
     /// Create a new command builder.
     ///
@@ -436,6 +1185,25 @@ impl MpsseCmdBuilder {
         MpsseCmdBuilder(vec)
     }
 
+    /// Create a new command builder with room for `capacity` bytes.
+    ///
+    /// Reserving the size of the whole command stream up front avoids
+    /// reallocations while building large batched transfers.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::{ClockDataOut, MpsseCmdBuilder};
+    ///
+    /// let data = [0x55; 1024];
+    /// let cmd = MpsseCmdBuilder::with_capacity(3 + data.len())
+    ///     .clock_data_out(ClockDataOut::MsbNeg, &data);
+    /// assert!(cmd.0.capacity() >= 1027);
+    /// ```
+    pub fn with_capacity(capacity: usize) -> MpsseCmdBuilder {
+        MpsseCmdBuilder::with_vec(Vec::with_capacity(capacity))
+    }
+
     /// Get the MPSSE command as a slice.
     ///
     /// # Example
@@ -456,29 +1224,309 @@ impl MpsseCmdBuilder {
         self.0.as_slice()
     }
 
+    /// Get the MPSSE command as a mutable slice.
+    ///
+    /// Bytes can be changed in place, for example to update a register
+    /// address in a stream sent repeatedly, but not added or removed. Changing
+    /// an opcode or a length field changes how the rest of the stream is
+    /// interpreted, see [`patch`](MpsseCmdBuilder::patch) for a bounds
+    /// checked alternative.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::{ClockDataOut, MpsseCmdBuilder};
+    ///
+    /// let mut cmd = MpsseCmdBuilder::new().clock_data_out(ClockDataOut::MsbNeg, &[0x03, 0x00]);
+    /// cmd.as_mut_slice()[4] = 0x10;
+    /// assert_eq!(cmd.as_slice(), [0x11, 0x01, 0x00, 0x03, 0x10]);
+    /// ```
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        self.0.as_mut_slice()
+    }
+
+    /// Overwrite the bytes of the command stream starting at `offset`.
+    ///
+    /// Returns an error and leaves the stream unchanged if the bytes do not
+    /// fit within the stream, patching never changes its length.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::{ClockDataOut, MpsseCmdBuilder};
+    ///
+    /// let mut cmd = MpsseCmdBuilder::new()
+    ///     .set_gpio_lower(0x00, 0x0B)
+    ///     .clock_data_out(ClockDataOut::MsbNeg, &[0x03, 0x00, 0x00]);
+    /// // read from register address 0x0010
+    /// cmd.patch(7, &[0x00, 0x10])?;
+    /// assert_eq!(cmd.as_slice()[6..], [0x03, 0x00, 0x10]);
+    /// assert!(cmd.patch(8, &[0x00, 0x10]).is_err());
+    /// # Ok::<(), ftdi_mpsse::PatchError>(())
+    /// ```
+    pub fn patch(&mut self, offset: usize, bytes: &[u8]) -> Result<(), PatchError> {
+        let stream_len = self.0.len();
+        match offset.checked_add(bytes.len()) {
+            Some(end) if end <= stream_len => {
+                self.0[offset..end].copy_from_slice(bytes);
+                Ok(())
+            }
+            _ => Err(PatchError {
+                offset,
+                len: bytes.len(),
+                stream_len,
+            }),
+        }
+    }
+
+    /// Append the commands of another builder.
+    ///
+    /// The vector of `other` is reused when this builder is empty and has no
+    /// more capacity than `other`, otherwise its bytes are moved to the end
+    /// of this builder.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::{ClockDataOut, MpsseCmdBuilder};
+    ///
+    /// let init = MpsseCmdBuilder::new().set_gpio_lower(0x08, 0x0B);
+    /// let write = MpsseCmdBuilder::new()
+    ///     .set_gpio_lower(0x00, 0x0B)
+    ///     .clock_data_out(ClockDataOut::MsbNeg, &[0x06])
+    ///     .set_gpio_lower(0x08, 0x0B);
+    /// let cmd = init.append(write).send_immediate();
+    /// assert_eq!(cmd.as_slice().len(), 14);
+    /// ```
+    pub fn append(mut self, mut other: MpsseCmdBuilder) -> Self {
+        if self.0.is_empty() && other.0.capacity() >= self.0.capacity() {
+            self.0 = other.0;
+        } else {
+            self.0.append(&mut other.0);
+        }
+        self
+    }
+
+    /// Append raw command bytes.
+    ///
+    /// This is the escape hatch for commands the builder does not model.
+    ///
+    /// **The bytes bypass all validation.** A malformed command makes the
+    /// device misinterpret the rest of the stream, and read lengths such as
+    /// [`read_len`](MpsseCmdBuilder::read_len) are computed from the bytes as
+    /// they are. See [`decode`] to check a command stream.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::MpsseCmdBuilder;
+    ///
+    /// let cmd = MpsseCmdBuilder::new().append_slice(&[0x80, 0x08, 0x0B]).gpio_lower();
+    /// assert_eq!(cmd.as_slice(), [0x80, 0x08, 0x0B, 0x81]);
+    /// ```
+    pub fn append_slice(mut self, bytes: &[u8]) -> Self {
+        self.0.extend_from_slice(bytes);
+        self
+    }
+
+    /// Append a command opcode followed by raw argument bytes.
+    ///
+    /// **The arguments bypass all validation**, they must be exactly the
+    /// arguments the command expects, see
+    /// [`append_slice`](MpsseCmdBuilder::append_slice).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::{MpsseCmd, MpsseCmdBuilder};
+    ///
+    /// let cmd = MpsseCmdBuilder::new().raw_cmd(MpsseCmd::EnableDriveOnlyZero, &[0x03, 0x00]);
+    /// assert_eq!(cmd.as_slice(), [0x9E, 0x03, 0x00]);
+    /// ```
+    pub fn raw_cmd(mut self, cmd: MpsseCmd, args: &[u8]) -> Self {
+        self.0.reserve(args.len() + 1);
+        self.0.push(cmd.into());
+        self.0.extend_from_slice(args);
+        self
+    }
+
+    /// Number of bytes the device returns for the command stream.
+    ///
+    /// Scans the commands, skipping over the data clocked out, and sums the
+    /// bytes returned by data reads, GPIO reads and the echo of unrecognized
+    /// opcodes. An incomplete command at the end of the stream is ignored.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::{ClockBitsIn, ClockData, MpsseCmdBuilder};
+    ///
+    /// let cmd = MpsseCmdBuilder::new()
+    ///     .clock_data(ClockData::MsbPosIn, &[0x81, 0x83])
+    ///     .clock_bits_in(ClockBitsIn::MsbPos, 3)
+    ///     .gpio_lower()
+    ///     .send_immediate();
+    /// assert_eq!(cmd.read_len(), 4);
+    ///
+    /// let mut buf = vec![0; cmd.read_len()];
+    /// ```
+    pub fn read_len(&self) -> usize {
+        parse::read_len(self.as_slice())
+    }
+
+    /// Token for `len` bytes read by the next command.
+    fn read_token(&self, len: usize) -> ReadToken {
+        ReadToken::new(self.read_len(), len)
+    }
+
     /// Set the MPSSE clock frequency using provided
     /// divisor value and clock divider configuration.
     /// Both parameters are device dependent.
     ///
     /// # Example
     ///
-    /// ```no_run
+    /// ```no_run
+    /// use ftdi_mpsse::MpsseCmdBuilder;
+    ///
+    /// let cmd = MpsseCmdBuilder::new().set_clock(9, Some(false));
+    ///
+    /// ```
+    pub fn set_clock(mut self, divisor: u32, clkdiv: Option<bool>) -> Self {
+        match clkdiv {
+            Some(true) => self.0.push(MpsseCmd::EnableClockDivide.into()),
+            Some(false) => self.0.push(MpsseCmd::DisableClockDivide.into()),
+            None => {}
+        };
+
+        self.0.extend_from_slice(&[
+            MpsseCmd::SetClockFrequency.into(),
+            (divisor & 0xFF) as u8,
+            ((divisor >> 8) & 0xFF) as u8,
+        ]);
+
+        self
+    }
+
+    /// Set the MPSSE clock frequency nearest to a target frequency.
+    ///
+    /// The clock frequency is `base / (2 * (divisor + 1))`, the divisor is
+    /// rounded to the nearest frequency and clamped to the 16-bit range, so
+    /// the actual frequency may differ from the target. For a 60 MHz base
+    /// clock the divide by 5 is disabled, and enabled only for frequencies
+    /// below 458 Hz. Devices with a base clock of 12 MHz or less have no
+    /// divide by 5 and it is not configured.
+    ///
+    /// # Arguments
+    ///
+    /// * `target_hz` - Target clock frequency in Hz.
+    /// * `device_base_hz` - Base clock of the device, 60 MHz for the H series
+    ///   devices and 12 MHz for the FT2232C/D.
+    ///
+    /// This will panic if `target_hz` is zero.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::MpsseCmdBuilder;
+    ///
+    /// // 60 MHz / (2 * (2 + 1)) = 10 MHz
+    /// let cmd = MpsseCmdBuilder::new().set_clock_hz(10_000_000, 60_000_000);
+    /// assert_eq!(cmd.as_slice(), [0x8A, 0x86, 0x02, 0x00]);
+    ///
+    /// // 12 MHz / (2 * (4 + 1)) = 1.2 MHz
+    /// let cmd = MpsseCmdBuilder::new().set_clock_hz(1_100_000, 12_000_000);
+    /// assert_eq!(cmd.as_slice(), [0x86, 0x04, 0x00]);
+    /// ```
+    pub fn set_clock_hz(self, target_hz: u32, device_base_hz: u32) -> Self {
+        assert!(target_hz != 0, "clock frequency cannot be zero");
+        let (divisor, clkdiv) = clock_divisor(target_hz, device_base_hz);
+        self.set_clock(divisor, clkdiv)
+    }
+
+    /// Toggle the clock for `clocks` cycles without transferring data.
+    pub(crate) fn dummy_clocks(mut self, mut clocks: usize) -> Self {
+        while clocks >= 8 {
+            let len = (clocks / 8).min(65536);
+            let arg = len - 1;
+            self.0.extend_from_slice(&[
+                MpsseCmd::ClockNx8Bits.into(),
+                (arg & 0xFF) as u8,
+                ((arg >> 8) & 0xFF) as u8,
+            ]);
+            clocks -= len * 8;
+        }
+        self.clock_cycles_bits(clocks as u8)
+    }
+
+    /// Check a command length, returning the builder in the error.
+    fn len_checked(self, got: usize, max: usize) -> Result<Self, TryBuildError> {
+        match check_len(got, max) {
+            Ok(()) => Ok(self),
+            Err(error) => Err(TryBuildError { cmd: self, error }),
+        }
+    }
+
+    /// Toggle the clock for 1 to 8 cycles without transferring data.
+    ///
+    /// Useful for SPI dummy cycles and short delays, nothing is read back.
+    ///
+    /// # Arguments
+    ///
+    /// * `len` - Number of clock cycles.
+    ///   This will panic for values greater than 8.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::MpsseCmdBuilder;
+    ///
+    /// let cmd = MpsseCmdBuilder::new().clock_cycles_bits(4);
+    /// assert_eq!(cmd.as_slice(), [0x8E, 0x03]);
+    /// ```
+    pub fn clock_cycles_bits(self, len: u8) -> Self {
+        self.try_clock_cycles_bits(len)
+            .expect("clock length cannot exceed 8")
+    }
+
+    /// Non-panicking [`clock_cycles_bits`](MpsseCmdBuilder::clock_cycles_bits).
+    ///
+    /// Returns an error for lengths greater than 8 clock cycles.
+    pub fn try_clock_cycles_bits(mut self, mut len: u8) -> Result<Self, TryBuildError> {
+        self = self.len_checked(usize::from(len), 8)?;
+        if len == 0 {
+            return Ok(self);
+        }
+        len -= 1;
+        self.0
+            .extend_from_slice(&[MpsseCmd::ClockNBits.into(), len]);
+        Ok(self)
+    }
+
+    /// Toggle the clock for `len` groups of 8 cycles without transferring
+    /// data.
+    ///
+    /// Useful for precise delays and JTAG run-test/idle padding, nothing is
+    /// read back.
+    ///
+    /// # Example
+    ///
+    /// ```
     /// use ftdi_mpsse::MpsseCmdBuilder;
     ///
-    /// let cmd = MpsseCmdBuilder::new().set_clock(9, Some(false));
-    ///
+    /// // 1000 * 8 clock cycles
+    /// let cmd = MpsseCmdBuilder::new().clock_cycles_bytes(1000);
+    /// assert_eq!(cmd.as_slice(), [0x8F, 0xE7, 0x03]);
     /// ```
-    pub fn set_clock(mut self, divisor: u32, clkdiv: Option<bool>) -> Self {
-        match clkdiv {
-            Some(true) => self.0.push(MpsseCmd::EnableClockDivide.into()),
-            Some(false) => self.0.push(MpsseCmd::DisableClockDivide.into()),
-            None => {}
-        };
-
-        self.0.push(MpsseCmd::SetClockFrequency.into());
-        self.0.push((divisor & 0xFF) as u8);
-        self.0.push(((divisor >> 8) & 0xFF) as u8);
-
+    pub fn clock_cycles_bytes(mut self, len: u16) -> Self {
+        if len == 0 {
+            return self;
+        }
+        let len = len - 1;
+        self.0.extend_from_slice(&[
+            MpsseCmd::ClockNx8Bits.into(),
+            (len & 0xFF) as u8,
+            ((len >> 8) & 0xFF) as u8,
+        ]);
         self
     }
 
@@ -591,7 +1639,7 @@ impl MpsseCmdBuilder {
         self
     }
 
-    /// Enable adaptive data clocking.
+    /// Disable adaptive data clocking.
     ///
     /// This is only available on FTx232H devices.
     pub fn disable_adaptive_data_clocking(mut self) -> Self {
@@ -599,6 +1647,63 @@ impl MpsseCmdBuilder {
         self
     }
 
+    /// Enable drive-only-zero on the selected GPIO pins.
+    ///
+    /// This is only available on the FT232H.
+    ///
+    /// Output pins selected by the masks only drive low and are tristated when
+    /// set high, letting an external pull-up pull the line high. This is the
+    /// open-drain drive needed by I2C on SDA and SCL.
+    ///
+    /// # Arguments
+    ///
+    /// * `lower_mask` - Lower byte (0-7) pins, `1` enables drive-only-zero.
+    /// * `upper_mask` - Upper byte (8-15) pins, `1` enables drive-only-zero.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ftdi_mpsse::MpsseCmdBuilder;
+    /// use libftd2xx::{Ft232h, FtdiCommon, FtdiMpsse};
+    ///
+    /// // open-drain SCL and SDA on AD0 and AD1
+    /// let cmd = MpsseCmdBuilder::new()
+    ///     .enable_drive_only_zero(0x03, 0x00)
+    ///     .enable_3phase_data_clocking();
+    ///
+    /// let mut ft = Ft232h::with_serial_number("FT5AVX6B")?;
+    /// ft.initialize_mpsse_default()?;
+    /// ft.write_all(cmd.as_slice())?;
+    /// # Ok::<(), std::boxed::Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn enable_drive_only_zero(mut self, lower_mask: u8, upper_mask: u8) -> Self {
+        self.0
+            .extend_from_slice(&[MpsseCmd::EnableDriveOnlyZero.into(), lower_mask, upper_mask]);
+        self
+    }
+
+    /// Disable drive-only-zero on all GPIO pins.
+    ///
+    /// This is only available on the FT232H.
+    ///
+    /// The MPSSE has no separate disable opcode, this sends the
+    /// [`enable_drive_only_zero`] command with both masks cleared, restoring
+    /// push-pull drive on every pin.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::MpsseCmdBuilder;
+    ///
+    /// let cmd = MpsseCmdBuilder::new().disable_drive_only_zero();
+    /// assert_eq!(cmd.as_slice(), [0x9E, 0x00, 0x00]);
+    /// ```
+    ///
+    /// [`enable_drive_only_zero`]: MpsseCmdBuilder::enable_drive_only_zero
+    pub fn disable_drive_only_zero(self) -> Self {
+        self.enable_drive_only_zero(0x00, 0x00)
+    }
+
     /// Set the pin direction and state of the lower byte (0-7) GPIO pins on the
     /// MPSSE interface.
     ///
@@ -785,22 +1890,200 @@ impl MpsseCmdBuilder {
         self
     }
 
+    /// Toggle the clock without transferring data until GPIOL1 or I/O1 is
+    /// high.
+    ///
+    /// Unlike [`wait_on_io_high`](MpsseCmdBuilder::wait_on_io_high) the clock
+    /// keeps running while waiting, for targets that need it to make
+    /// progress. The wait is unbounded, see
+    /// [`clock_n8_until_io_high`](MpsseCmdBuilder::clock_n8_until_io_high)
+    /// for a bounded form.
+    ///
+    /// This is only available on FTx232H devices.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::MpsseCmdBuilder;
+    ///
+    /// let cmd = MpsseCmdBuilder::new().clock_until_io_high();
+    /// assert_eq!(cmd.as_slice(), [0x94]);
+    /// ```
+    pub fn clock_until_io_high(mut self) -> Self {
+        self.0.push(MpsseCmd::ClockUntilIOHigh.into());
+        self
+    }
+
+    /// Toggle the clock without transferring data until GPIOL1 or I/O1 is
+    /// low.
+    ///
+    /// The wait is unbounded, see
+    /// [`clock_n8_until_io_low`](MpsseCmdBuilder::clock_n8_until_io_low) for
+    /// a bounded form.
+    ///
+    /// This is only available on FTx232H devices.
+    pub fn clock_until_io_low(mut self) -> Self {
+        self.0.push(MpsseCmd::ClockUntilIOLow.into());
+        self
+    }
+
+    /// Toggle the clock without transferring data for up to `n` groups of 8
+    /// cycles, stopping early once GPIOL1 or I/O1 is high.
+    ///
+    /// This is only available on FTx232H devices.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::MpsseCmdBuilder;
+    ///
+    /// // time out after 256 * 8 clock cycles
+    /// let cmd = MpsseCmdBuilder::new().clock_n8_until_io_high(256);
+    /// assert_eq!(cmd.as_slice(), [0x9C, 0xFF, 0x00]);
+    /// ```
+    pub fn clock_n8_until_io_high(self, n: u16) -> Self {
+        self.clock_n8_until(MpsseCmd::ClockNx8BitsUntilIOHigh, n)
+    }
+
+    /// Toggle the clock without transferring data for up to `n` groups of 8
+    /// cycles, stopping early once GPIOL1 or I/O1 is low.
+    ///
+    /// This is only available on FTx232H devices.
+    pub fn clock_n8_until_io_low(self, n: u16) -> Self {
+        self.clock_n8_until(MpsseCmd::ClockNx8BitsUntilIOLow, n)
+    }
+
+    fn clock_n8_until(mut self, cmd: MpsseCmd, n: u16) -> Self {
+        if n == 0 {
+            return self;
+        }
+        let n = n - 1;
+        self.0
+            .extend_from_slice(&[cmd.into(), (n & 0xFF) as u8, ((n >> 8) & 0xFF) as u8]);
+        self
+    }
+
     /// Clock data out.
     ///
     /// This will clock out bytes on TDI/DO.
     /// No data is clocked into the device on TDO/DI.
     ///
     /// This will panic for data lengths greater than `u16::MAX + 1`.
-    pub fn clock_data_out(mut self, mode: ClockDataOut, data: &[u8]) -> Self {
+    pub fn clock_data_out(self, mode: ClockDataOut, data: &[u8]) -> Self {
+        self.try_clock_data_out(mode, data)
+            .expect("data length cannot exceed u16::MAX + 1")
+    }
+
+    /// Non-panicking [`clock_data_out`](MpsseCmdBuilder::clock_data_out).
+    ///
+    /// Returns an error for data lengths greater than `u16::MAX + 1`, 65536
+    /// bytes.
+    pub fn try_clock_data_out(
+        mut self,
+        mode: ClockDataOut,
+        data: &[u8],
+    ) -> Result<Self, TryBuildError> {
         let mut len = data.len();
-        assert!(len <= 65536, "data length cannot exceed u16::MAX + 1");
+        self = self.len_checked(len, MAX_CLOCK_DATA_LEN)?;
         if len == 0 {
-            return self;
+            return Ok(self);
         }
         len -= 1;
+        self.0.reserve(data.len() + 3);
         self.0
             .extend_from_slice(&[mode.into(), (len & 0xFF) as u8, ((len >> 8) & 0xFF) as u8]);
         self.0.extend_from_slice(data);
+        Ok(self)
+    }
+
+    /// Clock data out, reversing the bit order of each byte.
+    ///
+    /// This is equivalent to [`clock_data_out`] with every byte of `data`
+    /// bit-reversed, without making a reversed copy of `data`. Use it when
+    /// the bit order of the data does not match the bit order of `mode`.
+    ///
+    /// This will panic for data lengths greater than `u16::MAX + 1`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::{ClockDataOut, MpsseCmdBuilder};
+    ///
+    /// let cmd = MpsseCmdBuilder::new().clock_data_out_bitswap(ClockDataOut::MsbNeg, &[0x01, 0x30]);
+    /// assert_eq!(cmd.as_slice(), [0x11, 0x01, 0x00, 0x80, 0x0C]);
+    /// ```
+    ///
+    /// [`clock_data_out`]: MpsseCmdBuilder::clock_data_out
+    pub fn clock_data_out_bitswap(self, mode: ClockDataOut, data: &[u8]) -> Self {
+        self.try_clock_data_out_bitswap(mode, data)
+            .expect("data length cannot exceed u16::MAX + 1")
+    }
+
+    /// Non-panicking [`clock_data_out_bitswap`](MpsseCmdBuilder::clock_data_out_bitswap).
+    ///
+    /// Returns an error for data lengths greater than `u16::MAX + 1`, 65536
+    /// bytes.
+    pub fn try_clock_data_out_bitswap(
+        mut self,
+        mode: ClockDataOut,
+        data: &[u8],
+    ) -> Result<Self, TryBuildError> {
+        let mut len = data.len();
+        self = self.len_checked(len, MAX_CLOCK_DATA_LEN)?;
+        if len == 0 {
+            return Ok(self);
+        }
+        len -= 1;
+        self.0.reserve(data.len() + 3);
+        self.0
+            .extend_from_slice(&[mode.into(), (len & 0xFF) as u8, ((len >> 8) & 0xFF) as u8]);
+        self.0.extend(data.iter().map(|byte| byte.reverse_bits()));
+        Ok(self)
+    }
+
+    /// Clock data out from an iterator.
+    ///
+    /// Like [`clock_data_out`](MpsseCmdBuilder::clock_data_out), with the
+    /// data taken from an iterator yielding exactly `len` bytes, so lazily
+    /// generated data does not have to be collected first.
+    ///
+    /// This will panic if `len` is greater than `u16::MAX + 1`. Debug builds
+    /// also panic if the iterator does not yield exactly `len` bytes, release
+    /// builds ignore the extra bytes of a longer iterator and leave the
+    /// command short for a shorter one.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::{ClockDataOut, MpsseCmdBuilder};
+    ///
+    /// let cmd = MpsseCmdBuilder::new().clock_data_out_iter(ClockDataOut::MsbNeg, 3, (1..=3).map(|i| i * 2));
+    /// assert_eq!(cmd.as_slice(), [0x11, 0x02, 0x00, 0x02, 0x04, 0x06]);
+    /// ```
+    pub fn clock_data_out_iter<I: IntoIterator<Item = u8>>(
+        mut self,
+        mode: ClockDataOut,
+        len: usize,
+        data: I,
+    ) -> Self {
+        check_len(len, MAX_CLOCK_DATA_LEN).expect("data length cannot exceed u16::MAX + 1");
+        let mut data = data.into_iter();
+        if len > 0 {
+            let arg = len - 1;
+            self.0.reserve(len + 3);
+            self.0
+                .extend_from_slice(&[mode.into(), (arg & 0xFF) as u8, ((arg >> 8) & 0xFF) as u8]);
+            let start = self.0.len();
+            self.0.extend(data.by_ref().take(len));
+            debug_assert!(
+                self.0.len() - start == len,
+                "iterator yielded less than len bytes"
+            );
+        }
+        debug_assert!(
+            data.next().is_none(),
+            "iterator yielded more than len bytes"
+        );
         self
     }
 
@@ -813,50 +2096,99 @@ impl MpsseCmdBuilder {
     ///
     /// * `mode` - Data clocking mode.
     /// * `len` - Number of bytes to clock in.
-    ///           This will panic for values greater than `u16::MAX + 1`.
+    ///   Lengths greater than `u16::MAX + 1` are split into several commands.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::{ClockDataIn, MpsseCmdBuilder};
+    ///
+    /// let cmd = MpsseCmdBuilder::new().clock_data_in(ClockDataIn::MsbPos, 70000);
+    /// assert_eq!(cmd.as_slice(), [0x20, 0xFF, 0xFF, 0x20, 0x6F, 0x11]);
+    /// assert_eq!(cmd.read_len(), 70000);
+    /// ```
     pub fn clock_data_in(mut self, mode: ClockDataIn, mut len: usize) -> Self {
-        assert!(len <= 65536, "data length cannot exceed u16::MAX + 1");
-        if len == 0 {
-            return self;
+        while len > 0 {
+            let block = len.min(MAX_CLOCK_DATA_LEN);
+            let arg = block - 1;
+            self.0
+                .extend_from_slice(&[mode.into(), (arg & 0xFF) as u8, ((arg >> 8) & 0xFF) as u8]);
+            len -= block;
         }
-        len -= 1;
-        self.0
-            .extend_from_slice(&[mode.into(), (len & 0xFF) as u8, ((len >> 8) & 0xFF) as u8]);
         self
     }
 
     /// Clock data in and out simultaneously.
     ///
     /// This will panic for data lengths greater than `u16::MAX + 1`.
-    pub fn clock_data(mut self, mode: ClockData, data: &[u8]) -> Self {
+    pub fn clock_data(self, mode: ClockData, data: &[u8]) -> Self {
+        self.try_clock_data(mode, data)
+            .expect("data length cannot exceed u16::MAX + 1")
+    }
+
+    /// Non-panicking [`clock_data`](MpsseCmdBuilder::clock_data).
+    ///
+    /// Returns an error for data lengths greater than `u16::MAX + 1`, 65536
+    /// bytes.
+    pub fn try_clock_data(mut self, mode: ClockData, data: &[u8]) -> Result<Self, TryBuildError> {
         let mut len = data.len();
-        assert!(len <= 65536, "data length cannot exceed u16::MAX + 1");
+        self = self.len_checked(len, MAX_CLOCK_DATA_LEN)?;
         if len == 0 {
-            return self;
+            return Ok(self);
         }
         len -= 1;
+        self.0.reserve(data.len() + 3);
         self.0
             .extend_from_slice(&[mode.into(), (len & 0xFF) as u8, ((len >> 8) & 0xFF) as u8]);
         self.0.extend_from_slice(data);
-        self
+        Ok(self)
     }
 
     /// Clock data bits out.
     ///
+    /// MSB first modes clock out the `len` most significant bits of `data`,
+    /// starting with bit 7, LSB first modes clock out the `len` least
+    /// significant bits, starting with bit 0. The other bits of `data` are
+    /// ignored, [`validate`](MpsseCmdBuilder::validate) warns when any of
+    /// them is set.
+    ///
     /// # Arguments
     ///
     /// * `mode` - Bit clocking mode.
     /// * `data` - Data bits.
     /// * `len` - Number of bits to clock out.
-    ///           This will panic for values greater than 8.
-    pub fn clock_bits_out(mut self, mode: ClockBitsOut, data: u8, mut len: u8) -> Self {
-        assert!(len <= 8, "data length cannot exceed 8");
+    ///   This will panic for values greater than 8.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::{ClockBitsOut, MpsseCmdBuilder};
+    ///
+    /// // clocks out 1, 0, 1 from bits 7, 6 and 5
+    /// let cmd = MpsseCmdBuilder::new().clock_bits_out(ClockBitsOut::MsbNeg, 0xA0, 3);
+    /// assert!(cmd.validate(0x00).is_empty());
+    /// ```
+    pub fn clock_bits_out(self, mode: ClockBitsOut, data: u8, len: u8) -> Self {
+        self.try_clock_bits_out(mode, data, len)
+            .expect("data length cannot exceed 8")
+    }
+
+    /// Non-panicking [`clock_bits_out`](MpsseCmdBuilder::clock_bits_out).
+    ///
+    /// Returns an error for lengths greater than 8 bits.
+    pub fn try_clock_bits_out(
+        mut self,
+        mode: ClockBitsOut,
+        data: u8,
+        mut len: u8,
+    ) -> Result<Self, TryBuildError> {
+        self = self.len_checked(usize::from(len), 8)?;
         if len == 0 {
-            return self;
+            return Ok(self);
         }
         len -= 1;
         self.0.extend_from_slice(&[mode.into(), len, data]);
-        self
+        Ok(self)
     }
 
     /// Clock data bits in.
@@ -865,32 +2197,61 @@ impl MpsseCmdBuilder {
     ///
     /// * `mode` - Bit clocking mode.
     /// * `len` - Number of bits to clock in.
-    ///           This will panic for values greater than 8.
-    pub fn clock_bits_in(mut self, mode: ClockBitsIn, mut len: u8) -> Self {
-        assert!(len <= 8, "data length cannot exceed 8");
+    ///   This will panic for values greater than 8.
+    pub fn clock_bits_in(self, mode: ClockBitsIn, len: u8) -> Self {
+        self.try_clock_bits_in(mode, len)
+            .expect("data length cannot exceed 8")
+    }
+
+    /// Non-panicking [`clock_bits_in`](MpsseCmdBuilder::clock_bits_in).
+    ///
+    /// Returns an error for lengths greater than 8 bits.
+    pub fn try_clock_bits_in(
+        mut self,
+        mode: ClockBitsIn,
+        mut len: u8,
+    ) -> Result<Self, TryBuildError> {
+        self = self.len_checked(usize::from(len), 8)?;
         if len == 0 {
-            return self;
+            return Ok(self);
         }
         len -= 1;
         self.0.extend_from_slice(&[mode.into(), len]);
-        self
+        Ok(self)
     }
 
     /// Clock data bits in and out simultaneously.
     ///
+    /// The bits of `data` clocked out are the same as for
+    /// [`clock_bits_out`](MpsseCmdBuilder::clock_bits_out).
+    ///
     /// # Arguments
     ///
     /// * `mode` - Bit clocking mode.
+    /// * `data` - Data bits.
     /// * `len` - Number of bits to clock in.
-    ///           This will panic for values greater than 8.
-    pub fn clock_bits(mut self, mode: ClockBits, data: u8, mut len: u8) -> Self {
-        assert!(len <= 8, "data length cannot exceed 8");
+    ///   This will panic for values greater than 8.
+    pub fn clock_bits(self, mode: ClockBits, data: u8, len: u8) -> Self {
+        self.try_clock_bits(mode, data, len)
+            .expect("data length cannot exceed 8")
+    }
+
+    /// Non-panicking [`clock_bits`](MpsseCmdBuilder::clock_bits).
+    ///
+    /// Returns an error for lengths greater than 8 bits.
+    pub fn try_clock_bits(
+        mut self,
+        mode: ClockBits,
+        data: u8,
+        mut len: u8,
+    ) -> Result<Self, TryBuildError> {
+        self = self.len_checked(usize::from(len), 8)?;
         if len == 0 {
-            return self;
+            return Ok(self);
         }
         len -= 1;
         self.0.extend_from_slice(&[mode.into(), len, data]);
-        self
+        Ok(self)
     }
 
     /// Clock TMS bits out.
@@ -901,23 +2262,63 @@ impl MpsseCmdBuilder {
     /// * `data` - TMS bits.
     /// * `tdi` - Value to place on TDI while clocking.
     /// * `len` - Number of bits to clock out.
-    ///           This will panic for values greater than 7.
-    pub fn clock_tms_out(
+    ///   This will panic for values greater than 7.
+    pub fn clock_tms_out(self, mode: ClockTMSOut, data: u8, tdi: bool, len: u8) -> Self {
+        self.try_clock_tms_out(mode, data, tdi, len)
+            .expect("data length cannot exceed 7")
+    }
+
+    /// Non-panicking [`clock_tms_out`](MpsseCmdBuilder::clock_tms_out).
+    ///
+    /// Returns an error for lengths greater than 7 bits.
+    pub fn try_clock_tms_out(
         mut self,
         mode: ClockTMSOut,
         mut data: u8,
         tdi: bool,
         mut len: u8,
-    ) -> Self {
-        assert!(len <= 7, "data length cannot exceed 7");
+    ) -> Result<Self, TryBuildError> {
+        self = self.len_checked(usize::from(len), 7)?;
         if len == 0 {
-            return self;
+            return Ok(self);
         }
         len -= 1;
         if tdi {
             data |= 0x80;
         }
         self.0.extend_from_slice(&[mode.into(), len, data]);
+        Ok(self)
+    }
+
+    /// Clock a TMS sequence of any length out.
+    ///
+    /// The sequence is split into [`clock_tms_out`] commands of up to 7
+    /// bits, the first bit of `bits` is clocked out first. TDI is held at
+    /// `tdi` for the whole sequence, including the last bit.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::{ClockTMSOut, MpsseCmdBuilder};
+    ///
+    /// // Test-Logic-Reset from any state, then Run-Test/Idle
+    /// let cmd = MpsseCmdBuilder::new().clock_tms_out_seq(
+    ///     ClockTMSOut::NegEdge,
+    ///     &[true, true, true, true, true, false, false],
+    ///     false,
+    /// );
+    /// assert_eq!(cmd.as_slice(), [0x4B, 0x06, 0x1F]);
+    /// ```
+    ///
+    /// [`clock_tms_out`]: MpsseCmdBuilder::clock_tms_out
+    pub fn clock_tms_out_seq(mut self, mode: ClockTMSOut, bits: &[bool], tdi: bool) -> Self {
+        for chunk in bits.chunks(7) {
+            let data = chunk
+                .iter()
+                .rev()
+                .fold(0, |data, &bit| data << 1 | u8::from(bit));
+            self = self.clock_tms_out(mode, data, tdi, chunk.len() as u8);
+        }
         self
     }
 
@@ -929,18 +2330,139 @@ impl MpsseCmdBuilder {
     /// * `data` - TMS bits.
     /// * `tdi` - Value to place on TDI while clocking.
     /// * `len` - Number of bits to clock out.
-    ///           This will panic for values greater than 7.
-    pub fn clock_tms(mut self, mode: ClockTMS, mut data: u8, tdi: bool, mut len: u8) -> Self {
-        assert!(len <= 7, "data length cannot exceed 7");
+    ///   This will panic for values greater than 7.
+    pub fn clock_tms(self, mode: ClockTMS, data: u8, tdi: bool, len: u8) -> Self {
+        self.try_clock_tms(mode, data, tdi, len)
+            .expect("data length cannot exceed 7")
+    }
+
+    /// Non-panicking [`clock_tms`](MpsseCmdBuilder::clock_tms).
+    ///
+    /// Returns an error for lengths greater than 7 bits.
+    pub fn try_clock_tms(
+        mut self,
+        mode: ClockTMS,
+        mut data: u8,
+        tdi: bool,
+        mut len: u8,
+    ) -> Result<Self, TryBuildError> {
+        self = self.len_checked(usize::from(len), 7)?;
         if len == 0 {
-            return self;
+            return Ok(self);
         }
         len -= 1;
         if tdi {
             data |= 0x80;
         }
         self.0.extend_from_slice(&[mode.into(), len, data]);
-        self
+        Ok(self)
+    }
+}
+
+/// Data inserted into the command data built by the [`mpsse`] macro.
+#[doc(hidden)]
+#[derive(Copy, Clone)]
+pub enum __MpsseSplice<'a> {
+    /// Named array.
+    Array(&'a [u8]),
+    /// Start of a `repeat` block.
+    RepeatStart,
+    /// End of a `repeat` block, with the number of repetitions.
+    RepeatEnd(usize),
+}
+
+/// Length of the command data built by the [`mpsse`] macro.
+///
+/// `out` is the length of the data of the macro rules, and `splices` the
+/// data inserted with the number of bytes of `out` preceding it, in order.
+#[doc(hidden)]
+pub const fn __mpsse_splice_len<const M: usize>(
+    out: usize,
+    splices: &[(usize, __MpsseSplice); M],
+) -> usize {
+    let mut len = 0;
+    let mut starts = [0; M];
+    let mut depth = 0;
+    let mut consumed = 0;
+    let mut splice = 0;
+    loop {
+        while splice < M && splices[splice].0 == consumed {
+            match splices[splice].1 {
+                __MpsseSplice::Array(array) => len += array.len(),
+                __MpsseSplice::RepeatStart => {
+                    starts[depth] = len;
+                    depth += 1;
+                }
+                __MpsseSplice::RepeatEnd(times) => {
+                    depth -= 1;
+                    len = starts[depth] + (len - starts[depth]) * times;
+                }
+            }
+            splice += 1;
+        }
+        if consumed == out {
+            return len;
+        }
+        len += 1;
+        consumed += 1;
+    }
+}
+
+/// Build the command data of the [`mpsse`] macro, see
+/// [`__mpsse_splice_len`].
+#[doc(hidden)]
+pub const fn __mpsse_splice<const N: usize, const M: usize>(
+    out: &[u8],
+    splices: &[(usize, __MpsseSplice); M],
+) -> [u8; N] {
+    let mut data = [0; N];
+    let mut len = 0;
+    let mut starts = [0; M];
+    let mut depth = 0;
+    let mut consumed = 0;
+    let mut splice = 0;
+    loop {
+        while splice < M && splices[splice].0 == consumed {
+            match splices[splice].1 {
+                __MpsseSplice::Array(array) => {
+                    let mut i = 0;
+                    while i < array.len() {
+                        data[len] = array[i];
+                        len += 1;
+                        i += 1;
+                    }
+                }
+                __MpsseSplice::RepeatStart => {
+                    starts[depth] = len;
+                    depth += 1;
+                }
+                __MpsseSplice::RepeatEnd(times) => {
+                    depth -= 1;
+                    let start = starts[depth];
+                    let body = len - start;
+                    if times == 0 {
+                        len = start;
+                    }
+                    let mut copy = 1;
+                    while copy < times {
+                        let mut i = 0;
+                        while i < body {
+                            data[len] = data[start + i];
+                            len += 1;
+                            i += 1;
+                        }
+                        copy += 1;
+                    }
+                }
+            }
+            splice += 1;
+        }
+        if consumed == out.len() {
+            return data;
+        }
+        data[len] = out[consumed];
+        len += 1;
+        consumed += 1;
     }
 }
 
@@ -971,6 +2493,10 @@ impl MpsseCmdBuilder {
 /// * [`disable_loopback()`][`MpsseCmdBuilder::disable_loopback`]
 /// * [`enable_3phase_data_clocking()`][`MpsseCmdBuilder::enable_3phase_data_clocking`]
 /// * [`disable_3phase_data_clocking()`][`MpsseCmdBuilder::disable_3phase_data_clocking`]
+/// * [`enable_adaptive_data_clocking()`][`MpsseCmdBuilder::enable_adaptive_data_clocking`]
+/// * [`disable_adaptive_data_clocking()`][`MpsseCmdBuilder::disable_adaptive_data_clocking`]
+/// * [`enable_drive_only_zero(lower_mask: u8, upper_mask: u8)`][`MpsseCmdBuilder::enable_drive_only_zero`]
+/// * [`disable_drive_only_zero()`][`MpsseCmdBuilder::disable_drive_only_zero`]
 /// * [`set_gpio_lower(state: u8, direction: u8)`][`MpsseCmdBuilder::set_gpio_lower`]
 /// * [`set_gpio_upper(state: u8, direction: u8)`][`MpsseCmdBuilder::set_gpio_upper`]
 /// * [`gpio_lower() -> usize`][`MpsseCmdBuilder::gpio_lower`]
@@ -978,12 +2504,18 @@ impl MpsseCmdBuilder {
 /// * [`send_immediate()`][`MpsseCmdBuilder::send_immediate`]
 /// * [`wait_on_io_high()`][`MpsseCmdBuilder::wait_on_io_high`]
 /// * [`wait_on_io_low()`][`MpsseCmdBuilder::wait_on_io_low`]
+/// * [`clock_until_io_high()`][`MpsseCmdBuilder::clock_until_io_high`]
+/// * [`clock_until_io_low()`][`MpsseCmdBuilder::clock_until_io_low`]
+/// * [`clock_n8_until_io_high(n: u16)`][`MpsseCmdBuilder::clock_n8_until_io_high`]
+/// * [`clock_n8_until_io_low(n: u16)`][`MpsseCmdBuilder::clock_n8_until_io_low`]
 /// * [`clock_data_out(mode: ClockDataOut, data: [u8])`][`MpsseCmdBuilder::clock_data_out`]
 /// * [`clock_data_in(mode: ClockDataIn, len: u16) -> std::ops::Range<usize>`][`MpsseCmdBuilder::clock_data_in`]
 /// * [`clock_data(mode: ClockData, data: [u8]) -> std::ops::Range<usize>`][`MpsseCmdBuilder::clock_data`]
 /// * [`clock_bits_out(mode: ClockBitsOut, data: u8, len: u8)`][`MpsseCmdBuilder::clock_bits_out`]
 /// * [`clock_bits_in(mode: ClockBitsIn, len: u8) -> usize`][`MpsseCmdBuilder::clock_bits_in`]
 /// * [`clock_bits(mode: ClockBits, data: u8, len: u8) -> usize`][`MpsseCmdBuilder::clock_bits`]
+/// * [`clock_cycles_bits(len: u8)`][`MpsseCmdBuilder::clock_cycles_bits`]
+/// * [`clock_cycles_bytes(len: u16)`][`MpsseCmdBuilder::clock_cycles_bytes`]
 /// * [`clock_tms_out(mode: ClockTMSOut, data: u8, tdi: bool, len: u8)`][`MpsseCmdBuilder::clock_tms_out`]
 /// * [`clock_tms(mode: ClockTMS, data: u8, tdi: bool, len: u8) -> usize`][`MpsseCmdBuilder::clock_tms`]
 ///
@@ -997,12 +2529,54 @@ impl MpsseCmdBuilder {
 ///     };
 /// }
 /// ```
-/// This provides a constant [`Range`][`std::ops::Range`] or [`usize`] index value that may be used
+/// This provides a constant [`Range`][`core::ops::Range`] or [`usize`] index value that may be used
 /// to subscript the data read from the device.
 ///
-/// `clock_data` and `clock_data_out` require that the second argument is a fixed-length, square
-/// bracketed list of `u8` values. Compile-time limitations make arbitrary array concatenation or
-/// coercion infeasible.
+/// `clock_data` and `clock_data_out` take either a fixed-length, square bracketed list of `u8`
+/// values, or the name of a `u8` array constant. The array must be a `const` item, not a local
+/// variable, since its length is part of the type of the command data.
+///
+/// ```
+/// use ftdi_mpsse::{mpsse, ClockData, ClockDataOut};
+///
+/// const PAYLOAD: [u8; 3] = [0x12, 0x34, 0x56];
+///
+/// mpsse! {
+///     const (COMMAND_DATA, READ_LEN) = {
+///         clock_data_out(ClockDataOut::MsbNeg, PAYLOAD);
+///         const DATA_RANGE = clock_data(ClockData::MsbPosIn, PAYLOAD);
+///     };
+/// }
+/// assert_eq!(COMMAND_DATA, [0x11, 2, 0, 0x12, 0x34, 0x56, 0x31, 2, 0, 0x12, 0x34, 0x56]);
+/// assert_eq!(DATA_RANGE, 0..3);
+/// ```
+///
+/// # Repeat
+///
+/// A `repeat N { ... }` block emits the commands it contains `N` times, `N` being an integer
+/// literal, the name of a `usize` constant, or a parenthesized const expression. Blocks can be
+/// nested, and the read length accounts for every repetition.
+///
+/// Index and range constants bound within a `repeat` block refer to the data read by the first
+/// repetition, the data of repetition `i` is offset by `i` times the read length of the block.
+///
+/// ```
+/// use ftdi_mpsse::{mpsse, ClockBitsIn};
+///
+/// mpsse! {
+///     const (COMMAND_DATA, READ_LEN) = {
+///         repeat 3 {
+///             set_gpio_lower(0x01, 0x01);
+///             const BIT_INDEX = clock_bits_in(ClockBitsIn::MsbPos, 1);
+///             set_gpio_lower(0x00, 0x01);
+///         }
+///         send_immediate();
+///     };
+/// }
+/// assert_eq!(COMMAND_DATA.len(), 3 * 8 + 1);
+/// assert_eq!(READ_LEN, 3);
+/// assert_eq!(BIT_INDEX, 0);
+/// ```
 ///
 /// # Asserts
 ///
@@ -1054,7 +2628,7 @@ impl MpsseCmdBuilder {
 ///
 ///     // Everything else handled by libftd2xx crate implementation.
 ///     ($($tokens:tt)*) => {
-///         ::ftdi_mpsse::mpsse!($($tokens)*);
+///         ::ftdi_mpsse::mpsse!($($tokens)*)
 ///     };
 /// }
 ///
@@ -1106,7 +2680,7 @@ macro_rules! mpsse {
     //
     // Temporarily running a let expansion can be helpful to diagnose errors.
     (@assert ((let, $_user_passthru:tt), $_read_len:expr), $e:expr, $msg:expr) => {
-        ::std::assert!($e, $msg);
+        ::core::assert!($e, $msg);
     };
     (@assert ((const, $_user_passthru:tt), $_read_len:expr), $e:expr, $_msg:expr) => {
         ::static_assertions::const_assert!($e);
@@ -1117,25 +2691,25 @@ macro_rules! mpsse {
 
     // let command_data = { command1(); command2(); ... commandN(); };
     (let $id:ident = {$($commands:tt)*}; $($tail:tt)*) => {
-        mpsse!(((let, ($id, _)), 0) {$($commands)*} -> []);
+        mpsse!(((let, ($id, _, [])), 0) {$($commands)*} -> []);
         mpsse!($($tail)*);
     };
 
     // const COMMAND_DATA = { command1(); command2(); ... commandN(); };
     (const $id:ident = {$($commands:tt)*}; $($tail:tt)*) => {
-        mpsse!(((const, ($id, _)), 0) {$($commands)*} -> []);
+        mpsse!(((const, ($id, _, [])), 0) {$($commands)*} -> []);
         mpsse!($($tail)*);
     };
 
     // let (command_data, READ_LEN) = { command1(); command2(); ... commandN(); };
     (let ($id:ident, $read_len_id:ident) = {$($commands:tt)*}; $($tail:tt)*) => {
-        mpsse!(((let, ($id, $read_len_id)), 0) {$($commands)*} -> []);
+        mpsse!(((let, ($id, $read_len_id, [])), 0) {$($commands)*} -> []);
         mpsse!($($tail)*);
     };
 
     // const (COMMAND_DATA, READ_LEN) = { command1(); command2(); ... commandN(); };
     (const ($id:ident, $read_len_id:ident) = {$($commands:tt)*}; $($tail:tt)*) => {
-        mpsse!(((const, ($id, $read_len_id)), 0) {$($commands)*} -> []);
+        mpsse!(((const, ($id, $read_len_id, [])), 0) {$($commands)*} -> []);
         mpsse!($($tail)*);
     };
 
@@ -1154,6 +2728,18 @@ macro_rules! mpsse {
     // Additionally, the following form is used to provide the invoker with a usize index or
     // range to later access a specific data read `const READ_INDEX = <FUNCTION NAME>();`:
     // (($passthru:tt, $read_len:tt) {const $idx_id:ident = <FUNCTION NAME>(); $($tail:tt)*} -> [$($out:tt)*])
+    //
+    // Data from a named const array cannot be appended to the output token by token. The
+    // position in the output and the array identifier are recorded in the passthru instead, and
+    // the array is spliced into the output when emitting the command data. Repeat blocks are
+    // expanded once, recording their start and end in the same way to be copied on emission.
+
+    ((($const_let:tt, ($id:tt, $read_len_id:tt, [$($splices:tt)*])), $read_len:tt) {repeat $n:tt {$($body:tt)*} $($tail:tt)*} -> [$($out:tt)*]) => {
+        mpsse!((($const_let, ($id, $read_len_id, [$($splices)* ((mpsse!(@count_elements $($out)*)), $crate::__MpsseSplice::RepeatStart)])), $read_len) {$($body)* @repeat_end($n, $read_len); $($tail)*} -> [$($out)*]);
+    };
+    ((($const_let:tt, ($id:tt, $read_len_id:tt, [$($splices:tt)*])), $read_len:tt) {@repeat_end($n:tt, $start_read_len:tt); $($tail:tt)*} -> [$($out:tt)*]) => {
+        mpsse!((($const_let, ($id, $read_len_id, [$($splices)* ((mpsse!(@count_elements $($out)*)), $crate::__MpsseSplice::RepeatEnd($n))])), ($start_read_len + ($n) * ($read_len - $start_read_len))) {$($tail)*} -> [$($out)*]);
+    };
 
     ($passthru:tt {enable_loopback(); $($tail:tt)*} -> [$($out:tt)*]) => {
         mpsse!($passthru {$($tail)*} -> [$($out)* $crate::MpsseCmd::EnableLoopback as u8,]);
@@ -1168,10 +2754,16 @@ macro_rules! mpsse {
         mpsse!($passthru {$($tail)*} -> [$($out)* $crate::MpsseCmd::Disable3PhaseClocking as u8,]);
     };
     ($passthru:tt {enable_adaptive_data_clocking(); $($tail:tt)*} -> [$($out:tt)*]) => {
-        mpsse!($passthru {$($tail)*} -> [$($out)* $crate::MpsseCmd::EnableDataClocking as u8,]);
+        mpsse!($passthru {$($tail)*} -> [$($out)* $crate::MpsseCmd::EnableAdaptiveClocking as u8,]);
     };
     ($passthru:tt {disable_adaptive_data_clocking(); $($tail:tt)*} -> [$($out:tt)*]) => {
-        mpsse!($passthru {$($tail)*} -> [$($out)* $crate::MpsseCmd::DisableDataClocking as u8,]);
+        mpsse!($passthru {$($tail)*} -> [$($out)* $crate::MpsseCmd::DisableAdaptiveClocking as u8,]);
+    };
+    ($passthru:tt {enable_drive_only_zero($lower_mask:expr, $upper_mask:expr); $($tail:tt)*} -> [$($out:tt)*]) => {
+        mpsse!($passthru {$($tail)*} -> [$($out)* $crate::MpsseCmd::EnableDriveOnlyZero as u8, $lower_mask as u8, $upper_mask as u8,]);
+    };
+    ($passthru:tt {disable_drive_only_zero(); $($tail:tt)*} -> [$($out:tt)*]) => {
+        mpsse!($passthru {$($tail)*} -> [$($out)* $crate::MpsseCmd::EnableDriveOnlyZero as u8, 0_u8, 0_u8,]);
     };
     ($passthru:tt {set_gpio_lower($state:expr, $direction:expr); $($tail:tt)*} -> [$($out:tt)*]) => {
         mpsse!($passthru {$($tail)*} -> [$($out)* $crate::MpsseCmd::SetDataBitsLowbyte as u8, $state as u8, $direction as u8,]);
@@ -1202,6 +2794,24 @@ macro_rules! mpsse {
     ($passthru:tt {wait_on_io_low(); $($tail:tt)*} -> [$($out:tt)*]) => {
         mpsse!($passthru {$($tail)*} -> [$($out)* $crate::MpsseCmd::WaitOnIOLow as u8,]);
     };
+    ($passthru:tt {clock_until_io_high(); $($tail:tt)*} -> [$($out:tt)*]) => {
+        mpsse!($passthru {$($tail)*} -> [$($out)* $crate::MpsseCmd::ClockUntilIOHigh as u8,]);
+    };
+    ($passthru:tt {clock_until_io_low(); $($tail:tt)*} -> [$($out:tt)*]) => {
+        mpsse!($passthru {$($tail)*} -> [$($out)* $crate::MpsseCmd::ClockUntilIOLow as u8,]);
+    };
+    ($passthru:tt {clock_n8_until_io_high($n:expr); $($tail:tt)*} -> [$($out:tt)*]) => {
+        mpsse!(@assert $passthru, (($n) as usize > 0_usize && ($n) as usize <= 65535_usize), "clock length must be in 1..=u16::MAX");
+        mpsse!($passthru {$($tail)*} -> [$($out)* $crate::MpsseCmd::ClockNx8BitsUntilIOHigh as u8,
+        ((($n) - 1) & 0xFF_usize) as u8,
+        (((($n) - 1) >> 8) & 0xFF_usize) as u8,]);
+    };
+    ($passthru:tt {clock_n8_until_io_low($n:expr); $($tail:tt)*} -> [$($out:tt)*]) => {
+        mpsse!(@assert $passthru, (($n) as usize > 0_usize && ($n) as usize <= 65535_usize), "clock length must be in 1..=u16::MAX");
+        mpsse!($passthru {$($tail)*} -> [$($out)* $crate::MpsseCmd::ClockNx8BitsUntilIOLow as u8,
+        ((($n) - 1) & 0xFF_usize) as u8,
+        (((($n) - 1) >> 8) & 0xFF_usize) as u8,]);
+    };
     ($passthru:tt {clock_data_out($mode:expr, [$($data:expr),* $(,)*]); $($tail:tt)*} -> [$($out:tt)*]) => {
         mpsse!(@assert $passthru, (mpsse!(@count_elements $($data,)*) as usize > 0_usize && mpsse!(@count_elements $($data,)*) as usize <= 65536_usize), "data length must be in 1..=(u16::MAX + 1)");
         mpsse!($passthru {$($tail)*} -> [$($out)* $mode as $crate::ClockDataOut as u8,
@@ -1209,6 +2819,12 @@ macro_rules! mpsse {
         (((mpsse!(@count_elements $($data,)*) - 1) >> 8) & 0xFF_usize) as u8,
         $($data as u8,)*]);
     };
+    ((($const_let:tt, ($id:tt, $read_len_id:tt, [$($splices:tt)*])), $read_len:tt) {clock_data_out($mode:expr, $data:ident); $($tail:tt)*} -> [$($out:tt)*]) => {
+        mpsse!(@assert (($const_let, $id), $read_len), ($data.len() > 0_usize && $data.len() <= 65536_usize), "data length must be in 1..=(u16::MAX + 1)");
+        mpsse!((($const_let, ($id, $read_len_id, [$($splices)* ((mpsse!(@count_elements $($out)* 0, 0, 0)), $crate::__MpsseSplice::Array(&$data))])), $read_len) {$($tail)*} -> [$($out)* $mode as $crate::ClockDataOut as u8,
+        (($data.len() - 1) & 0xFF_usize) as u8,
+        ((($data.len() - 1) >> 8) & 0xFF_usize) as u8,]);
+    };
     (($passthru:tt, $read_len:tt) {clock_data_in($mode:expr, $len:expr); $($tail:tt)*} -> [$($out:tt)*]) => {
         mpsse!(@assert ($passthru, $read_len), (($len) as usize > 0_usize && ($len) as usize <= 65536_usize), "data length must be in 1..=(u16::MAX + 1)");
         mpsse!(($passthru, ($read_len + ($len))) {$($tail)*} -> [$($out)* $mode as $crate::ClockDataIn as u8,
@@ -1216,7 +2832,7 @@ macro_rules! mpsse {
         (((($len) - 1) >> 8) & 0xFF_usize) as u8,]);
     };
     (($passthru:tt, $read_len:tt) {const $range_id:ident = clock_data_in($mode:expr, $len:expr); $($tail:tt)*} -> [$($out:tt)*]) => {
-        const $range_id: ::std::ops::Range<usize> = $read_len..$read_len + ($len);
+        const $range_id: ::core::ops::Range<usize> = $read_len..$read_len + ($len);
         mpsse!(($passthru, $read_len) {clock_data_in($mode, $len); $($tail)*} -> [$($out)*]);
     };
     (($passthru:tt, $read_len:tt) {clock_data($mode:expr, [$($data:expr),* $(,)*]); $($tail:tt)*} -> [$($out:tt)*]) => {
@@ -1227,9 +2843,19 @@ macro_rules! mpsse {
         $($data as u8,)*]);
     };
     (($passthru:tt, $read_len:tt) {const $range_id:ident = clock_data($mode:expr, [$($data:expr),* $(,)*]); $($tail:tt)*} -> [$($out:tt)*]) => {
-        const $range_id: ::std::ops::Range<usize> = $read_len..$read_len + mpsse!(@count_elements $($data,)*);
+        const $range_id: ::core::ops::Range<usize> = $read_len..$read_len + mpsse!(@count_elements $($data,)*);
         mpsse!(($passthru, $read_len) {clock_data($mode, [$($data,)*]); $($tail)*} -> [$($out)*]);
     };
+    ((($const_let:tt, ($id:tt, $read_len_id:tt, [$($splices:tt)*])), $read_len:tt) {clock_data($mode:expr, $data:ident); $($tail:tt)*} -> [$($out:tt)*]) => {
+        mpsse!(@assert (($const_let, $id), $read_len), ($data.len() > 0_usize && $data.len() <= 65536_usize), "data length must be in 1..=(u16::MAX + 1)");
+        mpsse!((($const_let, ($id, $read_len_id, [$($splices)* ((mpsse!(@count_elements $($out)* 0, 0, 0)), $crate::__MpsseSplice::Array(&$data))])), ($read_len + $data.len())) {$($tail)*} -> [$($out)* $mode as $crate::ClockData as u8,
+        (($data.len() - 1) & 0xFF_usize) as u8,
+        ((($data.len() - 1) >> 8) & 0xFF_usize) as u8,]);
+    };
+    (($passthru:tt, $read_len:tt) {const $range_id:ident = clock_data($mode:expr, $data:ident); $($tail:tt)*} -> [$($out:tt)*]) => {
+        const $range_id: ::core::ops::Range<usize> = $read_len..$read_len + $data.len();
+        mpsse!(($passthru, $read_len) {clock_data($mode, $data); $($tail)*} -> [$($out)*]);
+    };
     ($passthru:tt {clock_bits_out($mode:expr, $data:expr, $len:expr); $($tail:tt)*} -> [$($out:tt)*]) => {
         mpsse!(@assert $passthru, ($len as u8 > 0_u8 && $len as u8 <= 8_u8), "data length must be in 1..=8");
         mpsse!($passthru {$($tail)*} -> [$($out)* $mode as $crate::ClockBitsOut as u8, (($len) - 1) as u8, $data as u8,]);
@@ -1250,6 +2876,16 @@ macro_rules! mpsse {
         const $idx_id: usize = $read_len;
         mpsse!(($passthru, $read_len) {clock_bits($mode, $data, $len); $($tail)*} -> [$($out)*]);
     };
+    ($passthru:tt {clock_cycles_bits($len:expr); $($tail:tt)*} -> [$($out:tt)*]) => {
+        mpsse!(@assert $passthru, ($len as u8 > 0_u8 && $len as u8 <= 8_u8), "clock length must be in 1..=8");
+        mpsse!($passthru {$($tail)*} -> [$($out)* $crate::MpsseCmd::ClockNBits as u8, (($len) - 1) as u8,]);
+    };
+    ($passthru:tt {clock_cycles_bytes($len:expr); $($tail:tt)*} -> [$($out:tt)*]) => {
+        mpsse!(@assert $passthru, (($len) as usize > 0_usize && ($len) as usize <= 65535_usize), "clock length must be in 1..=u16::MAX");
+        mpsse!($passthru {$($tail)*} -> [$($out)* $crate::MpsseCmd::ClockNx8Bits as u8,
+        ((($len) - 1) & 0xFF_usize) as u8,
+        (((($len) - 1) >> 8) & 0xFF_usize) as u8,]);
+    };
     ($passthru:tt {clock_tms_out($mode:expr, $data:expr, $tdi:expr, $len:expr); $($tail:tt)*} -> [$($out:tt)*]) => {
         mpsse!(@assert $passthru, ($len as u8 > 0_u8 && $len as u8 <= 7_u8), "data length must be in 1..=7");
         mpsse!($passthru {$($tail)*} -> [$($out)* $mode as $crate::ClockTMSOut as u8, (($len) - 1) as u8, ($data as u8) | if $tdi { 0x80 } else { 0 },]);
@@ -1264,13 +2900,17 @@ macro_rules! mpsse {
     };
 
     // Emit command_data
-    ((($const_let:tt, ($id:tt, _)), $read_len:expr) {} -> [$($out:tt)*]) => {
+    ((($const_let:tt, ($id:tt, _, [])), $read_len:expr) {} -> [$($out:tt)*]) => {
         $const_let $id: [u8; mpsse!(@count_elements $($out)*)] = [$($out)*];
     };
+    ((($const_let:tt, ($id:tt, _, [$(($pos:expr, $splice:expr))+])), $read_len:expr) {} -> [$($out:tt)*]) => {
+        $const_let $id: [u8; $crate::__mpsse_splice_len(mpsse!(@count_elements $($out)*), &[$(($pos, $splice),)+])] =
+            $crate::__mpsse_splice(&[$($out)*], &[$(($pos, $splice),)+]);
+    };
 
     // Emit command_data, READ_LEN
-    ((($const_let:tt, ($id:tt, $read_len_id:tt)), $read_len:expr) {} -> [$($out:tt)*]) => {
-        $const_let $id: [u8; mpsse!(@count_elements $($out)*)] = [$($out)*];
+    ((($const_let:tt, ($id:tt, $read_len_id:tt, $splices:tt)), $read_len:expr) {} -> [$($out:tt)*]) => {
+        mpsse!((($const_let, ($id, _, $splices)), $read_len) {} -> [$($out)*]);
         const $read_len_id: usize = $read_len;
     };
 }
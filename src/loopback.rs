@@ -0,0 +1,135 @@
+//! Simulation of the device responses in loopback mode.
+use crate::{parse, MpsseCmdBuilder, BAD_COMMAND_RESPONSE};
+use alloc::vec::Vec;
+
+/// Device state relevant to loopback responses.
+///
+/// In loopback mode TDI/DO is connected to TDO/DI inside the device, so data
+/// clocked in is the data clocked out. Read-only commands read the level TDI/DO
+/// was left at.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub(crate) struct LoopbackModel {
+    /// Lower byte GPIO state and direction.
+    lower: (u8, u8),
+    /// Upper byte GPIO state and direction.
+    upper: (u8, u8),
+    /// Level of TDI/DO.
+    tdi: bool,
+}
+
+impl LoopbackModel {
+    /// Append the response to a single command.
+    pub(crate) fn respond(&mut self, cmd: &[u8], response: &mut Vec<u8>) {
+        let opcode = cmd[0];
+        if parse::is_clocking(opcode) {
+            self.respond_clocking(cmd, response);
+        } else if parse::is_bad_command(opcode) {
+            response.extend_from_slice(&[BAD_COMMAND_RESPONSE, opcode]);
+        } else {
+            match opcode {
+                0x80 => {
+                    self.lower = (cmd[1], cmd[2]);
+                    self.tdi = cmd[1] & 0x02 != 0;
+                }
+                0x81 => response.push(self.lower.0 & self.lower.1),
+                0x82 => self.upper = (cmd[1], cmd[2]),
+                0x83 => response.push(self.upper.0 & self.upper.1),
+                _ => {}
+            }
+        }
+    }
+
+    fn respond_clocking(&mut self, cmd: &[u8], response: &mut Vec<u8>) {
+        let opcode = cmd[0];
+        let bit_mode = opcode & 0x02 != 0;
+        let lsb = opcode & 0x08 != 0;
+        let write_tdi = opcode & 0x10 != 0;
+        let read = opcode & 0x20 != 0;
+        let write_tms = opcode & 0x40 != 0;
+
+        if !bit_mode {
+            let count = (usize::from(cmd[1]) | (usize::from(cmd[2]) << 8)) + 1;
+            if write_tdi {
+                let data = &cmd[3..];
+                if read {
+                    response.extend_from_slice(data);
+                }
+                let last = data[data.len() - 1];
+                self.tdi = if lsb {
+                    last & 0x80 != 0
+                } else {
+                    last & 0x01 != 0
+                };
+            } else if read {
+                let level = if self.tdi { 0xFF } else { 0x00 };
+                response.resize(response.len() + count, level);
+            }
+            return;
+        }
+
+        // the device only uses the low 3 bits of the bit mode length
+        let bits = u32::from(cmd[1] & 0x07) + 1;
+        // bits clocked out on TDI/DO, first bit in the LSB
+        let out = if write_tms {
+            // TDI/DO is held at bit 7 of the data byte
+            self.tdi = cmd[2] & 0x80 != 0;
+            if self.tdi {
+                0xFF
+            } else {
+                0x00
+            }
+        } else if write_tdi {
+            let data = if lsb { cmd[2] } else { cmd[2].reverse_bits() };
+            self.tdi = data >> (bits - 1) & 0x01 != 0;
+            data
+        } else if self.tdi {
+            0xFF
+        } else {
+            0x00
+        };
+        if read {
+            let mask = 0xFF >> (8 - bits);
+            // LSB first reads shift in from the MSB, MSB first reads from the LSB
+            let data = if lsb {
+                (out & mask) << (8 - bits)
+            } else {
+                (out & mask).reverse_bits() >> (8 - bits)
+            };
+            response.push(data);
+        }
+    }
+}
+
+impl MpsseCmdBuilder {
+    /// Response of the device to the command stream in loopback mode.
+    ///
+    /// Simulates every command assuming loopback is enabled for the whole
+    /// stream, with the device in its reset state: data clocked in is the
+    /// data clocked out on TDI/DO, and read-only commands read the level
+    /// TDI/DO was left at. GPIO reads return the state of the pins set as
+    /// outputs, pins set as inputs read low. Bits not clocked by bit mode
+    /// commands read as zeros.
+    ///
+    /// An incomplete command at the end of the stream is ignored.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::{ClockBits, ClockData, MpsseCmdBuilder};
+    ///
+    /// let cmd = MpsseCmdBuilder::new()
+    ///     .enable_loopback()
+    ///     .clock_data(ClockData::MsbPosIn, &[0x12, 0x34])
+    ///     .clock_bits(ClockBits::LsbPosIn, 0x05, 3)
+    ///     .send_immediate();
+    /// assert_eq!(cmd.expected_loopback_response(), [0x12, 0x34, 0xA0]);
+    /// ```
+    pub fn expected_loopback_response(&self) -> Vec<u8> {
+        let mut model = LoopbackModel::default();
+        let mut response = Vec::with_capacity(parse::read_len(self.as_slice()));
+        for cmd in parse::commands(self.as_slice()).map_while(Result::ok) {
+            model.respond(cmd.bytes, &mut response);
+        }
+        response
+    }
+}
@@ -0,0 +1,242 @@
+//! Mock executors for testing command construction.
+use crate::loopback::LoopbackModel;
+use crate::{parse, MpsseCmd, MpsseCmdExecutor, MpsseSettings};
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// Error returned by [`MockMpsseExecutor`] and [`LoopbackExecutor`] when a
+/// receive asks for more bytes than the responses left.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct MockError {
+    /// Number of bytes requested.
+    pub requested: usize,
+    /// Number of response bytes left.
+    pub available: usize,
+}
+
+impl fmt::Display for MockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "received {} bytes with {} response bytes left",
+            self.requested, self.available
+        )
+    }
+}
+
+impl core::error::Error for MockError {}
+
+/// Executor recording the data sent and answering with canned responses,
+/// without hardware.
+///
+/// Every [`send`](MpsseCmdExecutor::send), including the one of each
+/// [`xfer`](MpsseCmdExecutor::xfer), is recorded.
+/// [`recv`](MpsseCmdExecutor::recv) takes its bytes from the front of the
+/// canned responses, and fails without consuming any if there are not
+/// enough.
+///
+/// # Example
+///
+/// ```
+/// use ftdi_mpsse::{MockMpsseExecutor, MpsseCmdBuilder, MpsseCmdExecutor};
+///
+/// let mut exec = MockMpsseExecutor::new();
+/// exec.push_response(&[0x5A]);
+///
+/// let cmd = MpsseCmdBuilder::new().gpio_lower().send_immediate();
+/// let mut gpio = [0; 1];
+/// exec.xfer(cmd.as_slice(), &mut gpio)?;
+/// assert_eq!(gpio, [0x5A]);
+/// exec.assert_sent(&[0x81, 0x87]);
+/// # Ok::<(), ftdi_mpsse::MockError>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MockMpsseExecutor {
+    settings: Option<MpsseSettings>,
+    sent: Vec<Vec<u8>>,
+    responses: VecDeque<u8>,
+}
+
+impl MockMpsseExecutor {
+    /// Create an executor without canned responses.
+    pub fn new() -> MockMpsseExecutor {
+        MockMpsseExecutor::default()
+    }
+
+    /// Queue bytes returned by the following receives.
+    pub fn push_response(&mut self, data: &[u8]) {
+        self.responses.extend(data);
+    }
+
+    /// Settings passed to the last [`init`](MpsseCmdExecutor::init).
+    pub fn settings(&self) -> Option<&MpsseSettings> {
+        self.settings.as_ref()
+    }
+
+    /// Data sent, one entry per send.
+    pub fn sent(&self) -> &[Vec<u8>] {
+        &self.sent
+    }
+
+    /// Number of canned response bytes not received yet.
+    pub fn remaining_responses(&self) -> usize {
+        self.responses.len()
+    }
+
+    /// Assert that all the data sent, concatenated, is `expected`.
+    ///
+    /// This will panic with both streams in hexadecimal if they differ.
+    pub fn assert_sent(&self, expected: &[u8]) {
+        let sent = self.sent.concat();
+        assert!(
+            sent == expected,
+            "sent data does not match\n  sent: {:02X?}\nexpected: {:02X?}",
+            sent,
+            expected
+        );
+    }
+}
+
+impl MpsseCmdExecutor for MockMpsseExecutor {
+    type Error = MockError;
+
+    fn init(&mut self, settings: &MpsseSettings) -> Result<(), Self::Error> {
+        self.settings = Some(*settings);
+        Ok(())
+    }
+
+    fn send(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        self.sent.push(data.to_vec());
+        Ok(())
+    }
+
+    fn recv(&mut self, data: &mut [u8]) -> Result<(), Self::Error> {
+        if data.len() > self.responses.len() {
+            return Err(MockError {
+                requested: data.len(),
+                available: self.responses.len(),
+            });
+        }
+        let len = data.len();
+        for (byte, response) in data.iter_mut().zip(self.responses.drain(..len)) {
+            *byte = response;
+        }
+        Ok(())
+    }
+
+    fn purge(&mut self) -> Result<(), Self::Error> {
+        self.responses.clear();
+        Ok(())
+    }
+}
+
+/// Executor simulating the device responses to the commands sent.
+///
+/// Commands are parsed as they are sent, and may be split across sends.
+/// While loopback is enabled with
+/// [`enable_loopback`](crate::MpsseCmdBuilder::enable_loopback), data
+/// clocked in is the data clocked out on TDI/DO, in the bit order of the
+/// commands, see
+/// [`expected_loopback_response`](crate::MpsseCmdBuilder::expected_loopback_response).
+/// While loopback is disabled, as after reset, nothing drives TDO/DI and
+/// data reads return ones, as the input is pulled up. GPIO reads return the
+/// state of the pins set as outputs and bad commands are echoed.
+///
+/// # Example
+///
+/// ```
+/// use ftdi_mpsse::{ClockData, LoopbackExecutor, MpsseCmdBuilder, MpsseCmdExecutor};
+///
+/// let mut exec = LoopbackExecutor::new();
+/// let cmd = MpsseCmdBuilder::new()
+///     .enable_loopback()
+///     .clock_data(ClockData::MsbPosIn, &[0x12, 0x34])
+///     .send_immediate();
+/// let mut rx = [0; 2];
+/// exec.xfer(cmd.as_slice(), &mut rx)?;
+/// assert_eq!(rx, [0x12, 0x34]);
+/// # Ok::<(), ftdi_mpsse::MockError>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct LoopbackExecutor {
+    model: LoopbackModel,
+    loopback: bool,
+    pending: Vec<u8>,
+    responses: VecDeque<u8>,
+}
+
+impl LoopbackExecutor {
+    /// Create an executor with loopback disabled.
+    pub fn new() -> LoopbackExecutor {
+        LoopbackExecutor::default()
+    }
+
+    /// Returns `true` if loopback is enabled.
+    pub fn loopback(&self) -> bool {
+        self.loopback
+    }
+
+    /// Number of response bytes not received yet.
+    pub fn remaining_responses(&self) -> usize {
+        self.responses.len()
+    }
+}
+
+impl MpsseCmdExecutor for LoopbackExecutor {
+    type Error = MockError;
+
+    fn init(&mut self, settings: &MpsseSettings) -> Result<(), Self::Error> {
+        if settings.reset {
+            *self = LoopbackExecutor::default();
+        }
+        Ok(())
+    }
+
+    fn send(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        let enable: u8 = MpsseCmd::EnableLoopback.into();
+        let disable: u8 = MpsseCmd::DisableLoopback.into();
+        self.pending.extend_from_slice(data);
+        let mut response = Vec::new();
+        let mut consumed = self.pending.len();
+        for cmd in parse::commands(&self.pending) {
+            let cmd = match cmd {
+                Ok(cmd) => cmd,
+                Err(truncated) => {
+                    consumed = truncated.offset;
+                    break;
+                }
+            };
+            let opcode = cmd.bytes[0];
+            if opcode == enable {
+                self.loopback = true;
+            } else if opcode == disable {
+                self.loopback = false;
+            }
+            let start = response.len();
+            self.model.respond(cmd.bytes, &mut response);
+            if parse::is_clocking(opcode) && !self.loopback {
+                for byte in &mut response[start..] {
+                    *byte = 0xFF;
+                }
+            }
+        }
+        self.pending.drain(..consumed);
+        self.responses.extend(response);
+        Ok(())
+    }
+
+    fn recv(&mut self, data: &mut [u8]) -> Result<(), Self::Error> {
+        if data.len() > self.responses.len() {
+            return Err(MockError {
+                requested: data.len(),
+                available: self.responses.len(),
+            });
+        }
+        let len = data.len();
+        for (byte, response) in data.iter_mut().zip(self.responses.drain(..len)) {
+            *byte = response;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,209 @@
+//! 1-Wire bus master over a lower byte GPIO pin.
+//!
+//! The bus is driven low by setting the pin as an output, and released by
+//! setting it as an input, relying on the external pull-up resistor. Every
+//! other lower byte pin is set as an input.
+//!
+//! Delays are generated by toggling the MPSSE clock without transferring
+//! data, and rounded up to whole clock cycles of `clock_hz`, which must match
+//! the frequency set with [`set_clock`](crate::MpsseCmdBuilder::set_clock).
+//! Standard speed timing needs a clock of at least 1 MHz.
+use crate::waveform::clocks_for;
+use crate::{pin_mask, MpsseCmdBuilder, ReadToken};
+use core::time::Duration;
+
+/// Duration of the reset pulse in µs.
+const RESET_LOW_US: u64 = 480;
+/// Delay from the release of the reset pulse to the presence sample in µs.
+const PRESENCE_WAIT_US: u64 = 70;
+/// Delay from the presence sample to the end of the reset sequence in µs.
+const RESET_RECOVERY_US: u64 = 410;
+/// Low time of write 1 and read slots in µs.
+const SLOT_START_US: u64 = 6;
+/// Release time of a write 1 slot in µs.
+const WRITE_1_RELEASE_US: u64 = 64;
+/// Low time of a write 0 slot in µs.
+const WRITE_0_LOW_US: u64 = 60;
+/// Release time of a write 0 slot in µs.
+const WRITE_0_RELEASE_US: u64 = 10;
+/// Delay from the release of a read slot to the sample in µs.
+const READ_SAMPLE_US: u64 = 9;
+/// Delay from the sample to the end of a read slot in µs.
+const READ_RECOVERY_US: u64 = 55;
+
+impl MpsseCmdBuilder {
+    fn onewire_low(self, mask: u8) -> Self {
+        self.set_gpio_lower(0x00, mask)
+    }
+
+    fn onewire_release(self) -> Self {
+        self.set_gpio_lower(0x00, 0x00)
+    }
+
+    fn onewire_delay(self, us: u64, clock_hz: u32) -> Self {
+        self.dummy_clocks(clocks_for(Duration::from_micros(us), clock_hz))
+    }
+
+    /// Generate a 1-Wire reset pulse and sample the presence pulse.
+    ///
+    /// Drives the bus low for 480 µs, releases it, samples it 70 µs later and
+    /// waits another 410 µs for the end of the presence pulse. The bit of
+    /// `pin` in the byte covered by the returned token is `0` if a device
+    /// answered.
+    ///
+    /// # Arguments
+    ///
+    /// * `pin` - Lower byte GPIO pin number of the bus.
+    /// * `clock_hz` - Frequency of the MPSSE clock, at least 1 MHz.
+    ///
+    /// This will panic for pin numbers greater than 7.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::MpsseCmdBuilder;
+    ///
+    /// let (cmd, token) = MpsseCmdBuilder::new()
+    ///     .set_clock(29, Some(false))
+    ///     .onewire_reset(4, 1_000_000);
+    /// let cmd = cmd.send_immediate();
+    ///
+    /// let response = [0x00];
+    /// let present = token.get(&response)[0] & (1 << 4) == 0;
+    /// assert!(present);
+    /// ```
+    pub fn onewire_reset(self, pin: u8, clock_hz: u32) -> (Self, ReadToken) {
+        let mask = pin_mask(pin);
+        let cmd = self
+            .onewire_low(mask)
+            .onewire_delay(RESET_LOW_US, clock_hz)
+            .onewire_release()
+            .onewire_delay(PRESENCE_WAIT_US, clock_hz);
+        let token = cmd.read_token(1);
+        let cmd = cmd.gpio_lower().onewire_delay(RESET_RECOVERY_US, clock_hz);
+        (cmd, token)
+    }
+
+    /// Generate a 1-Wire write slot.
+    ///
+    /// A `1` is written by driving the bus low for 6 µs and releasing it for
+    /// 64 µs, a `0` by driving it low for 60 µs and releasing it for 10 µs.
+    ///
+    /// # Arguments
+    ///
+    /// * `pin` - Lower byte GPIO pin number of the bus.
+    /// * `bit` - Bit to write.
+    /// * `clock_hz` - Frequency of the MPSSE clock, at least 1 MHz.
+    ///
+    /// This will panic for pin numbers greater than 7.
+    pub fn onewire_write_bit(self, pin: u8, bit: bool, clock_hz: u32) -> Self {
+        let (low, release) = if bit {
+            (SLOT_START_US, WRITE_1_RELEASE_US)
+        } else {
+            (WRITE_0_LOW_US, WRITE_0_RELEASE_US)
+        };
+        self.onewire_low(pin_mask(pin))
+            .onewire_delay(low, clock_hz)
+            .onewire_release()
+            .onewire_delay(release, clock_hz)
+    }
+
+    /// Generate a 1-Wire read slot.
+    ///
+    /// Drives the bus low for 6 µs, releases it, samples it 9 µs later and
+    /// waits another 55 µs for the end of the slot. The bit of `pin` in the
+    /// byte covered by the returned token is the bit read.
+    ///
+    /// # Arguments
+    ///
+    /// * `pin` - Lower byte GPIO pin number of the bus.
+    /// * `clock_hz` - Frequency of the MPSSE clock, at least 1 MHz.
+    ///
+    /// This will panic for pin numbers greater than 7.
+    pub fn onewire_read_bit(self, pin: u8, clock_hz: u32) -> (Self, ReadToken) {
+        let cmd = self
+            .onewire_low(pin_mask(pin))
+            .onewire_delay(SLOT_START_US, clock_hz)
+            .onewire_release()
+            .onewire_delay(READ_SAMPLE_US, clock_hz);
+        let token = cmd.read_token(1);
+        let cmd = cmd.gpio_lower().onewire_delay(READ_RECOVERY_US, clock_hz);
+        (cmd, token)
+    }
+
+    /// Write a byte on a 1-Wire bus, LSB first.
+    ///
+    /// # Arguments
+    ///
+    /// * `pin` - Lower byte GPIO pin number of the bus.
+    /// * `byte` - Byte to write.
+    /// * `clock_hz` - Frequency of the MPSSE clock, at least 1 MHz.
+    ///
+    /// This will panic for pin numbers greater than 7.
+    pub fn onewire_write_byte(mut self, pin: u8, byte: u8, clock_hz: u32) -> Self {
+        for bit in 0..8 {
+            self = self.onewire_write_bit(pin, byte >> bit & 0x01 != 0, clock_hz);
+        }
+        self
+    }
+
+    /// Read a byte from a 1-Wire bus, LSB first.
+    ///
+    /// The returned token covers the 8 samples of the bus, decode them with
+    /// [`onewire_decode_byte`].
+    ///
+    /// # Arguments
+    ///
+    /// * `pin` - Lower byte GPIO pin number of the bus.
+    /// * `clock_hz` - Frequency of the MPSSE clock, at least 1 MHz.
+    ///
+    /// This will panic for pin numbers greater than 7.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::{onewire_decode_byte, MpsseCmdBuilder};
+    ///
+    /// // DS18B20 temperature conversion, then read the scratchpad
+    /// let clock_hz = 1_000_000;
+    /// let (cmd, _) = MpsseCmdBuilder::new()
+    ///     .set_clock(29, Some(false))
+    ///     .onewire_reset(4, clock_hz);
+    /// let cmd = cmd
+    ///     .onewire_write_byte(4, 0xCC, clock_hz)
+    ///     .onewire_write_byte(4, 0x44, clock_hz);
+    /// let (cmd, _) = cmd.onewire_reset(4, clock_hz);
+    /// let cmd = cmd
+    ///     .onewire_write_byte(4, 0xCC, clock_hz)
+    ///     .onewire_write_byte(4, 0xBE, clock_hz);
+    /// let (cmd, lsb) = cmd.onewire_read_byte(4, clock_hz);
+    /// let cmd = cmd.send_immediate();
+    ///
+    /// let samples = [0x10, 0x00, 0x00, 0x00, 0x10, 0x00, 0x10, 0x00];
+    /// assert_eq!(onewire_decode_byte(&samples, 4), 0x51);
+    /// ```
+    pub fn onewire_read_byte(self, pin: u8, clock_hz: u32) -> (Self, ReadToken) {
+        let (mut cmd, first) = self.onewire_read_bit(pin, clock_hz);
+        for _ in 1..8 {
+            cmd = cmd.onewire_read_bit(pin, clock_hz).0;
+        }
+        (cmd, ReadToken::new(first.offset(), 8))
+    }
+}
+
+/// Decode the bus samples of
+/// [`onewire_read_byte`](MpsseCmdBuilder::onewire_read_byte).
+///
+/// `samples` is the part of the response covered by the token, LSB first,
+/// and `pin` the lower byte GPIO pin number of the bus.
+///
+/// This will panic for pin numbers greater than 7.
+pub fn onewire_decode_byte(samples: &[u8], pin: u8) -> u8 {
+    let mask = pin_mask(pin);
+    samples
+        .iter()
+        .take(8)
+        .enumerate()
+        .filter(|(_, &sample)| sample & mask != 0)
+        .fold(0, |byte, (bit, _)| byte | 1 << bit)
+}
@@ -0,0 +1,75 @@
+//! Command stream optimizations.
+use crate::{parse, MpsseCmd, MpsseCmdBuilder};
+use alloc::vec::Vec;
+
+impl MpsseCmdBuilder {
+    /// Remove redundant GPIO set commands.
+    ///
+    /// A [`set_gpio_lower`] or [`set_gpio_upper`] command is removed if it
+    /// sets the same state and direction as the previous set command for the
+    /// same byte, with only GPIO set commands, GPIO reads and
+    /// [`send_immediate`] in between. Any other command may change the pins,
+    /// for example data clocking commands drive the lower byte, so it
+    /// prevents the removal.
+    ///
+    /// Only provably redundant commands are removed and commands are never
+    /// reordered, although removing a command shortens the time between the
+    /// surrounding ones. An incomplete command at the end of the stream is
+    /// kept as is.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::MpsseCmdBuilder;
+    ///
+    /// let cmd = MpsseCmdBuilder::new()
+    ///     .set_gpio_lower(0x08, 0x0B)
+    ///     .set_gpio_upper(0x00, 0x01)
+    ///     .set_gpio_lower(0x08, 0x0B)
+    ///     .dedup_gpio();
+    /// assert_eq!(cmd.as_slice(), [0x80, 0x08, 0x0B, 0x82, 0x00, 0x01]);
+    /// ```
+    ///
+    /// [`set_gpio_lower`]: MpsseCmdBuilder::set_gpio_lower
+    /// [`set_gpio_upper`]: MpsseCmdBuilder::set_gpio_upper
+    /// [`send_immediate`]: MpsseCmdBuilder::send_immediate
+    pub fn dedup_gpio(self) -> Self {
+        let set_lower: u8 = MpsseCmd::SetDataBitsLowbyte.into();
+        let set_upper: u8 = MpsseCmd::SetDataBitsHighbyte.into();
+        let get_lower: u8 = MpsseCmd::GetDataBitsLowbyte.into();
+        let get_upper: u8 = MpsseCmd::GetDataBitsHighbyte.into();
+        let send_immediate: u8 = MpsseCmd::SendImmediate.into();
+
+        let mut out = Vec::with_capacity(self.0.len());
+        let mut lower: Option<&[u8]> = None;
+        let mut upper: Option<&[u8]> = None;
+        for cmd in parse::commands(self.as_slice()) {
+            let cmd = match cmd {
+                Ok(cmd) => cmd,
+                Err(truncated) => {
+                    out.extend_from_slice(&self.0[truncated.offset..]);
+                    break;
+                }
+            };
+            let last = match cmd.bytes[0] {
+                op if op == set_lower => &mut lower,
+                op if op == set_upper => &mut upper,
+                op if op == get_lower || op == get_upper || op == send_immediate => {
+                    out.extend_from_slice(cmd.bytes);
+                    continue;
+                }
+                _ => {
+                    lower = None;
+                    upper = None;
+                    out.extend_from_slice(cmd.bytes);
+                    continue;
+                }
+            };
+            if *last != Some(cmd.bytes) {
+                *last = Some(cmd.bytes);
+                out.extend_from_slice(cmd.bytes);
+            }
+        }
+        MpsseCmdBuilder::with_vec(out)
+    }
+}
@@ -0,0 +1,75 @@
+//! Parity bits for bit-banged serial protocols.
+use crate::{ClockBitsOut, MpsseCmdBuilder};
+
+/// Parity bit making the number of ones in `bits`, parity bit included, even
+/// or odd.
+fn parity_bit(bits: u8, even: bool) -> bool {
+    (bits.count_ones() % 2 == 1) == even
+}
+
+/// Check the parity bit of received data.
+///
+/// Returns `true` if the number of ones in `data` and the parity bit is even
+/// for even parity, or odd for odd parity. Bits of `data` that were not
+/// received must be masked off first.
+///
+/// # Example
+///
+/// ```
+/// use ftdi_mpsse::check_parity;
+///
+/// assert!(check_parity(0b0000_0111, true, true));
+/// assert!(!check_parity(0b0000_0111, true, false));
+/// ```
+pub fn check_parity(data: u8, parity: bool, even: bool) -> bool {
+    parity_bit(data, even) == parity
+}
+
+impl MpsseCmdBuilder {
+    /// Clock data bits out followed by a parity bit.
+    ///
+    /// The parity is computed over the `len` bits clocked out, the upper bits
+    /// of `data` for MSB first modes and the lower bits for LSB first modes,
+    /// and clocked out right after them in the same bit order.
+    ///
+    /// # Arguments
+    ///
+    /// * `mode` - Bit clocking mode.
+    /// * `data` - Data bits.
+    /// * `len` - Number of bits to clock out, without the parity bit.
+    ///   This will panic for values greater than 8.
+    /// * `even` - Even parity if `true`, odd parity otherwise.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::{ClockBitsOut, MpsseCmdBuilder};
+    ///
+    /// // 7 data bits 0x41 with even parity
+    /// let cmd = MpsseCmdBuilder::new().clock_data_out_with_parity(ClockBitsOut::LsbNeg, 0x41, 7, true);
+    /// assert_eq!(cmd.as_slice(), [0x1B, 0x06, 0x41, 0x1B, 0x00, 0x00]);
+    /// ```
+    pub fn clock_data_out_with_parity(
+        self,
+        mode: ClockBitsOut,
+        data: u8,
+        len: u8,
+        even: bool,
+    ) -> Self {
+        assert!(len <= 8, "data length cannot exceed 8");
+        let lsb_first = u8::from(mode) & 0x08 != 0;
+        // the parity bit is clocked first in the same bit order
+        let (bits, first_bit) = match (len, lsb_first) {
+            (0, _) => (0x00, if lsb_first { 0x01 } else { 0x80 }),
+            (_, true) => (data & (0xFF >> (8 - len)), 0x01),
+            (_, false) => (data & (0xFF << (8 - len)), 0x80),
+        };
+        let parity = if parity_bit(bits, even) {
+            first_bit
+        } else {
+            0x00
+        };
+        self.clock_bits_out(mode, data, len)
+            .clock_bits_out(mode, parity, 1)
+    }
+}
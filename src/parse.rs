@@ -0,0 +1,127 @@
+//! Minimal MPSSE command stream scanner.
+//!
+//! Splits a raw command buffer into individual commands without interpreting
+//! them beyond what is needed to find command boundaries and the number of
+//! bytes each command makes the device return.
+
+/// A single command found in a command stream.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub(crate) struct RawCmd<'a> {
+    /// Offset of the opcode within the scanned buffer.
+    pub offset: usize,
+    /// Opcode followed by all of its arguments and payload.
+    pub bytes: &'a [u8],
+    /// Number of bytes the device returns for this command.
+    pub read_len: usize,
+}
+
+/// A command was cut short by the end of the buffer.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub(crate) struct Truncated {
+    /// Offset of the opcode of the incomplete command.
+    pub offset: usize,
+}
+
+/// Iterator over the commands of a command stream.
+///
+/// Yields a single [`Truncated`] error and then stops if the last command is
+/// incomplete.
+pub(crate) struct RawCmds<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+/// Scan a command stream.
+pub(crate) fn commands(data: &[u8]) -> RawCmds<'_> {
+    RawCmds { data, offset: 0 }
+}
+
+/// Returns `true` if the opcode is a data clocking command.
+///
+/// Clocking commands are a bitfield:
+/// * bit 0: write on negative edge
+/// * bit 1: bit mode
+/// * bit 2: read on negative edge
+/// * bit 3: LSB first
+/// * bit 4: write TDI/DO
+/// * bit 5: read TDO/DI
+/// * bit 6: write TMS
+pub(crate) fn is_clocking(opcode: u8) -> bool {
+    opcode < 0x80 && opcode & 0x70 != 0
+}
+
+/// Returns `true` if the device does not recognize the opcode.
+pub(crate) fn is_bad_command(opcode: u8) -> bool {
+    !is_clocking(opcode) && !matches!(opcode, 0x80..=0x8F | 0x94..=0x97 | 0x9C..=0x9E)
+}
+
+/// Length of the command and the number of bytes it makes the device return.
+///
+/// `args` are the bytes following the opcode; only the length fields are
+/// inspected. Returns `None` if `args` is too short to hold the length fields.
+fn command_len(opcode: u8, args: &[u8]) -> Option<(usize, usize)> {
+    if is_clocking(opcode) {
+        let writes = opcode & 0x50 != 0;
+        let reads = opcode & 0x20 != 0;
+        if opcode & 0x02 != 0 {
+            args.first()?;
+            let payload = if writes { 1 } else { 0 };
+            let read = if reads { 1 } else { 0 };
+            Some((2 + payload, read))
+        } else {
+            let len = usize::from(*args.first()?) | (usize::from(*args.get(1)?) << 8);
+            let count = len + 1;
+            let payload = if writes { count } else { 0 };
+            let read = if reads { count } else { 0 };
+            Some((3 + payload, read))
+        }
+    } else if is_bad_command(opcode) {
+        // the device echoes 0xFA followed by the unrecognized opcode
+        Some((1, 2))
+    } else {
+        match opcode {
+            // set GPIO, set clock divisor, clock N*8 bits, clock N*8 until GPIOL1,
+            // drive only zero
+            0x80 | 0x82 | 0x86 | 0x8F | 0x9C | 0x9D | 0x9E => Some((3, 0)),
+            // get GPIO
+            0x81 | 0x83 => Some((1, 1)),
+            // clock N bits
+            0x8E => Some((2, 0)),
+            // single byte commands
+            _ => Some((1, 0)),
+        }
+    }
+}
+
+impl<'a> Iterator for RawCmds<'a> {
+    type Item = Result<RawCmd<'a>, Truncated>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let rest = &self.data[self.offset..];
+        let (&opcode, args) = rest.split_first()?;
+        let offset = self.offset;
+        match command_len(opcode, args) {
+            Some((len, read_len)) if len <= rest.len() => {
+                self.offset += len;
+                Some(Ok(RawCmd {
+                    offset,
+                    bytes: &rest[..len],
+                    read_len,
+                }))
+            }
+            _ => {
+                self.offset = self.data.len();
+                Some(Err(Truncated { offset }))
+            }
+        }
+    }
+}
+
+/// Total number of bytes returned by the device for a command stream,
+/// ignoring an incomplete trailing command.
+pub(crate) fn read_len(data: &[u8]) -> usize {
+    commands(data)
+        .filter_map(Result::ok)
+        .map(|cmd| cmd.read_len)
+        .sum()
+}
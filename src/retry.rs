@@ -0,0 +1,180 @@
+//! Executor decorator retrying transfers that fall out of sync.
+use crate::{
+    find_bad_command_echo, sync, MpsseCmd, MpsseCmdExecutor, MpsseSettings, BAD_COMMAND_RESPONSE,
+};
+use alloc::vec::Vec;
+use core::fmt;
+
+/// Error returned by [`RetryingExecutor`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum RetryError<E> {
+    /// Error from the wrapped executor.
+    Executor(E),
+    /// The bad command was not echoed at the end of the response of the last
+    /// attempt of the transfer, or the response contained another echo.
+    RetriesExhausted {
+        /// Number of attempts made.
+        attempts: usize,
+    },
+    /// The device did not echo the resynchronization marker.
+    SyncFailed,
+    /// Every attempt of a
+    /// [`spi_checked_transaction`](crate::spi_checked_transaction) received
+    /// a response with a bad CRC.
+    CrcMismatch {
+        /// Number of attempts made.
+        attempts: usize,
+    },
+}
+
+impl<E: fmt::Display> fmt::Display for RetryError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RetryError::Executor(e) => e.fmt(f),
+            RetryError::RetriesExhausted { attempts } => {
+                write!(f, "response out of sync after {} attempts", attempts)
+            }
+            RetryError::SyncFailed => write!(f, "failed to resynchronize with the MPSSE"),
+            RetryError::CrcMismatch { attempts } => {
+                write!(f, "CRC mismatch after {} attempts", attempts)
+            }
+        }
+    }
+}
+
+impl<E: core::error::Error + 'static> core::error::Error for RetryError<E> {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            RetryError::Executor(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Executor decorator that retries transfers falling out of sync.
+///
+/// Every [`xfer`] is followed by a bad command, see
+/// [`sync_bad_command`](crate::MpsseCmdBuilder::sync_bad_command), whose
+/// echo must be the last 2 bytes of the response. When it is not, or when
+/// [`find_bad_command_echo`] finds another echo within the response, the
+/// device has most likely misinterpreted the command stream, for example
+/// after a USB glitch. Errors of the wrapped executor, such as timeouts and
+/// short reads, are treated the same way.
+///
+/// The transfer is then retried, after discarding the data not read yet with
+/// [`purge`](MpsseCmdExecutor::purge) and optionally resynchronizing with the
+/// device using a different bad command as marker, so the echo of the failed
+/// attempt is not mistaken for it. When every attempt fails, the error of the
+/// last attempt is returned.
+///
+/// Data clocked in from the device that happens to contain a bad command
+/// echo is indistinguishable from a dropped command, and fails with
+/// [`RetryError::RetriesExhausted`].
+///
+/// Only [`xfer`] is retried; [`send`] and [`recv`] are passed through.
+///
+/// [`xfer`]: MpsseCmdExecutor::xfer
+/// [`send`]: MpsseCmdExecutor::send
+/// [`recv`]: MpsseCmdExecutor::recv
+#[derive(Debug)]
+pub struct RetryingExecutor<E> {
+    inner: E,
+    retries: usize,
+    resync: bool,
+}
+
+impl<E> RetryingExecutor<E> {
+    /// Wrap an executor.
+    ///
+    /// # Arguments
+    ///
+    /// * `inner` - Executor to wrap.
+    /// * `retries` - Number of times a transfer is retried after the first
+    ///   attempt.
+    /// * `resync` - Resynchronize with the device before retrying.
+    pub fn new(inner: E, retries: usize, resync: bool) -> RetryingExecutor<E> {
+        RetryingExecutor {
+            inner,
+            retries,
+            resync,
+        }
+    }
+
+    /// Number of times a transfer is retried.
+    pub fn retries(&self) -> usize {
+        self.retries
+    }
+
+    /// Returns `true` if the device is resynchronized before retrying.
+    pub fn resync(&self) -> bool {
+        self.resync
+    }
+
+    /// Get a reference to the wrapped executor.
+    pub fn inner(&self) -> &E {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the wrapped executor.
+    pub fn inner_mut(&mut self) -> &mut E {
+        &mut self.inner
+    }
+
+    /// Unwrap the executor.
+    pub fn into_inner(self) -> E {
+        self.inner
+    }
+}
+
+impl<E: MpsseCmdExecutor> MpsseCmdExecutor for RetryingExecutor<E> {
+    type Error = RetryError<E::Error>;
+
+    fn init(&mut self, settings: &MpsseSettings) -> Result<(), Self::Error> {
+        self.inner.init(settings).map_err(RetryError::Executor)
+    }
+
+    fn send(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        self.inner.send(data).map_err(RetryError::Executor)
+    }
+
+    fn recv(&mut self, data: &mut [u8]) -> Result<(), Self::Error> {
+        self.inner.recv(data).map_err(RetryError::Executor)
+    }
+
+    fn purge(&mut self) -> Result<(), Self::Error> {
+        self.inner.purge().map_err(RetryError::Executor)
+    }
+
+    fn xfer(&mut self, txdata: &[u8], rxdata: &mut [u8]) -> Result<(), Self::Error> {
+        let mut tx = Vec::with_capacity(txdata.len() + 2);
+        tx.extend_from_slice(txdata);
+        tx.extend_from_slice(&[sync::SYNC_BAD_COMMAND, MpsseCmd::SendImmediate.into()]);
+        let mut rx = alloc::vec![0; rxdata.len() + 2];
+        let mut error = None;
+        for attempt in 0..=self.retries {
+            if attempt > 0 {
+                self.inner.purge().map_err(RetryError::Executor)?;
+                if self.resync && !sync::resync(&mut self.inner).map_err(RetryError::Executor)? {
+                    return Err(RetryError::SyncFailed);
+                }
+            }
+            error = self.inner.xfer(&tx, &mut rx).err();
+            if error.is_some() {
+                continue;
+            }
+            let (response, echo) = rx.split_at(rxdata.len());
+            if echo == [BAD_COMMAND_RESPONSE, sync::SYNC_BAD_COMMAND]
+                && find_bad_command_echo(response).is_none()
+            {
+                rxdata.copy_from_slice(response);
+                return Ok(());
+            }
+        }
+        Err(match error {
+            Some(e) => RetryError::Executor(e),
+            None => RetryError::RetriesExhausted {
+                attempts: self.retries + 1,
+            },
+        })
+    }
+}
@@ -0,0 +1,632 @@
+//! SPI helpers.
+//!
+//! The MPSSE clocks SPI data with SCK on AD0, MOSI on AD1 and MISO on AD2.
+//! Chip select is any other lower byte GPIO pin, active low.
+use crate::{pin_mask, ClockData, ClockDataIn, ClockDataOut, MpsseCmdBuilder, ReadToken};
+
+/// SCK pin mask.
+pub(crate) const SCK: u8 = 0x01;
+/// MOSI pin mask.
+pub(crate) const MOSI: u8 = 0x02;
+
+/// Number of MISO readings taken by
+/// [`probe_miso_idle`](MpsseCmdBuilder::probe_miso_idle).
+const MISO_PROBE_READS: usize = 16;
+
+/// Byte clocked out to pad a transfer by
+/// [`clock_data_padded`](MpsseCmdBuilder::clock_data_padded).
+///
+/// MOSI is held high, which most devices ignore as a dummy byte.
+pub const SPI_PAD_BYTE: u8 = 0xFF;
+
+/// SPI clock polarity and phase.
+///
+/// | Mode    | CPOL | CPHA | Idle clock | Data out     | Data in      |
+/// |---------|------|------|------------|--------------|--------------|
+/// | `Mode0` | 0    | 0    | low        | falling edge | rising edge  |
+/// | `Mode1` | 0    | 1    | low        | rising edge  | falling edge |
+/// | `Mode2` | 1    | 0    | high       | rising edge  | falling edge |
+/// | `Mode3` | 1    | 1    | high       | falling edge | rising edge  |
+///
+/// The MPSSE does not set the idle clock level by itself: before the first
+/// transfer, set SCK (AD0) to the idle level with
+/// [`set_gpio_lower`](MpsseCmdBuilder::set_gpio_lower), low for `Mode0` and
+/// `Mode1`, high for `Mode2` and `Mode3`, see [`idle_clock_high`].
+///
+/// # Example
+///
+/// ```
+/// use ftdi_mpsse::{MpsseCmdBuilder, SpiMode};
+///
+/// let mode = SpiMode::Mode3;
+/// // SCK idles high, MOSI and SCK outputs
+/// let cmd = MpsseCmdBuilder::new()
+///     .set_gpio_lower(u8::from(mode.idle_clock_high()), 0x03)
+///     .clock_data(mode.data(), &[0x9F, 0x00]);
+/// assert_eq!(cmd.as_slice(), [0x80, 0x01, 0x03, 0x31, 0x01, 0x00, 0x9F, 0x00]);
+/// ```
+///
+/// [`idle_clock_high`]: SpiMode::idle_clock_high
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum SpiMode {
+    /// CPOL = 0, CPHA = 0.
+    Mode0,
+    /// CPOL = 0, CPHA = 1.
+    Mode1,
+    /// CPOL = 1, CPHA = 0.
+    Mode2,
+    /// CPOL = 1, CPHA = 1.
+    Mode3,
+}
+
+impl SpiMode {
+    /// Returns `true` if the clock idles high (CPOL = 1).
+    pub const fn idle_clock_high(self) -> bool {
+        matches!(self, SpiMode::Mode2 | SpiMode::Mode3)
+    }
+
+    /// Returns `true` if data is driven on the falling edge of the clock.
+    const fn out_on_falling_edge(self) -> bool {
+        matches!(self, SpiMode::Mode0 | SpiMode::Mode3)
+    }
+
+    /// Clocking mode writing data, driven on the edge opposite to the one
+    /// the device samples data on.
+    pub const fn data_out(self) -> ClockDataOut {
+        if self.out_on_falling_edge() {
+            ClockDataOut::MsbNeg
+        } else {
+            ClockDataOut::MsbPos
+        }
+    }
+
+    /// Clocking mode reading data, sampled on the edge opposite to the one
+    /// the device drives data on.
+    pub const fn data_in(self) -> ClockDataIn {
+        if self.out_on_falling_edge() {
+            ClockDataIn::MsbPos
+        } else {
+            ClockDataIn::MsbNeg
+        }
+    }
+
+    /// Clocking mode writing and reading data, combining [`data_out`] and
+    /// [`data_in`].
+    ///
+    /// [`data_out`]: SpiMode::data_out
+    /// [`data_in`]: SpiMode::data_in
+    pub const fn data(self) -> ClockData {
+        if self.out_on_falling_edge() {
+            ClockData::MsbPosIn
+        } else {
+            ClockData::MsbNegIn
+        }
+    }
+}
+
+/// Idle state of MISO found by
+/// [`probe_miso_idle`](MpsseCmdBuilder::probe_miso_idle).
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum MisoIdle {
+    /// MISO was high in every reading.
+    PullUp,
+    /// MISO was low in every reading.
+    PullDown,
+    /// MISO changed between readings.
+    Floating,
+}
+
+impl MisoIdle {
+    /// Decode the readings of
+    /// [`probe_miso_idle`](MpsseCmdBuilder::probe_miso_idle).
+    ///
+    /// `readings` is the part of the response covered by the token, and
+    /// `miso_pin` the pin number passed to `probe_miso_idle`.
+    ///
+    /// This will panic for pin numbers greater than 7.
+    pub fn from_readings(readings: &[u8], miso_pin: u8) -> MisoIdle {
+        let mask = pin_mask(miso_pin);
+        if readings.iter().all(|r| r & mask != 0) {
+            MisoIdle::PullUp
+        } else if readings.iter().all(|r| r & mask == 0) {
+            MisoIdle::PullDown
+        } else {
+            MisoIdle::Floating
+        }
+    }
+}
+
+/// 3-to-8 demultiplexer driving the chip select of up to 8 SPI devices.
+///
+/// The device number is driven on three lower byte GPIO address pins, A0
+/// being the LSB, to select one of the demultiplexer outputs, as with a
+/// 74HC138. Without an enable pin one output is always asserted, so a device
+/// stays selected between transfers.
+///
+/// # Example
+///
+/// ```
+/// use ftdi_mpsse::{DemuxCs, MpsseCmdBuilder, SpiDevice, SpiMode};
+///
+/// let demux = DemuxCs::new(3, 4, 5).with_enable(6);
+/// let cmd = demux.select(MpsseCmdBuilder::new(), 5);
+/// assert_eq!(cmd.as_slice(), [0x80, 0x28, 0x7B]);
+///
+/// let adc = SpiDevice::with_demux(demux, 5, SpiMode::Mode0);
+/// let (cmd, token) = adc.transfer(MpsseCmdBuilder::new(), &[0x00, 0x00]);
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct DemuxCs {
+    address: [u8; 3],
+    enable: u8,
+}
+
+impl DemuxCs {
+    /// Create a demultiplexer from the pin numbers of its address lines.
+    ///
+    /// This will panic for pin numbers greater than 7.
+    pub fn new(a0: u8, a1: u8, a2: u8) -> DemuxCs {
+        DemuxCs {
+            address: [pin_mask(a0), pin_mask(a1), pin_mask(a2)],
+            enable: 0x00,
+        }
+    }
+
+    /// Set the pin number of the active low enable line.
+    ///
+    /// The demultiplexer is disabled to deselect every device between
+    /// transfers.
+    ///
+    /// This will panic for pin numbers greater than 7.
+    pub fn with_enable(mut self, pin: u8) -> DemuxCs {
+        self.enable = pin_mask(pin);
+        self
+    }
+
+    /// Lower byte GPIO mask of the address and enable pins.
+    fn mask(&self) -> u8 {
+        self.address[0] | self.address[1] | self.address[2] | self.enable
+    }
+
+    /// Lower byte GPIO state of the address pins selecting `device`.
+    ///
+    /// This will panic for device numbers greater than 7.
+    fn address(&self, device: u8) -> u8 {
+        assert!(device <= 7, "device number cannot exceed 7");
+        self.address
+            .iter()
+            .enumerate()
+            .filter(|(bit, _)| device >> bit & 0x01 != 0)
+            .fold(0x00, |state, (_, mask)| state | mask)
+    }
+
+    /// Assert the chip select of a device.
+    ///
+    /// Drives the address of the device and the enable line low. SCK and
+    /// MOSI are driven low, use [`SpiDevice::with_demux`] for devices with an
+    /// idle high clock.
+    ///
+    /// This will panic for device numbers greater than 7.
+    pub fn select(&self, cmd: MpsseCmdBuilder, device: u8) -> MpsseCmdBuilder {
+        cmd.set_gpio_lower(self.address(device), SCK | MOSI | self.mask())
+    }
+}
+
+/// SPI device with a chip select pin.
+///
+/// Builds chip select framed transfers, MSB first, with the clocking modes
+/// matching the [`SpiMode`]. Between transfers SCK is held at the idle level
+/// of the mode.
+///
+/// # Example
+///
+/// ```
+/// use ftdi_mpsse::{MpsseCmdBuilder, SpiDevice, SpiMode};
+///
+/// let flash = SpiDevice::new(3, SpiMode::Mode0);
+/// let cmd = flash.init(MpsseCmdBuilder::new());
+/// let (cmd, id) = flash.transfer(cmd, &[0x9F, 0x00, 0x00, 0x00]);
+/// let cmd = cmd.send_immediate();
+/// assert_eq!(id.len(), 4);
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct SpiDevice {
+    /// Lower byte GPIO mask of the chip select pins.
+    cs_mask: u8,
+    /// Chip select pin state when the device is selected.
+    selected: u8,
+    /// Chip select pin state when the device is deselected.
+    deselected: u8,
+    mode: SpiMode,
+}
+
+impl SpiDevice {
+    /// Create a SPI device.
+    ///
+    /// # Arguments
+    ///
+    /// * `cs` - Lower byte GPIO pin number of the chip select, 3-7.
+    /// * `mode` - SPI mode of the device.
+    ///
+    /// This will panic for pin numbers greater than 7.
+    pub fn new(cs: u8, mode: SpiMode) -> SpiDevice {
+        let cs = pin_mask(cs);
+        SpiDevice {
+            cs_mask: cs,
+            selected: 0x00,
+            deselected: cs,
+            mode,
+        }
+    }
+
+    /// Create a SPI device selected through a demultiplexer.
+    ///
+    /// Transfers drive the address of `device` to select it first, and
+    /// disable the demultiplexer at the end if it has an enable pin.
+    ///
+    /// This will panic for device numbers greater than 7.
+    pub fn with_demux(demux: DemuxCs, device: u8, mode: SpiMode) -> SpiDevice {
+        let address = demux.address(device);
+        SpiDevice {
+            cs_mask: demux.mask(),
+            selected: address,
+            deselected: address | demux.enable,
+            mode,
+        }
+    }
+
+    /// SPI mode of the device.
+    pub fn mode(&self) -> SpiMode {
+        self.mode
+    }
+
+    fn direction(&self) -> u8 {
+        SCK | MOSI | self.cs_mask
+    }
+
+    fn idle_clock(&self) -> u8 {
+        if self.mode.idle_clock_high() {
+            SCK
+        } else {
+            0x00
+        }
+    }
+
+    pub(crate) fn assert_cs(&self, cmd: MpsseCmdBuilder) -> MpsseCmdBuilder {
+        cmd.set_gpio_lower(self.selected | self.idle_clock(), self.direction())
+    }
+
+    /// Deassert chip select and set SCK to the idle level of the mode.
+    ///
+    /// This must be done before the first transfer.
+    pub fn init(&self, cmd: MpsseCmdBuilder) -> MpsseCmdBuilder {
+        cmd.set_gpio_lower(self.deselected | self.idle_clock(), self.direction())
+    }
+
+    /// Clock data out and in with chip select asserted.
+    ///
+    /// This will panic for data lengths greater than `u16::MAX + 1`.
+    pub fn transfer(&self, cmd: MpsseCmdBuilder, data: &[u8]) -> (MpsseCmdBuilder, ReadToken) {
+        let cmd = self.assert_cs(cmd);
+        let token = cmd.read_token(data.len());
+        let cmd = self.init(cmd.clock_data(self.mode.data(), data));
+        (cmd, token)
+    }
+
+    /// Clock data out with chip select asserted.
+    ///
+    /// This will panic for data lengths greater than `u16::MAX + 1`.
+    pub fn write(&self, cmd: MpsseCmdBuilder, data: &[u8]) -> MpsseCmdBuilder {
+        let cmd = self
+            .assert_cs(cmd)
+            .clock_data_out(self.mode.data_out(), data);
+        self.init(cmd)
+    }
+
+    /// Clock data in with chip select asserted.
+    pub fn read(&self, cmd: MpsseCmdBuilder, len: usize) -> (MpsseCmdBuilder, ReadToken) {
+        let cmd = self.assert_cs(cmd);
+        let token = cmd.read_token(len);
+        let cmd = self.init(cmd.clock_data_in(self.mode.data_in(), len));
+        (cmd, token)
+    }
+}
+
+impl MpsseCmdBuilder {
+    /// Set up the lower byte GPIO pins for a SPI mode 3 device.
+    ///
+    /// SCK idles high in mode 3, so it is driven high along with the
+    /// deasserted chip select before the first transfer. Data must then be
+    /// clocked out on the falling edge and in on the rising edge, for example
+    /// with [`ClockData::MsbPosIn`].
+    ///
+    /// # Arguments
+    ///
+    /// * `cs` - Lower byte GPIO pin number of the chip select, 3-7.
+    ///
+    /// This will panic for pin numbers greater than 7.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::MpsseCmdBuilder;
+    ///
+    /// let cmd = MpsseCmdBuilder::new().spi_mode3_init(3);
+    /// assert_eq!(cmd.as_slice(), [0x80, 0x09, 0x0B]);
+    /// ```
+    pub fn spi_mode3_init(self, cs: u8) -> Self {
+        SpiDevice::new(cs, SpiMode::Mode3).init(self)
+    }
+
+    /// Read consecutive registers of a SPI device in one transaction.
+    ///
+    /// Asserts chip select, clocks out the start register address with the
+    /// auto-increment bits set, clocks in `count` bytes and deasserts chip
+    /// select. SCK is driven low while chip select is asserted, use
+    /// [`SpiDevice`] for devices with an idle high clock.
+    ///
+    /// # Arguments
+    ///
+    /// * `cs` - Lower byte GPIO pin number of the chip select, 3-7.
+    /// * `mode_out` - Clocking mode of the address byte.
+    /// * `mode_in` - Clocking mode of the register data.
+    /// * `start_reg` - Address of the first register.
+    /// * `count` - Number of registers to read.
+    /// * `auto_increment_bit` - Mask of the bits set in the address byte to
+    ///   request a burst, for example `0x40` for the multi-byte bit of an
+    ///   ADXL345. Read flags can be included in the same mask.
+    ///
+    /// This will panic for pin numbers greater than 7, and for counts greater
+    /// than `u16::MAX + 1`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::{ClockDataIn, ClockDataOut, MpsseCmdBuilder};
+    ///
+    /// // ADXL345 acceleration data, read and multi-byte bits set
+    /// let (cmd, token) = MpsseCmdBuilder::new().spi_burst_read(
+    ///     3,
+    ///     ClockDataOut::MsbNeg,
+    ///     ClockDataIn::MsbPos,
+    ///     0x32,
+    ///     6,
+    ///     0xC0,
+    /// );
+    /// let cmd = cmd.send_immediate();
+    /// assert_eq!(cmd.as_slice()[6], 0xF2);
+    /// assert_eq!(token.range(), 0..6);
+    /// ```
+    pub fn spi_burst_read(
+        self,
+        cs: u8,
+        mode_out: ClockDataOut,
+        mode_in: ClockDataIn,
+        start_reg: u8,
+        count: usize,
+        auto_increment_bit: u8,
+    ) -> (Self, ReadToken) {
+        let cs = pin_mask(cs);
+        let dir = SCK | MOSI | cs;
+        let cmd = self
+            .set_gpio_lower(0x00, dir)
+            .clock_data_out(mode_out, &[start_reg | auto_increment_bit]);
+        let token = cmd.read_token(count);
+        let cmd = cmd.clock_data_in(mode_in, count).set_gpio_lower(cs, dir);
+        (cmd, token)
+    }
+
+    /// Clock data out and in once a flow controlled SPI device is ready.
+    ///
+    /// Asserts chip select, waits until the READY pin is high, clocks the data
+    /// and deasserts chip select. SCK is driven low while chip select is
+    /// asserted, and the transfer stalls for as long as READY stays low.
+    ///
+    /// The MPSSE can only wait on GPIOL1 (AD5 on the FT232H), so the READY
+    /// pin must be wired there and left as an input.
+    ///
+    /// # Arguments
+    ///
+    /// * `cs` - Lower byte GPIO pin number of the chip select, 3-7 except 5.
+    /// * `ready_pin_is_gpiol1` - Confirms that READY is wired to GPIOL1.
+    /// * `mode` - Data clocking mode.
+    /// * `data` - Data to clock out.
+    ///
+    /// This will panic if `ready_pin_is_gpiol1` is `false`, for chip select
+    /// pin numbers greater than 7 or equal to 5, and for data lengths greater
+    /// than `u16::MAX + 1`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::{ClockData, MpsseCmdBuilder};
+    ///
+    /// let (cmd, token) = MpsseCmdBuilder::new().spi_transfer_flow_controlled(
+    ///     3,
+    ///     true,
+    ///     ClockData::MsbPosIn,
+    ///     &[0x12, 0x34],
+    /// );
+    /// let cmd = cmd.send_immediate();
+    /// assert_eq!(token.range(), 0..2);
+    /// ```
+    pub fn spi_transfer_flow_controlled(
+        self,
+        cs: u8,
+        ready_pin_is_gpiol1: bool,
+        mode: ClockData,
+        data: &[u8],
+    ) -> (Self, ReadToken) {
+        assert!(ready_pin_is_gpiol1, "READY pin must be wired to GPIOL1");
+        assert!(cs != 5, "chip select cannot be on GPIOL1");
+        let cs = pin_mask(cs);
+        let dir = SCK | MOSI | cs;
+        let cmd = self.set_gpio_lower(0x00, dir).wait_on_io_high();
+        let token = cmd.read_token(data.len());
+        let cmd = cmd.clock_data(mode, data).set_gpio_lower(cs, dir);
+        (cmd, token)
+    }
+
+    /// Read the idle state of MISO with every device deselected.
+    ///
+    /// Drives the chip select pins high, SCK and MOSI low, and reads the lower
+    /// byte GPIO pins several times. Decode the data covered by the returned
+    /// token with [`MisoIdle::from_readings`]. A MISO line reading high when
+    /// no device drives it explains reads of all `0xFF`.
+    ///
+    /// # Arguments
+    ///
+    /// * `miso_pin` - Lower byte GPIO pin number of MISO, usually 2. It is
+    ///   left as an input.
+    /// * `cs_pins` - Lower byte GPIO pin numbers of every chip select.
+    ///
+    /// This will panic for pin numbers greater than 7.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::{MisoIdle, MpsseCmdBuilder};
+    ///
+    /// let (cmd, token) = MpsseCmdBuilder::new().probe_miso_idle(2, &[3, 4]);
+    /// let cmd = cmd.send_immediate();
+    ///
+    /// let response = vec![0x1C; token.len()];
+    /// assert_eq!(
+    ///     MisoIdle::from_readings(token.get(&response), 2),
+    ///     MisoIdle::PullUp
+    /// );
+    /// ```
+    pub fn probe_miso_idle(self, miso_pin: u8, cs_pins: &[u8]) -> (Self, ReadToken) {
+        let miso = pin_mask(miso_pin);
+        let cs = cs_pins.iter().fold(0, |mask, &pin| mask | pin_mask(pin));
+        let mut cmd = self.set_gpio_lower(cs, (SCK | MOSI | cs) & !miso);
+        let token = cmd.read_token(MISO_PROBE_READS);
+        for _ in 0..MISO_PROBE_READS {
+            cmd = cmd.gpio_lower();
+        }
+        (cmd, token)
+    }
+
+    /// Trigger a conversion of a SPI ADC and read the result.
+    ///
+    /// Pulses CONVST high with chip select deasserted, waits `conv_clocks`
+    /// clock cycles for the conversion, then asserts chip select, clocks in
+    /// the result and deasserts chip select. SCK is driven low.
+    ///
+    /// # Arguments
+    ///
+    /// * `convst_pin` - Lower byte GPIO pin number of CONVST, 3-7.
+    /// * `cs` - Lower byte GPIO pin number of the chip select, 3-7.
+    /// * `mode` - Clocking mode of the result.
+    /// * `result_bytes` - Length of the result in bytes.
+    /// * `conv_clocks` - Conversion time in clock cycles of the frequency set
+    ///   with [`set_clock`](MpsseCmdBuilder::set_clock).
+    ///
+    /// This will panic for pin numbers greater than 7.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::{ClockDataIn, MpsseCmdBuilder};
+    ///
+    /// // 16-bit ADC with a 4 µs conversion time, at 10 MHz
+    /// let (cmd, token) = MpsseCmdBuilder::new().spi_adc_read(4, 3, ClockDataIn::MsbPos, 2, 40);
+    /// let cmd = cmd.send_immediate();
+    /// assert_eq!(token.range(), 0..2);
+    /// ```
+    pub fn spi_adc_read(
+        self,
+        convst_pin: u8,
+        cs: u8,
+        mode: ClockDataIn,
+        result_bytes: usize,
+        conv_clocks: usize,
+    ) -> (Self, ReadToken) {
+        let convst = pin_mask(convst_pin);
+        let cs = pin_mask(cs);
+        let dir = SCK | MOSI | cs | convst;
+        let cmd = self
+            .set_gpio_lower(cs | convst, dir)
+            .set_gpio_lower(cs, dir)
+            .dummy_clocks(conv_clocks)
+            .set_gpio_lower(0x00, dir);
+        let token = cmd.read_token(result_bytes);
+        let cmd = cmd
+            .clock_data_in(mode, result_bytes)
+            .set_gpio_lower(cs, dir);
+        (cmd, token)
+    }
+
+    /// Read the inputs of a chain of 74HC165 shift registers.
+    ///
+    /// Pulses the active low SH/LD pin to load the parallel inputs, then
+    /// clocks in one byte per register. The first byte comes from the
+    /// register driving MISO, MSB first being input H with the MSB first
+    /// modes. SCK is driven low and CLK INH must be tied low.
+    ///
+    /// # Arguments
+    ///
+    /// * `load_pin` - Lower byte GPIO pin number of SH/LD, 3-7.
+    /// * `mode` - Clocking mode of the inputs, data is shifted out on the
+    ///   rising edge so [`ClockDataIn::MsbPos`] reads the first bit before
+    ///   it shifts.
+    /// * `num_registers` - Number of registers in the chain.
+    ///
+    /// This will panic for pin numbers greater than 7.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::{ClockDataIn, MpsseCmdBuilder};
+    ///
+    /// let (cmd, inputs) = MpsseCmdBuilder::new().spi_165_read(3, ClockDataIn::MsbPos, 2);
+    /// let cmd = cmd.send_immediate();
+    /// assert_eq!(inputs.len(), 2);
+    /// ```
+    pub fn spi_165_read(
+        self,
+        load_pin: u8,
+        mode: ClockDataIn,
+        num_registers: usize,
+    ) -> (Self, ReadToken) {
+        let load = pin_mask(load_pin);
+        let dir = SCK | MOSI | load;
+        let cmd = self
+            .set_gpio_lower(load, dir)
+            .set_gpio_lower(0x00, dir)
+            .set_gpio_lower(load, dir);
+        let token = cmd.read_token(num_registers);
+        (cmd.clock_data_in(mode, num_registers), token)
+    }
+
+    /// Clock data out and in, padded to a minimum length.
+    ///
+    /// Data shorter than `min_bytes` is followed by [`SPI_PAD_BYTE`] bytes,
+    /// for devices requiring a minimum number of clock cycles per chip select
+    /// assertion. The token covers the whole read, padding included.
+    ///
+    /// This will panic if the padded length is greater than `u16::MAX + 1`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::{ClockData, MpsseCmdBuilder};
+    ///
+    /// let (cmd, token) = MpsseCmdBuilder::new().clock_data_padded(ClockData::MsbPosIn, &[0x9F], 4);
+    /// assert_eq!(cmd.as_slice(), [0x31, 0x03, 0x00, 0x9F, 0xFF, 0xFF, 0xFF]);
+    /// assert_eq!(token.len(), 4);
+    /// ```
+    pub fn clock_data_padded(
+        self,
+        mode: ClockData,
+        data: &[u8],
+        min_bytes: usize,
+    ) -> (Self, ReadToken) {
+        let mut padded = data.to_vec();
+        if padded.len() < min_bytes {
+            padded.resize(min_bytes, SPI_PAD_BYTE);
+        }
+        let token = self.read_token(padded.len());
+        (self.clock_data(mode, &padded), token)
+    }
+}
@@ -0,0 +1,237 @@
+//! SPI command builders.
+use crate::spi::{MOSI, SCK};
+use crate::{MpsseCmdBuilder, ReadToken, SpiDevice, SpiMode};
+use core::marker::PhantomData;
+
+/// [`SpiBuilder`] state with chip select deasserted.
+#[derive(Debug)]
+pub enum Idle {}
+
+/// [`SpiBuilder`] state with chip select asserted.
+#[derive(Debug)]
+pub enum CsAsserted {}
+
+/// SPI command builder enforcing chip select framing at compile time.
+///
+/// Data can only be clocked in the [`CsAsserted`] state, and the commands
+/// can only be built in the [`Idle`] state, so clocking data without
+/// asserting chip select, or leaving chip select asserted at the end of the
+/// commands, do not compile. Several transfers can be chained within a
+/// single chip select assertion, unlike with the [`SpiDevice`] methods.
+///
+/// # Example
+///
+/// ```
+/// use ftdi_mpsse::{SpiBuilder, SpiDevice, SpiMode};
+///
+/// let flash = SpiDevice::new(3, SpiMode::Mode0);
+/// let (spi, data) = SpiBuilder::new(flash)
+///     .cs_assert()
+///     .write(&[0x03, 0x00, 0x10, 0x00])
+///     .read(256);
+/// let cmd = spi.cs_deassert().build().send_immediate();
+/// assert_eq!(data.len(), 256);
+/// ```
+///
+/// Building with chip select asserted is a compile error:
+///
+/// ```compile_fail
+/// use ftdi_mpsse::{SpiBuilder, SpiDevice, SpiMode};
+///
+/// let flash = SpiDevice::new(3, SpiMode::Mode0);
+/// let cmd = SpiBuilder::new(flash).cs_assert().write(&[0x06]).build();
+/// ```
+pub struct SpiBuilder<S> {
+    cmd: MpsseCmdBuilder,
+    device: SpiDevice,
+    state: PhantomData<S>,
+}
+
+impl<S> SpiBuilder<S> {
+    fn transition<T>(
+        self,
+        f: impl FnOnce(&SpiDevice, MpsseCmdBuilder) -> MpsseCmdBuilder,
+    ) -> SpiBuilder<T> {
+        SpiBuilder {
+            cmd: f(&self.device, self.cmd),
+            device: self.device,
+            state: PhantomData,
+        }
+    }
+}
+
+impl SpiBuilder<Idle> {
+    /// Create a builder with a new command stream.
+    ///
+    /// The stream starts with chip select deasserted and SCK at the idle
+    /// level, see [`SpiDevice::init`].
+    pub fn new(device: SpiDevice) -> SpiBuilder<Idle> {
+        SpiBuilder::with_cmd(device, MpsseCmdBuilder::new())
+    }
+
+    /// Create a builder appending to an existing command stream.
+    ///
+    /// Chip select is deasserted and SCK set to the idle level first, see
+    /// [`SpiDevice::init`].
+    pub fn with_cmd(device: SpiDevice, cmd: MpsseCmdBuilder) -> SpiBuilder<Idle> {
+        SpiBuilder {
+            cmd: device.init(cmd),
+            device,
+            state: PhantomData,
+        }
+    }
+
+    /// Assert chip select.
+    pub fn cs_assert(self) -> SpiBuilder<CsAsserted> {
+        self.transition(SpiDevice::assert_cs)
+    }
+
+    /// Get the command stream.
+    pub fn build(self) -> MpsseCmdBuilder {
+        self.cmd
+    }
+}
+
+impl SpiBuilder<CsAsserted> {
+    /// Clock data out and in.
+    ///
+    /// This will panic for data lengths greater than `u16::MAX + 1`.
+    pub fn transfer(mut self, data: &[u8]) -> (Self, ReadToken) {
+        let token = self.cmd.read_token(data.len());
+        self.cmd = self.cmd.clock_data(self.device.mode().data(), data);
+        (self, token)
+    }
+
+    /// Clock data out.
+    ///
+    /// This will panic for data lengths greater than `u16::MAX + 1`.
+    pub fn write(mut self, data: &[u8]) -> Self {
+        self.cmd = self.cmd.clock_data_out(self.device.mode().data_out(), data);
+        self
+    }
+
+    /// Clock data in.
+    pub fn read(mut self, len: usize) -> (Self, ReadToken) {
+        let token = self.cmd.read_token(len);
+        self.cmd = self.cmd.clock_data_in(self.device.mode().data_in(), len);
+        (self, token)
+    }
+
+    /// Deassert chip select.
+    pub fn cs_deassert(self) -> SpiBuilder<Idle> {
+        self.transition(SpiDevice::init)
+    }
+}
+
+/// Single SPI transaction with chip select asserted for its whole duration.
+///
+/// Creating the transaction sets SCK to the idle level of the mode and
+/// asserts chip select, and [`finish`](SpiTransaction::finish) deasserts
+/// it. The data clocking opcodes, which edge data is clocked out on and
+/// which edge it is sampled on, are derived from the [`SpiMode`].
+///
+/// # Example
+///
+/// ```
+/// use ftdi_mpsse::{SpiMode, SpiTransaction};
+///
+/// // chip select on AD3, active low
+/// let (spi, data) = SpiTransaction::new(0x08, false, SpiMode::Mode0)
+///     .write(&[0x03, 0x00, 0x10, 0x00])
+///     .read(256);
+/// let cmd = spi.finish().send_immediate();
+/// assert_eq!(data.len(), 256);
+/// ```
+pub struct SpiTransaction {
+    cmd: MpsseCmdBuilder,
+    cs_mask: u8,
+    cs_active_high: bool,
+    mode: SpiMode,
+}
+
+impl SpiTransaction {
+    /// Start a transaction in a new command stream.
+    ///
+    /// # Arguments
+    ///
+    /// * `cs_mask` - Lower byte GPIO mask of the chip select pins.
+    /// * `cs_active_high` - Chip select level selecting the device.
+    /// * `mode` - SPI mode of the device.
+    ///
+    /// This will panic if `cs_mask` includes SCK, MOSI or MISO.
+    pub fn new(cs_mask: u8, cs_active_high: bool, mode: SpiMode) -> SpiTransaction {
+        SpiTransaction::with_cmd(MpsseCmdBuilder::new(), cs_mask, cs_active_high, mode)
+    }
+
+    /// Start a transaction appending to an existing command stream.
+    ///
+    /// This will panic if `cs_mask` includes SCK, MOSI or MISO.
+    pub fn with_cmd(
+        cmd: MpsseCmdBuilder,
+        cs_mask: u8,
+        cs_active_high: bool,
+        mode: SpiMode,
+    ) -> SpiTransaction {
+        assert!(
+            cs_mask & 0x07 == 0,
+            "chip select cannot use the SCK, MOSI or MISO pins"
+        );
+        let mut transaction = SpiTransaction {
+            cmd,
+            cs_mask,
+            cs_active_high,
+            mode,
+        };
+        // set SCK idle before asserting chip select
+        let (state, direction) = transaction.gpio(false);
+        transaction.cmd = transaction.cmd.set_gpio_lower(state, direction);
+        let (state, direction) = transaction.gpio(true);
+        transaction.cmd = transaction.cmd.set_gpio_lower(state, direction);
+        transaction
+    }
+
+    /// Lower byte GPIO `(state, direction)` with SCK idle.
+    fn gpio(&self, selected: bool) -> (u8, u8) {
+        let cs = if selected == self.cs_active_high {
+            self.cs_mask
+        } else {
+            0x00
+        };
+        let sck = if self.mode.idle_clock_high() {
+            SCK
+        } else {
+            0x00
+        };
+        (cs | sck, SCK | MOSI | self.cs_mask)
+    }
+
+    /// Clock data out.
+    ///
+    /// This will panic for data lengths greater than `u16::MAX + 1`.
+    pub fn write(mut self, data: &[u8]) -> SpiTransaction {
+        self.cmd = self.cmd.clock_data_out(self.mode.data_out(), data);
+        self
+    }
+
+    /// Clock data in.
+    pub fn read(mut self, len: usize) -> (SpiTransaction, ReadToken) {
+        let token = self.cmd.read_token(len);
+        self.cmd = self.cmd.clock_data_in(self.mode.data_in(), len);
+        (self, token)
+    }
+
+    /// Clock data out and in.
+    ///
+    /// This will panic for data lengths greater than `u16::MAX + 1`.
+    pub fn transfer(mut self, data: &[u8]) -> (SpiTransaction, ReadToken) {
+        let token = self.cmd.read_token(data.len());
+        self.cmd = self.cmd.clock_data(self.mode.data(), data);
+        (self, token)
+    }
+
+    /// Deassert chip select and get the command stream.
+    pub fn finish(self) -> MpsseCmdBuilder {
+        let (state, direction) = self.gpio(false);
+        self.cmd.set_gpio_lower(state, direction)
+    }
+}
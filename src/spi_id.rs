@@ -0,0 +1,100 @@
+//! SPI device identification.
+use crate::{MpsseCmdBuilder, MpsseCmdExecutor, SpiDevice, SPI_PAD_BYTE};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// Error returned by [`verify_spi_id`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum SpiIdError<E> {
+    /// Error from the executor.
+    Executor(E),
+    /// The device answered with another ID.
+    MismatchedId {
+        /// Expected ID.
+        expected: Vec<u8>,
+        /// ID read from the device.
+        found: Vec<u8>,
+    },
+}
+
+impl<E: fmt::Display> fmt::Display for SpiIdError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpiIdError::Executor(e) => e.fmt(f),
+            SpiIdError::MismatchedId { expected, found } => write!(
+                f,
+                "expected device ID {:02X?}, found {:02X?}",
+                expected, found
+            ),
+        }
+    }
+}
+
+impl<E: core::error::Error + 'static> core::error::Error for SpiIdError<E> {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            SpiIdError::Executor(e) => Some(e),
+            SpiIdError::MismatchedId { .. } => None,
+        }
+    }
+}
+
+/// Read the ID of a SPI device and check it against the expected value.
+///
+/// Clocks out `id_cmd` followed by one [`SPI_PAD_BYTE`] for each byte of
+/// `expected` with chip select asserted, and compares the
+/// data read during the padding with `expected`. Run this before operating
+/// on a device to catch the wrong device being connected, or a disconnected
+/// bus reading all `0xFF` or `0x00`.
+///
+/// As this is usually the first transaction with the device, the pins are
+/// set up with [`SpiDevice::init`] before chip select is asserted, bringing
+/// SCK to the idle level of the SPI mode.
+///
+/// # Arguments
+///
+/// * `device` - SPI device to identify.
+/// * `id_cmd` - Command reading the ID, such as `0x9F` for SPI flash JEDEC
+///   IDs.
+/// * `expected` - Expected ID.
+///
+/// This will panic if `id_cmd` and `expected` are longer than
+/// `u16::MAX + 1` bytes together.
+///
+/// # Example
+///
+/// ```no_run
+/// use ftdi_mpsse::{verify_spi_id, MpsseCmdExecutor, SpiDevice, SpiIdError, SpiMode};
+///
+/// # fn example<E: MpsseCmdExecutor>(mut exec: E) -> Result<(), SpiIdError<E::Error>> {
+/// // W25Q128 JEDEC ID
+/// let flash = SpiDevice::new(3, SpiMode::Mode0);
+/// verify_spi_id(&mut exec, &flash, &[0x9F], &[0xEF, 0x40, 0x18])?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn verify_spi_id<E: MpsseCmdExecutor>(
+    exec: &mut E,
+    device: &SpiDevice,
+    id_cmd: &[u8],
+    expected: &[u8],
+) -> Result<(), SpiIdError<E::Error>> {
+    let mut data = id_cmd.to_vec();
+    data.resize(id_cmd.len() + expected.len(), SPI_PAD_BYTE);
+    let (cmd, token) = device.transfer(device.init(MpsseCmdBuilder::new()), &data);
+    let cmd = cmd.send_immediate();
+
+    let mut response = vec![0; cmd.read_len()];
+    exec.xfer(cmd.as_slice(), &mut response)
+        .map_err(SpiIdError::Executor)?;
+    let found = &token.get(&response)[id_cmd.len()..];
+    if found == expected {
+        Ok(())
+    } else {
+        Err(SpiIdError::MismatchedId {
+            expected: expected.to_vec(),
+            found: found.to_vec(),
+        })
+    }
+}
@@ -0,0 +1,190 @@
+//! Snapshot and restore of the MPSSE state.
+use crate::{parse, MpsseCmd, MpsseCmdBuilder, ReadToken};
+
+/// MPSSE state captured by [`capture_state`](MpsseCmdBuilder::capture_state).
+///
+/// Only the GPIO pin levels can be read from the device. The other settings
+/// are tracked from the commands in the command stream before the capture,
+/// and are `None` if the stream does not set them.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct DeviceState {
+    /// Lower byte GPIO pin levels.
+    pub lower: u8,
+    /// Upper byte GPIO pin levels.
+    pub upper: u8,
+    /// Lower byte GPIO direction.
+    pub lower_direction: Option<u8>,
+    /// Upper byte GPIO direction.
+    pub upper_direction: Option<u8>,
+    /// Clock divisor.
+    pub clock_divisor: Option<u16>,
+    /// Clock divide by 5.
+    pub clock_divide: Option<bool>,
+    /// 3 phase data clocking.
+    pub three_phase: Option<bool>,
+    /// Adaptive clocking.
+    pub adaptive: Option<bool>,
+    /// Internal loopback.
+    pub loopback: Option<bool>,
+    /// Lower and upper byte masks of the drive-only-zero pins, see
+    /// [`enable_drive_only_zero`](MpsseCmdBuilder::enable_drive_only_zero).
+    pub drive_only_zero: Option<(u8, u8)>,
+}
+
+/// Token to decode the [`DeviceState`] captured by
+/// [`capture_state`](MpsseCmdBuilder::capture_state) from the device
+/// response.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct StateToken {
+    gpio: ReadToken,
+    shadow: DeviceState,
+}
+
+impl StateToken {
+    /// Get the captured state from the device response.
+    ///
+    /// This will panic if the response is too short.
+    pub fn decode(&self, response: &[u8]) -> DeviceState {
+        let gpio = self.gpio.get(response);
+        DeviceState {
+            lower: gpio[0],
+            upper: gpio[1],
+            ..self.shadow
+        }
+    }
+}
+
+impl MpsseCmdBuilder {
+    /// Settings of the device after the command stream, as far as they are
+    /// set by the stream.
+    fn shadow_state(&self) -> DeviceState {
+        let mut state = DeviceState {
+            lower: 0x00,
+            upper: 0x00,
+            lower_direction: None,
+            upper_direction: None,
+            clock_divisor: None,
+            clock_divide: None,
+            three_phase: None,
+            adaptive: None,
+            loopback: None,
+            drive_only_zero: None,
+        };
+        for cmd in parse::commands(self.as_slice()).filter_map(Result::ok) {
+            let bytes = cmd.bytes;
+            match bytes[0] {
+                0x80 => state.lower_direction = Some(bytes[2]),
+                0x82 => state.upper_direction = Some(bytes[2]),
+                0x84 => state.loopback = Some(true),
+                0x85 => state.loopback = Some(false),
+                0x86 => state.clock_divisor = Some(u16::from(bytes[1]) | u16::from(bytes[2]) << 8),
+                0x8A => state.clock_divide = Some(false),
+                0x8B => state.clock_divide = Some(true),
+                0x8C => state.three_phase = Some(true),
+                0x8D => state.three_phase = Some(false),
+                0x96 => state.adaptive = Some(true),
+                0x97 => state.adaptive = Some(false),
+                0x9E => state.drive_only_zero = Some((bytes[1], bytes[2])),
+                _ => {}
+            }
+        }
+        state
+    }
+
+    /// Capture the MPSSE state.
+    ///
+    /// Reads both GPIO bytes and records the settings made by the earlier
+    /// commands of the stream, decode the state from the response with
+    /// [`StateToken::decode`]. The GPIO directions cannot be read from the
+    /// device, they are only known if the stream sets them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::MpsseCmdBuilder;
+    ///
+    /// let cmd = MpsseCmdBuilder::new()
+    ///     .set_clock(5, Some(false))
+    ///     .set_gpio_lower(0x08, 0x0B);
+    /// let (cmd, token) = cmd.capture_state();
+    /// let cmd = cmd.send_immediate();
+    ///
+    /// let state = token.decode(&[0x0C, 0xFF]);
+    /// assert_eq!(state.lower, 0x0C);
+    /// assert_eq!(state.lower_direction, Some(0x0B));
+    /// assert_eq!(state.clock_divisor, Some(5));
+    /// assert_eq!(state.upper_direction, None);
+    /// ```
+    pub fn capture_state(self) -> (Self, StateToken) {
+        let token = StateToken {
+            gpio: self.read_token(2),
+            shadow: self.shadow_state(),
+        };
+        (self.gpio_lower().gpio_upper(), token)
+    }
+
+    /// Restore a captured MPSSE state.
+    ///
+    /// Sets every setting known in `state`, and the GPIO bytes whose
+    /// direction is known. Output pins are set to the captured levels.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::MpsseCmdBuilder;
+    ///
+    /// let (cmd, token) = MpsseCmdBuilder::new()
+    ///     .set_gpio_lower(0x08, 0x0B)
+    ///     .capture_state();
+    /// // response to the capture
+    /// let state = token.decode(&[0x08, 0x00]);
+    ///
+    /// let cmd = MpsseCmdBuilder::new()
+    ///     .set_gpio_lower(0x00, 0xFF)
+    ///     .restore(&state);
+    /// assert_eq!(cmd.as_slice(), [0x80, 0x00, 0xFF, 0x80, 0x08, 0x0B]);
+    /// ```
+    pub fn restore(mut self, state: &DeviceState) -> Self {
+        let setting = |enabled: Option<bool>, on: MpsseCmd, off: MpsseCmd| {
+            enabled.map(|enabled| if enabled { on } else { off })
+        };
+        let settings = [
+            setting(
+                state.loopback,
+                MpsseCmd::EnableLoopback,
+                MpsseCmd::DisableLoopback,
+            ),
+            setting(
+                state.clock_divide,
+                MpsseCmd::EnableClockDivide,
+                MpsseCmd::DisableClockDivide,
+            ),
+            setting(
+                state.three_phase,
+                MpsseCmd::Enable3PhaseClocking,
+                MpsseCmd::Disable3PhaseClocking,
+            ),
+            setting(
+                state.adaptive,
+                MpsseCmd::EnableAdaptiveClocking,
+                MpsseCmd::DisableAdaptiveClocking,
+            ),
+        ];
+        for cmd in settings.iter().flatten() {
+            self.0.push((*cmd).into());
+        }
+        if let Some((lower_mask, upper_mask)) = state.drive_only_zero {
+            self = self.enable_drive_only_zero(lower_mask, upper_mask);
+        }
+        if let Some(divisor) = state.clock_divisor {
+            self = self.set_clock(divisor.into(), None);
+        }
+        if let Some(direction) = state.lower_direction {
+            self = self.set_gpio_lower(state.lower & direction, direction);
+        }
+        if let Some(direction) = state.upper_direction {
+            self = self.set_gpio_upper(state.upper & direction, direction);
+        }
+        self
+    }
+}
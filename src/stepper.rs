@@ -0,0 +1,145 @@
+//! Stepper motor driven over lower byte GPIO pins.
+use crate::{pin_mask, MpsseCmdBuilder};
+
+/// Coil energization sequence of a [`Stepper`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum StepMode {
+    /// One coil energized at a time, lowest torque and power.
+    Wave,
+    /// Two adjacent coils energized at a time, full torque.
+    Full,
+    /// Alternating one and two coils, twice the resolution of full steps.
+    Half,
+}
+
+impl StepMode {
+    /// Coils energized at each position of the sequence, bit `n` being coil
+    /// `n`.
+    pub const fn sequence(self) -> &'static [u8] {
+        match self {
+            StepMode::Wave => &[0b0001, 0b0010, 0b0100, 0b1000],
+            StepMode::Full => &[0b0011, 0b0110, 0b1100, 0b1001],
+            StepMode::Half => &[
+                0b0001, 0b0011, 0b0010, 0b0110, 0b0100, 0b1100, 0b1000, 0b1001,
+            ],
+        }
+    }
+}
+
+/// Stepper motor with 4 coils driven by lower byte GPIO pins.
+///
+/// The coils are energized in order for forward steps, through a driver
+/// such as a ULN2003 for a unipolar motor. The stepper keeps track of its
+/// position so consecutive moves continue the sequence.
+///
+/// Only the coil pins are outputs, the other lower byte pins are set as
+/// inputs by every step.
+///
+/// # Example
+///
+/// ```
+/// use ftdi_mpsse::{MpsseCmdBuilder, StepMode, Stepper};
+///
+/// let mut stepper = Stepper::new([4, 5, 6, 7], StepMode::Full);
+/// // 200 steps per second with a 1 MHz clock
+/// let step_clocks = Stepper::step_clocks(200, 1_000_000);
+/// let cmd = stepper.steps(MpsseCmdBuilder::new(), 200, step_clocks);
+/// let cmd = stepper.steps(cmd, -50, step_clocks);
+/// let cmd = stepper.release(cmd);
+/// assert_eq!(stepper.position(), 150);
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Stepper {
+    coils: [u8; 4],
+    mode: StepMode,
+    position: i32,
+}
+
+impl Stepper {
+    /// Create a stepper at position 0.
+    ///
+    /// # Arguments
+    ///
+    /// * `coils` - Lower byte GPIO pin numbers of the coils, in sequence
+    ///   order.
+    /// * `mode` - Coil energization sequence.
+    ///
+    /// This will panic for pin numbers greater than 7.
+    pub fn new(coils: [u8; 4], mode: StepMode) -> Stepper {
+        Stepper {
+            coils: [
+                pin_mask(coils[0]),
+                pin_mask(coils[1]),
+                pin_mask(coils[2]),
+                pin_mask(coils[3]),
+            ],
+            mode,
+            position: 0,
+        }
+    }
+
+    /// Coil energization sequence.
+    pub fn mode(&self) -> StepMode {
+        self.mode
+    }
+
+    /// Number of steps taken, forward steps being positive.
+    pub fn position(&self) -> i32 {
+        self.position
+    }
+
+    /// Number of clocks per step for a step rate.
+    ///
+    /// Rounded up so the rate is never exceeded, `clock_hz` must match the
+    /// frequency set with [`set_clock`](MpsseCmdBuilder::set_clock).
+    ///
+    /// This will panic if `steps_per_second` is zero.
+    pub fn step_clocks(steps_per_second: u32, clock_hz: u32) -> usize {
+        assert!(steps_per_second != 0, "step rate cannot be zero");
+        clock_hz.div_ceil(steps_per_second) as usize
+    }
+
+    fn direction(&self) -> u8 {
+        self.coils.iter().fold(0x00, |mask, coil| mask | coil)
+    }
+
+    /// Lower byte GPIO state energizing the coils of the current position.
+    fn state(&self) -> u8 {
+        let sequence = self.mode.sequence();
+        let index = self.position.rem_euclid(sequence.len() as i32) as usize;
+        self.coils
+            .iter()
+            .enumerate()
+            .filter(|(coil, _)| sequence[index] >> coil & 0x01 != 0)
+            .fold(0x00, |state, (_, mask)| state | mask)
+    }
+
+    /// Energize the coils of the current position to hold it.
+    pub fn hold(&self, cmd: MpsseCmdBuilder) -> MpsseCmdBuilder {
+        cmd.set_gpio_lower(self.state(), self.direction())
+    }
+
+    /// De-energize every coil, the motor is free to turn.
+    pub fn release(&self, cmd: MpsseCmdBuilder) -> MpsseCmdBuilder {
+        cmd.set_gpio_lower(0x00, self.direction())
+    }
+
+    /// Move by `count` steps, in reverse for a negative count.
+    ///
+    /// Each step energizes the coils of the next position and waits for
+    /// `step_clocks` clocks, see [`step_clocks`](Stepper::step_clocks). The
+    /// coils stay energized after the last step.
+    pub fn steps(
+        &mut self,
+        mut cmd: MpsseCmdBuilder,
+        count: i32,
+        step_clocks: usize,
+    ) -> MpsseCmdBuilder {
+        let step = count.signum();
+        for _ in 0..count.unsigned_abs() {
+            self.position = self.position.wrapping_add(step);
+            cmd = self.hold(cmd).dummy_clocks(step_clocks);
+        }
+        cmd
+    }
+}
@@ -0,0 +1,133 @@
+//! Serial Wire Debug (SWD) wire protocol helpers.
+//!
+//! SWD uses a single bidirectional data line. These helpers assume the usual
+//! MPSSE wiring:
+//!
+//! * SWCLK on AD0 (TCK/SK).
+//! * SWDIO driven from AD1 (TDI/DO) through a series resistor.
+//! * SWDIO sampled on AD2 (TDO/DI).
+//!
+//! Bus turnaround is done by switching AD1 between output and input with
+//! [`set_gpio_lower`], which also sets AD3-AD7 as inputs.
+//!
+//! [`set_gpio_lower`]: crate::MpsseCmdBuilder::set_gpio_lower
+use crate::{ClockBitsIn, ClockBitsOut, ClockDataIn, ClockDataOut, MpsseCmdBuilder, ReadToken};
+
+/// Lower GPIO state with SWCLK low and SWDIO high.
+const SWD_STATE: u8 = 0x02;
+/// Lower GPIO direction with the host driving SWDIO.
+const SWD_DIR_DRIVE: u8 = 0x03;
+/// Lower GPIO direction with SWDIO released to the target.
+const SWD_DIR_RELEASE: u8 = 0x01;
+
+/// SWD acknowledge response.
+///
+/// Decoded from the token returned by
+/// [`swd_read_ack`](MpsseCmdBuilder::swd_read_ack).
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum SwdAck {
+    /// Transfer accepted.
+    Ok,
+    /// Target busy, the transfer must be retried.
+    Wait,
+    /// Target reported an error.
+    Fault,
+}
+
+impl SwdAck {
+    /// Decode the acknowledge from the byte read by
+    /// [`swd_read_ack`](MpsseCmdBuilder::swd_read_ack).
+    ///
+    /// Returns `None` for an invalid acknowledge, which usually means no
+    /// target is responding.
+    pub fn from_response(byte: u8) -> Option<SwdAck> {
+        // 4 bits clocked in LSB first land in bits 4-7, the first being the
+        // turnaround cycle.
+        match byte >> 5 {
+            0b001 => Some(SwdAck::Ok),
+            0b010 => Some(SwdAck::Wait),
+            0b100 => Some(SwdAck::Fault),
+            _ => None,
+        }
+    }
+}
+
+/// Decode the data read by [`swd_read_data`](MpsseCmdBuilder::swd_read_data).
+///
+/// Returns `None` if the parity check fails.
+///
+/// This will panic if `response` is shorter than 5 bytes.
+pub fn swd_decode_data(response: &[u8]) -> Option<u32> {
+    let data = u32::from_le_bytes([response[0], response[1], response[2], response[3]]);
+    let parity = response[4] >> 7;
+    if data.count_ones() & 1 == u32::from(parity) {
+        Some(data)
+    } else {
+        None
+    }
+}
+
+impl MpsseCmdBuilder {
+    /// Clock out an SWD request packet and release SWDIO to the target.
+    ///
+    /// SWDIO must already be driven by the host, as it is after
+    /// `set_gpio_lower(0x02, 0x03)` or a previous data phase.
+    ///
+    /// # Arguments
+    ///
+    /// * `ap` - Access an access port register rather than a debug port
+    ///   register.
+    /// * `read` - Read the register rather than write it.
+    /// * `addr` - Register address, only bits 2 and 3 are used.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::MpsseCmdBuilder;
+    ///
+    /// // read DP IDCODE
+    /// let cmd = MpsseCmdBuilder::new().swd_request(false, true, 0x0);
+    /// assert_eq!(cmd.as_slice()[2], 0xA5);
+    /// ```
+    pub fn swd_request(self, ap: bool, read: bool, addr: u8) -> Self {
+        let fields = (ap as u8) | ((read as u8) << 1) | (((addr >> 2) & 0x3) << 2);
+        let parity = (fields.count_ones() & 1) as u8;
+        // start, APnDP, RnW, A[2:3], parity, stop, park
+        let request = 0x01 | (fields << 1) | (parity << 5) | 0x80;
+        self.clock_bits_out(ClockBitsOut::LsbNeg, request, 8)
+            .set_gpio_lower(SWD_STATE, SWD_DIR_RELEASE)
+    }
+
+    /// Clock in the turnaround cycle and the 3 bit SWD acknowledge.
+    ///
+    /// The returned token covers a single byte, decode it with
+    /// [`SwdAck::from_response`].
+    pub fn swd_read_ack(self) -> (Self, ReadToken) {
+        let token = self.read_token(1);
+        (self.clock_bits_in(ClockBitsIn::LsbPos, 4), token)
+    }
+
+    /// Clock in 32 bits of data and the parity bit, then take back SWDIO.
+    ///
+    /// The returned token covers 5 bytes, decode them with
+    /// [`swd_decode_data`].
+    pub fn swd_read_data(self) -> (Self, ReadToken) {
+        let token = self.read_token(5);
+        let cmd = self
+            .clock_data_in(ClockDataIn::LsbPos, 4)
+            .clock_bits_in(ClockBitsIn::LsbPos, 1)
+            .clock_bits_out(ClockBitsOut::LsbNeg, 0, 1)
+            .set_gpio_lower(SWD_STATE, SWD_DIR_DRIVE);
+        (cmd, token)
+    }
+
+    /// Take back SWDIO after the acknowledge and clock out 32 bits of data
+    /// followed by the parity bit.
+    pub fn swd_write_data(self, data: u32) -> Self {
+        let parity = (data.count_ones() & 1) as u8;
+        self.clock_bits_out(ClockBitsOut::LsbNeg, 0, 1)
+            .set_gpio_lower(SWD_STATE, SWD_DIR_DRIVE)
+            .clock_data_out(ClockDataOut::LsbNeg, &data.to_le_bytes())
+            .clock_bits_out(ClockBitsOut::LsbNeg, parity, 1)
+    }
+}
@@ -0,0 +1,71 @@
+//! SPI clock frequency sweep.
+use crate::{ClockData, MpsseCmdBuilder, MpsseCmdExecutor};
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Largest clock divisor.
+const MAX_DIVISOR: u32 = 0xFFFF;
+
+/// Clock divisor for the highest frequency not above `frequency`.
+fn divisor(base: u32, frequency: u32) -> u32 {
+    let period = u64::from(frequency) * 2;
+    let divisor = u64::from(base).div_ceil(period).saturating_sub(1);
+    divisor.min(u64::from(MAX_DIVISOR)) as u32
+}
+
+/// Test data clocking at increasing frequencies.
+///
+/// For each frequency, sets the clock divisor and clocks `test_pattern` out
+/// and back in through the internal loopback, and records whether the data
+/// read back matches. The divisor is rounded up so the clock never exceeds
+/// the requested frequency. The divide by 5 setting is left unchanged, and
+/// the clock is left at the last frequency.
+///
+/// # Arguments
+///
+/// * `base` - MPSSE base clock, 60 MHz for H series devices with the divide
+///   by 5 disabled, 12 MHz otherwise.
+/// * `frequencies` - Clock frequencies to test, in Hz.
+/// * `test_pattern` - Data clocked through the loopback.
+///
+/// This will panic for a zero frequency, or for test patterns longer than
+/// `u16::MAX + 1` bytes.
+///
+/// # Example
+///
+/// ```no_run
+/// use ftdi_mpsse::{spi_clock_sweep, MpsseCmdExecutor};
+///
+/// # fn example<E: MpsseCmdExecutor>(mut exec: E) -> Result<(), E::Error> {
+/// let frequencies = [1_000_000, 5_000_000, 10_000_000, 30_000_000];
+/// let results = spi_clock_sweep(&mut exec, 60_000_000, &frequencies, &[0x55, 0xAA])?;
+/// let highest = results
+///     .iter()
+///     .filter(|(_, passed)| *passed)
+///     .map(|(frequency, _)| *frequency)
+///     .max();
+/// println!("highest passing clock: {:?}", highest);
+/// # Ok(())
+/// # }
+/// ```
+pub fn spi_clock_sweep<E: MpsseCmdExecutor>(
+    exec: &mut E,
+    base: u32,
+    frequencies: &[u32],
+    test_pattern: &[u8],
+) -> Result<Vec<(u32, bool)>, E::Error> {
+    let mut results = Vec::with_capacity(frequencies.len());
+    let mut response = vec![0; test_pattern.len()];
+    for &frequency in frequencies {
+        assert!(frequency != 0, "clock frequency cannot be zero");
+        let cmd = MpsseCmdBuilder::new()
+            .set_clock(divisor(base, frequency), None)
+            .enable_loopback()
+            .clock_data(ClockData::MsbPosIn, test_pattern)
+            .disable_loopback()
+            .send_immediate();
+        exec.xfer(cmd.as_slice(), &mut response)?;
+        results.push((frequency, response == test_pattern));
+    }
+    Ok(results)
+}
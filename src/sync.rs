@@ -0,0 +1,218 @@
+//! Bad command echo detection and MPSSE synchronization.
+use crate::{
+    parse, ClockDataOut, MpsseCmd, MpsseCmdBuilder, MpsseCmdExecutor, ReadToken, MAX_CLOCK_DATA_LEN,
+};
+use alloc::vec::Vec;
+use core::fmt;
+
+/// First byte of the response the MPSSE sends for an unrecognized opcode.
+///
+/// The device responds to a bad command with this byte followed by the
+/// offending opcode.
+pub const BAD_COMMAND_RESPONSE: u8 = 0xFA;
+
+/// Find a bad command echo in data read from the device.
+///
+/// Returns the offset of the first `0xFA` byte that is followed by an opcode
+/// the MPSSE does not recognize.
+///
+/// Data clocked in from a device may legitimately contain such a byte pair,
+/// so a match is only a strong hint that the device fell out of sync with the
+/// command stream.
+///
+/// # Example
+///
+/// ```
+/// use ftdi_mpsse::find_bad_command_echo;
+///
+/// assert_eq!(find_bad_command_echo(&[0x12, 0xFA, 0xAA]), Some(1));
+/// assert_eq!(find_bad_command_echo(&[0x12, 0xFA, 0x80]), None);
+/// ```
+pub fn find_bad_command_echo(response: &[u8]) -> Option<usize> {
+    response
+        .windows(2)
+        .position(|w| w[0] == BAD_COMMAND_RESPONSE && parse::is_bad_command(w[1]))
+}
+
+/// Bad opcode sent by [`sync_bad_command`](MpsseCmdBuilder::sync_bad_command).
+pub(crate) const SYNC_BAD_COMMAND: u8 = 0xAA;
+
+/// Error returned by [`verify_sync_response`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum SyncError {
+    /// The response is not 2 bytes long.
+    WrongLength {
+        /// Length of the response.
+        len: usize,
+    },
+    /// The response is not the echo of the bad command.
+    UnexpectedResponse {
+        /// Bytes received.
+        found: [u8; 2],
+    },
+}
+
+impl fmt::Display for SyncError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SyncError::WrongLength { len } => {
+                write!(f, "expected a 2 byte bad command echo, got {} bytes", len)
+            }
+            SyncError::UnexpectedResponse { found } => write!(
+                f,
+                "expected bad command echo [0xFA, 0xAA], got {:02X?}",
+                found
+            ),
+        }
+    }
+}
+
+impl core::error::Error for SyncError {}
+
+/// Check the response to [`sync_bad_command`](MpsseCmdBuilder::sync_bad_command).
+///
+/// The response must be exactly the echo `[0xFA, 0xAA]`. Extra bytes, such as
+/// stale data from earlier commands, mean the host is not in sync with the
+/// device.
+///
+/// # Example
+///
+/// ```
+/// use ftdi_mpsse::{verify_sync_response, SyncError};
+///
+/// assert_eq!(verify_sync_response(&[0xFA, 0xAA]), Ok(()));
+/// assert_eq!(
+///     verify_sync_response(&[0x00, 0xFA]),
+///     Err(SyncError::UnexpectedResponse { found: [0x00, 0xFA] })
+/// );
+/// ```
+pub fn verify_sync_response(resp: &[u8]) -> Result<(), SyncError> {
+    match resp {
+        [BAD_COMMAND_RESPONSE, SYNC_BAD_COMMAND] => Ok(()),
+        &[first, second] => Err(SyncError::UnexpectedResponse {
+            found: [first, second],
+        }),
+        _ => Err(SyncError::WrongLength { len: resp.len() }),
+    }
+}
+
+/// Opcode used as a marker to resynchronize with the device.
+pub(crate) const SYNC_MARKER: u8 = 0xAB;
+
+/// Maximum number of stale bytes discarded while looking for the marker echo.
+const SYNC_MAX_DISCARD: usize = 4096;
+
+/// Resynchronize with the device using a bad command marker.
+///
+/// Sends a bad opcode and reads back until its echo is found, discarding any
+/// stale bytes left over from earlier commands. Returns `false` if the echo
+/// was not found.
+pub(crate) fn resync<E: MpsseCmdExecutor>(exec: &mut E) -> Result<bool, E::Error> {
+    exec.send(&[SYNC_MARKER, MpsseCmd::SendImmediate.into()])?;
+    let mut window = [0; 2];
+    exec.recv(&mut window)?;
+    for _ in 0..SYNC_MAX_DISCARD {
+        if window == [BAD_COMMAND_RESPONSE, SYNC_MARKER] {
+            return Ok(true);
+        }
+        window[0] = window[1];
+        exec.recv(&mut window[1..])?;
+    }
+    Ok(false)
+}
+
+impl MpsseCmdBuilder {
+    /// Send the bad opcode `0xAA` to check synchronization with the device.
+    ///
+    /// This is the handshake recommended by FTDI AN_135 after enabling the
+    /// MPSSE: the device answers with the 2 byte echo `[0xFA, 0xAA]`, check
+    /// it with [`verify_sync_response`]. Follow with
+    /// [`send_immediate`](MpsseCmdBuilder::send_immediate) to get the
+    /// response right away.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::MpsseCmdBuilder;
+    ///
+    /// let cmd = MpsseCmdBuilder::new().sync_bad_command().send_immediate();
+    /// assert_eq!(cmd.as_slice(), [0xAA, 0x87]);
+    /// assert_eq!(cmd.read_len(), 2);
+    /// ```
+    pub fn sync_bad_command(mut self) -> Self {
+        self.0.push(SYNC_BAD_COMMAND);
+        self
+    }
+
+    /// Insert a sync marker.
+    ///
+    /// The marker is a bad opcode that the device echoes as
+    /// `[BAD_COMMAND_RESPONSE, 0xAB]`, at the position of the returned token
+    /// in the response. Any other value means the response is out of sync
+    /// with the command stream.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::{MpsseCmdBuilder, BAD_COMMAND_RESPONSE};
+    ///
+    /// let (cmd, marker) = MpsseCmdBuilder::new().gpio_lower().sync_marker();
+    /// let cmd = cmd.send_immediate();
+    /// assert_eq!(cmd.as_slice(), [0x81, 0xAB, 0x87]);
+    ///
+    /// let response = [0x08, BAD_COMMAND_RESPONSE, 0xAB];
+    /// assert_eq!(marker.get(&response), [BAD_COMMAND_RESPONSE, 0xAB]);
+    /// ```
+    pub fn sync_marker(mut self) -> (Self, ReadToken) {
+        let token = self.read_token(2);
+        self.0.push(SYNC_MARKER);
+        (self, token)
+    }
+
+    /// Clock data out with a sync marker after every `marker_interval` bytes.
+    ///
+    /// A marker follows each chunk of data, including the last one, see
+    /// [`sync_marker`](MpsseCmdBuilder::sync_marker). On a marginal link the
+    /// first marker not echoed as expected locates the chunk where the
+    /// command stream or the response was corrupted.
+    ///
+    /// This will panic if `marker_interval` is 0.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::{ClockDataOut, MpsseCmdBuilder, BAD_COMMAND_RESPONSE};
+    ///
+    /// let (cmd, markers) = MpsseCmdBuilder::new().clock_data_out_with_sync(
+    ///     ClockDataOut::MsbNeg,
+    ///     &[0x55; 1000],
+    ///     256,
+    /// );
+    /// let cmd = cmd.send_immediate();
+    /// assert_eq!(markers.len(), 4);
+    ///
+    /// # let response = [BAD_COMMAND_RESPONSE, 0xAB].repeat(4);
+    /// let corrupted = markers
+    ///     .iter()
+    ///     .position(|marker| marker.get(&response) != [BAD_COMMAND_RESPONSE, 0xAB]);
+    /// assert_eq!(corrupted, None);
+    /// ```
+    pub fn clock_data_out_with_sync(
+        mut self,
+        mode: ClockDataOut,
+        data: &[u8],
+        marker_interval: usize,
+    ) -> (Self, Vec<ReadToken>) {
+        assert!(marker_interval != 0, "marker interval cannot be zero");
+        let mut markers = Vec::with_capacity(data.len().div_ceil(marker_interval));
+        for chunk in data.chunks(marker_interval) {
+            for command in chunk.chunks(MAX_CLOCK_DATA_LEN) {
+                self = self.clock_data_out(mode, command);
+            }
+            let (cmd, marker) = self.sync_marker();
+            self = cmd;
+            markers.push(marker);
+        }
+        (self, markers)
+    }
+}
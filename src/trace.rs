@@ -0,0 +1,261 @@
+//! Recording and replay of executor sessions.
+use crate::{MpsseCmdExecutor, MpsseSettings};
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Executor operation captured in a [`Trace`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TraceOp {
+    /// Call to [`init`](MpsseCmdExecutor::init).
+    Init(MpsseSettings),
+    /// Call to [`send`](MpsseCmdExecutor::send) with the data sent.
+    Send(Vec<u8>),
+    /// Call to [`recv`](MpsseCmdExecutor::recv) with the data received.
+    Recv(Vec<u8>),
+    /// Call to [`xfer`](MpsseCmdExecutor::xfer) with the data sent and
+    /// received.
+    Xfer {
+        /// Data sent.
+        tx: Vec<u8>,
+        /// Data received.
+        rx: Vec<u8>,
+    },
+}
+
+/// Timestamped executor operation.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TraceEvent {
+    /// Time elapsed from the start of the recording when the operation
+    /// completed.
+    pub elapsed: Duration,
+    /// Operation.
+    pub op: TraceOp,
+}
+
+/// Executor session recorded by [`RecordingExecutor`].
+///
+/// Only operations that completed successfully are recorded. With the `serde`
+/// feature enabled traces can be serialized.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Trace {
+    /// Recorded operations, in call order.
+    pub events: Vec<TraceEvent>,
+}
+
+impl Trace {
+    /// Run the recorded operations against another executor.
+    ///
+    /// Data is received into buffers of the recorded lengths. Returns the
+    /// trace of the new session, which can be compared to this one.
+    pub fn replay<E: MpsseCmdExecutor>(&self, exec: &mut E) -> Result<Trace, E::Error> {
+        let mut recorder = RecordingExecutor::new(exec);
+        for event in &self.events {
+            match &event.op {
+                TraceOp::Init(settings) => recorder.init(settings)?,
+                TraceOp::Send(data) => recorder.send(data)?,
+                TraceOp::Recv(data) => recorder.recv(&mut vec![0; data.len()])?,
+                TraceOp::Xfer { tx, rx } => recorder.xfer(tx, &mut vec![0; rx.len()])?,
+            }
+        }
+        Ok(recorder.into_trace())
+    }
+}
+
+/// Executor decorator recording every operation into a [`Trace`].
+///
+/// # Example
+///
+/// ```no_run
+/// use ftdi_mpsse::{MpsseCmdBuilder, MpsseCmdExecutor, RecordingExecutor};
+///
+/// # fn example<E: MpsseCmdExecutor>(exec: E) -> Result<(), E::Error> {
+/// let mut exec = RecordingExecutor::new(exec);
+/// let cmd = MpsseCmdBuilder::new().gpio_lower().send_immediate();
+/// let mut gpio = [0; 1];
+/// exec.xfer(cmd.as_slice(), &mut gpio)?;
+/// println!("{:?}", exec.trace());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct RecordingExecutor<E> {
+    inner: E,
+    start: Instant,
+    trace: Trace,
+}
+
+impl<E> RecordingExecutor<E> {
+    /// Wrap an executor, starting the recording.
+    pub fn new(inner: E) -> RecordingExecutor<E> {
+        RecordingExecutor {
+            inner,
+            start: Instant::now(),
+            trace: Trace::default(),
+        }
+    }
+
+    /// Operations recorded so far.
+    pub fn trace(&self) -> &Trace {
+        &self.trace
+    }
+
+    /// Stop recording and return the trace.
+    pub fn into_trace(self) -> Trace {
+        self.trace
+    }
+
+    /// Get a reference to the wrapped executor.
+    pub fn inner(&self) -> &E {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the wrapped executor.
+    pub fn inner_mut(&mut self) -> &mut E {
+        &mut self.inner
+    }
+
+    /// Unwrap the executor, discarding the trace.
+    pub fn into_inner(self) -> E {
+        self.inner
+    }
+
+    fn record(&mut self, op: TraceOp) {
+        self.trace.events.push(TraceEvent {
+            elapsed: self.start.elapsed(),
+            op,
+        });
+    }
+}
+
+impl<E: MpsseCmdExecutor> MpsseCmdExecutor for RecordingExecutor<E> {
+    type Error = E::Error;
+
+    fn init(&mut self, settings: &MpsseSettings) -> Result<(), Self::Error> {
+        self.inner.init(settings)?;
+        self.record(TraceOp::Init(*settings));
+        Ok(())
+    }
+
+    fn send(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        self.inner.send(data)?;
+        self.record(TraceOp::Send(data.to_vec()));
+        Ok(())
+    }
+
+    fn recv(&mut self, data: &mut [u8]) -> Result<(), Self::Error> {
+        self.inner.recv(data)?;
+        self.record(TraceOp::Recv(data.to_vec()));
+        Ok(())
+    }
+
+    fn xfer(&mut self, txdata: &[u8], rxdata: &mut [u8]) -> Result<(), Self::Error> {
+        self.inner.xfer(txdata, rxdata)?;
+        self.record(TraceOp::Xfer {
+            tx: txdata.to_vec(),
+            rx: rxdata.to_vec(),
+        });
+        Ok(())
+    }
+
+    fn purge(&mut self) -> Result<(), Self::Error> {
+        self.inner.purge()
+    }
+}
+
+/// Error returned by [`ReplayExecutor`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ReplayError {
+    /// The operation does not match the recorded one.
+    Mismatch {
+        /// Index of the recorded event.
+        index: usize,
+    },
+    /// All recorded operations have been replayed.
+    Exhausted,
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::Mismatch { index } => {
+                write!(f, "operation does not match recorded event {}", index)
+            }
+            ReplayError::Exhausted => write!(f, "no recorded operations left"),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+/// Executor answering with the responses of a recorded [`Trace`].
+///
+/// Every operation must match the next recorded one: the same kind of call,
+/// with the same data sent and the same receive length. Settings passed to
+/// `init` are not compared.
+#[derive(Debug, Clone)]
+pub struct ReplayExecutor {
+    trace: Trace,
+    position: usize,
+}
+
+impl ReplayExecutor {
+    /// Create an executor replaying a trace.
+    pub fn new(trace: Trace) -> ReplayExecutor {
+        ReplayExecutor { trace, position: 0 }
+    }
+
+    /// Number of recorded operations not replayed yet.
+    pub fn remaining(&self) -> usize {
+        self.trace.events.len() - self.position
+    }
+
+    fn next(&mut self) -> Result<(usize, &TraceOp), ReplayError> {
+        let index = self.position;
+        let event = self.trace.events.get(index).ok_or(ReplayError::Exhausted)?;
+        self.position += 1;
+        Ok((index, &event.op))
+    }
+}
+
+impl MpsseCmdExecutor for ReplayExecutor {
+    type Error = ReplayError;
+
+    fn init(&mut self, _settings: &MpsseSettings) -> Result<(), Self::Error> {
+        match self.next()? {
+            (_, TraceOp::Init(_)) => Ok(()),
+            (index, _) => Err(ReplayError::Mismatch { index }),
+        }
+    }
+
+    fn send(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        match self.next()? {
+            (_, TraceOp::Send(sent)) if sent.as_slice() == data => Ok(()),
+            (index, _) => Err(ReplayError::Mismatch { index }),
+        }
+    }
+
+    fn recv(&mut self, data: &mut [u8]) -> Result<(), Self::Error> {
+        match self.next()? {
+            (_, TraceOp::Recv(received)) if received.len() == data.len() => {
+                data.copy_from_slice(received);
+                Ok(())
+            }
+            (index, _) => Err(ReplayError::Mismatch { index }),
+        }
+    }
+
+    fn xfer(&mut self, txdata: &[u8], rxdata: &mut [u8]) -> Result<(), Self::Error> {
+        match self.next()? {
+            (_, TraceOp::Xfer { tx, rx })
+                if tx.as_slice() == txdata && rx.len() == rxdata.len() =>
+            {
+                rxdata.copy_from_slice(rx);
+                Ok(())
+            }
+            (index, _) => Err(ReplayError::Mismatch { index }),
+        }
+    }
+}
@@ -0,0 +1,293 @@
+//! Static checks of built command streams.
+use crate::{parse, MpsseCmd, MpsseCmdBuilder};
+use alloc::vec::Vec;
+use core::fmt;
+
+/// Possible mistake found by [`validate`](MpsseCmdBuilder::validate).
+///
+/// Offsets are the positions of the opcodes in the command stream.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum ValidationWarning {
+    /// Data clocking commands within the same chip select assertion use
+    /// different bit orders.
+    MixedBitOrder {
+        /// Offset of the earlier command.
+        previous: usize,
+        /// Offset of the offending command.
+        offset: usize,
+    },
+    /// Data clocking commands within the same chip select assertion drive or
+    /// sample data on different clock edges.
+    MixedEdges {
+        /// Offset of the earlier command.
+        previous: usize,
+        /// Offset of the offending command.
+        offset: usize,
+    },
+    /// A bit clocking command has data bits set that are not clocked out,
+    /// see [`clock_bits_out`](MpsseCmdBuilder::clock_bits_out).
+    UnclockedDataBits {
+        /// Offset of the offending command.
+        offset: usize,
+        /// Data bits set that are not clocked out.
+        bits: u8,
+    },
+    /// A bit clocking command encodes a length above the 8 bits it can
+    /// clock.
+    BitLengthTooLong {
+        /// Offset of the offending command.
+        offset: usize,
+        /// Encoded number of bits.
+        len: u16,
+    },
+}
+
+impl fmt::Display for ValidationWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationWarning::MixedBitOrder { previous, offset } => write!(
+                f,
+                "command at offset {} uses a different bit order than the command at offset {}",
+                offset, previous
+            ),
+            ValidationWarning::MixedEdges { previous, offset } => write!(
+                f,
+                "command at offset {} uses different clock edges than the command at offset {}",
+                offset, previous
+            ),
+            ValidationWarning::UnclockedDataBits { offset, bits } => write!(
+                f,
+                "command at offset {} has data bits {:#04x} set that are not clocked out",
+                offset, bits
+            ),
+            ValidationWarning::BitLengthTooLong { offset, len } => write!(
+                f,
+                "command at offset {} clocks {} bits, more than the 8 bits it can clock",
+                offset, len
+            ),
+        }
+    }
+}
+
+/// Error returned by
+/// [`check_command_buffer`](MpsseCmdBuilder::check_command_buffer).
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct CommandBufferError {
+    /// Offset of the first byte of the burst.
+    pub offset: usize,
+    /// Length of the burst in bytes.
+    pub len: usize,
+    /// Size of the command buffer in bytes.
+    pub buffer_size: usize,
+}
+
+impl fmt::Display for CommandBufferError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "burst of {} bytes at offset {} exceeds the {} byte command buffer",
+            self.len, self.offset, self.buffer_size
+        )
+    }
+}
+
+impl core::error::Error for CommandBufferError {}
+
+/// Data bits clocked out by a bit clocking command of 1 to 8 `len` bits.
+fn clocked_bits(lsb: bool, len: u8) -> u8 {
+    if lsb {
+        ((1u16 << len) - 1) as u8
+    } else {
+        (0xFF00u16 >> len) as u8
+    }
+}
+
+/// Clocking mode of the last data command in a chip select assertion.
+#[derive(Default)]
+struct Region {
+    /// Offset and LSB first flag of the last data command.
+    order: Option<(usize, bool)>,
+    /// Offset and negative edge flag of the last command writing data.
+    write_edge: Option<(usize, bool)>,
+    /// Offset and negative edge flag of the last command reading data.
+    read_edge: Option<(usize, bool)>,
+}
+
+impl MpsseCmdBuilder {
+    /// Check the command stream for likely mistakes.
+    ///
+    /// Tracks the chip select pins set with [`set_gpio_lower`] and, while any
+    /// of them is low, checks that consecutive data clocking commands use the
+    /// same bit order and the same clock edges to write and to read data. A
+    /// mismatch usually means a command was copied from code for another
+    /// device. TMS commands are ignored.
+    ///
+    /// Independently of the chip select, bit clocking commands writing data
+    /// bits that are not clocked out are reported, usually a data byte
+    /// aligned for the other bit order.
+    ///
+    /// # Arguments
+    ///
+    /// * `cs` - Lower byte GPIO mask of the active low chip select pins.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::{ClockDataIn, ClockDataOut, MpsseCmdBuilder, ValidationWarning};
+    ///
+    /// let cmd = MpsseCmdBuilder::new()
+    ///     .set_gpio_lower(0x00, 0x0B)
+    ///     .clock_data_out(ClockDataOut::MsbNeg, &[0x03])
+    ///     .clock_data_in(ClockDataIn::LsbPos, 1)
+    ///     .set_gpio_lower(0x08, 0x0B);
+    /// assert_eq!(
+    ///     cmd.validate(0x08),
+    ///     vec![ValidationWarning::MixedBitOrder {
+    ///         previous: 3,
+    ///         offset: 7
+    ///     }]
+    /// );
+    /// ```
+    ///
+    /// [`set_gpio_lower`]: MpsseCmdBuilder::set_gpio_lower
+    pub fn validate(&self, cs: u8) -> Vec<ValidationWarning> {
+        let set_lower: u8 = MpsseCmd::SetDataBitsLowbyte.into();
+        let mut warnings = Vec::new();
+        let mut asserted = false;
+        let mut region = Region::default();
+        for cmd in parse::commands(self.as_slice()).map_while(Result::ok) {
+            let opcode = cmd.bytes[0];
+            let offset = cmd.offset;
+            // bit mode, including TMS
+            if parse::is_clocking(opcode) && opcode & 0x02 != 0 && cmd.bytes[1] > 7 {
+                warnings.push(ValidationWarning::BitLengthTooLong {
+                    offset,
+                    len: u16::from(cmd.bytes[1]) + 1,
+                });
+            } else if parse::is_clocking(opcode) && opcode & 0x52 == 0x12 {
+                // write TDI/DO in bit mode
+                let bits = cmd.bytes[2] & !clocked_bits(opcode & 0x08 != 0, cmd.bytes[1] + 1);
+                if bits != 0 {
+                    warnings.push(ValidationWarning::UnclockedDataBits { offset, bits });
+                }
+            }
+            if opcode == set_lower {
+                let now_asserted = cmd.bytes[1] & cs != cs;
+                if now_asserted != asserted {
+                    region = Region::default();
+                }
+                asserted = now_asserted;
+                continue;
+            }
+            // only TDI/TDO data commands
+            if !asserted || !parse::is_clocking(opcode) || opcode & 0x40 != 0 {
+                continue;
+            }
+
+            let lsb = opcode & 0x08 != 0;
+            if let Some((previous, previous_lsb)) = region.order {
+                if previous_lsb != lsb {
+                    warnings.push(ValidationWarning::MixedBitOrder { previous, offset });
+                }
+            }
+            region.order = Some((offset, lsb));
+
+            let mut edges = Vec::with_capacity(2);
+            if opcode & 0x10 != 0 {
+                edges.push((&mut region.write_edge, opcode & 0x01 != 0));
+            }
+            if opcode & 0x20 != 0 {
+                edges.push((&mut region.read_edge, opcode & 0x04 != 0));
+            }
+            let mut mixed = None;
+            for (last, neg) in edges {
+                if let Some((previous, previous_neg)) = *last {
+                    if previous_neg != neg {
+                        mixed = Some(previous);
+                    }
+                }
+                *last = Some((offset, neg));
+            }
+            if let Some(previous) = mixed {
+                warnings.push(ValidationWarning::MixedEdges { previous, offset });
+            }
+        }
+        warnings
+    }
+
+    /// Offset and length of the bursts of the command stream.
+    ///
+    /// A burst ends with a [`send_immediate`](MpsseCmdBuilder::send_immediate)
+    /// command, or at the end of the stream.
+    fn bursts(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let send_immediate: u8 = MpsseCmd::SendImmediate.into();
+        let mut start = 0;
+        let mut ends: Vec<usize> = parse::commands(self.as_slice())
+            .filter_map(Result::ok)
+            .filter(|cmd| cmd.bytes[0] == send_immediate)
+            .map(|cmd| cmd.offset + 1)
+            .collect();
+        if ends.last() != Some(&self.0.len()) {
+            ends.push(self.0.len());
+        }
+        ends.into_iter().map(move |end| {
+            let burst = (start, end - start);
+            start = end;
+            burst
+        })
+    }
+
+    /// Length of the longest burst of commands in the stream.
+    ///
+    /// Bursts are the runs of commands ending with a
+    /// [`send_immediate`](MpsseCmdBuilder::send_immediate), where the host
+    /// usually waits for the device response, or at the end of the stream.
+    /// The device has to buffer a whole burst when it cannot process commands
+    /// as fast as they arrive, for example while waiting on GPIOL1.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::{ClockDataOut, MpsseCmdBuilder};
+    ///
+    /// let cmd = MpsseCmdBuilder::new()
+    ///     .gpio_lower()
+    ///     .send_immediate()
+    ///     .clock_data_out(ClockDataOut::MsbNeg, &[0; 16]);
+    /// assert_eq!(cmd.max_burst_len(), 19);
+    /// ```
+    pub fn max_burst_len(&self) -> usize {
+        self.bursts().map(|(_, len)| len).max().unwrap_or(0)
+    }
+
+    /// Check that every burst of commands fits in the device command buffer.
+    ///
+    /// The command buffer is a limit of the device, distinct from the USB
+    /// transfer size and from the read buffer, see
+    /// [`max_burst_len`](MpsseCmdBuilder::max_burst_len) for the definition
+    /// of a burst. Returns an error for the first burst longer than
+    /// `buffer_size`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::{ClockDataOut, MpsseCmdBuilder};
+    ///
+    /// let cmd = MpsseCmdBuilder::new()
+    ///     .clock_data_out(ClockDataOut::MsbNeg, &[0; 1024])
+    ///     .send_immediate();
+    /// assert!(cmd.check_command_buffer(1024).is_err());
+    /// assert!(cmd.check_command_buffer(1028).is_ok());
+    /// ```
+    pub fn check_command_buffer(&self, buffer_size: usize) -> Result<(), CommandBufferError> {
+        match self.bursts().find(|&(_, len)| len > buffer_size) {
+            Some((offset, len)) => Err(CommandBufferError {
+                offset,
+                len,
+                buffer_size,
+            }),
+            None => Ok(()),
+        }
+    }
+}
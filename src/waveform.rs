@@ -0,0 +1,196 @@
+//! GPIO waveform generation.
+//!
+//! Delays between GPIO changes are generated by toggling the MPSSE clock
+//! without transferring data, so timing is expressed in clock cycles of the
+//! frequency set with [`set_clock`](crate::MpsseCmdBuilder::set_clock).
+use crate::{pin_mask, MpsseCmdBuilder};
+use core::convert::TryFrom;
+use core::time::Duration;
+
+/// Number of clock cycles lasting at least `duration`.
+pub(crate) fn clocks_for(duration: Duration, clock_hz: u32) -> usize {
+    let cycles = (duration.as_nanos() * u128::from(clock_hz)).div_ceil(1_000_000_000);
+    usize::try_from(cycles).unwrap_or(usize::MAX)
+}
+
+impl MpsseCmdBuilder {
+    /// Generate a square wave on a lower byte GPIO pin.
+    ///
+    /// The pin is driven high then low `cycles` times, waiting
+    /// `half_period_clocks` clock cycles after each transition. All other
+    /// lower byte pins are driven low, or left as inputs.
+    ///
+    /// The resulting frequency is `clock_hz / (2 * half_period_clocks)`,
+    /// slightly lower in practice due to the time the MPSSE takes to process
+    /// each GPIO command.
+    ///
+    /// # Arguments
+    ///
+    /// * `pin` - Lower byte GPIO pin number, 0-7.
+    ///   This will panic for values greater than 7.
+    /// * `half_period_clocks` - Clock cycles between transitions.
+    /// * `cycles` - Number of periods to generate.
+    /// * `dir` - GPIO direction mask, `pin` is always set as an output.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::MpsseCmdBuilder;
+    ///
+    /// // 1 kHz on AD4 with a 1 MHz clock
+    /// let cmd = MpsseCmdBuilder::new()
+    ///     .set_clock(29, Some(false))
+    ///     .square_wave(4, 500, 1000, 0x10);
+    /// ```
+    pub fn square_wave(
+        mut self,
+        pin: u8,
+        half_period_clocks: usize,
+        cycles: usize,
+        dir: u8,
+    ) -> Self {
+        let mask = pin_mask(pin);
+        let dir = dir | mask;
+        for _ in 0..cycles {
+            self = self
+                .set_gpio_lower(mask, dir)
+                .dummy_clocks(half_period_clocks)
+                .set_gpio_lower(0x00, dir)
+                .dummy_clocks(half_period_clocks);
+        }
+        self
+    }
+
+    /// Generate a PWM signal on a lower byte GPIO pin.
+    ///
+    /// Each of the `cycles` periods drives the pin high for
+    /// `duty * period_clocks` clock cycles, rounded to the nearest cycle, and
+    /// low for the rest of the period. All other lower byte pins are driven
+    /// low, or left as inputs. A duty cycle of `0.0` or `1.0` holds the pin
+    /// low or high without glitches.
+    ///
+    /// The resulting frequency is `clock_hz / period_clocks` and the duty
+    /// cycle resolution is `1 / period_clocks`, slightly off in practice due
+    /// to the time the MPSSE takes to process each GPIO command. For example
+    /// a 50 Hz servo signal with a 1 MHz clock has a period of 20000 clocks,
+    /// a resolution of 1 µs.
+    ///
+    /// # Arguments
+    ///
+    /// * `pin` - Lower byte GPIO pin number, 0-7.
+    ///   This will panic for values greater than 7.
+    /// * `duty` - Fraction of the period the pin is high, `0.0` to `1.0`.
+    ///   This will panic for values outside of this range.
+    /// * `period_clocks` - Clock cycles of each period.
+    /// * `cycles` - Number of periods to generate.
+    /// * `dir` - GPIO direction mask, `pin` is always set as an output.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::MpsseCmdBuilder;
+    ///
+    /// // 1.5 ms servo pulse at 50 Hz on AD4 with a 1 MHz clock
+    /// let cmd = MpsseCmdBuilder::new()
+    ///     .set_clock(29, Some(false))
+    ///     .gpio_pwm(4, 0.075, 20_000, 50, 0x10);
+    /// ```
+    pub fn gpio_pwm(
+        mut self,
+        pin: u8,
+        duty: f32,
+        period_clocks: usize,
+        cycles: usize,
+        dir: u8,
+    ) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&duty),
+            "duty cycle must be between 0.0 and 1.0"
+        );
+        let mask = pin_mask(pin);
+        let dir = dir | mask;
+        // rounded without `f64::round`, which needs std
+        let high_clocks = (f64::from(duty) * period_clocks as f64 + 0.5) as usize;
+        let low_clocks = period_clocks.saturating_sub(high_clocks);
+        for _ in 0..cycles {
+            if high_clocks > 0 {
+                self = self.set_gpio_lower(mask, dir).dummy_clocks(high_clocks);
+            }
+            if low_clocks > 0 {
+                self = self.set_gpio_lower(0x00, dir).dummy_clocks(low_clocks);
+            }
+        }
+        self
+    }
+
+    /// Set the lower byte GPIO pins with setup and hold times.
+    ///
+    /// Waits at least `setup_ns` before and `hold_ns` after the
+    /// [`set_gpio_lower`] command, by toggling the clock without transferring
+    /// data. Times are rounded up to a whole number of clock cycles of
+    /// `clock_hz`, which must match the frequency set with [`set_clock`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::MpsseCmdBuilder;
+    ///
+    /// // 50 ns setup and 120 ns hold at 30 MHz: 2 and 4 clock cycles
+    /// let cmd = MpsseCmdBuilder::new().set_gpio_timed(0x10, 0x1B, 50, 120, 30_000_000);
+    /// assert_eq!(cmd.as_slice(), [0x8E, 1, 0x80, 0x10, 0x1B, 0x8E, 3]);
+    /// ```
+    ///
+    /// [`set_gpio_lower`]: MpsseCmdBuilder::set_gpio_lower
+    /// [`set_clock`]: MpsseCmdBuilder::set_clock
+    pub fn set_gpio_timed(
+        self,
+        state: u8,
+        direction: u8,
+        setup_ns: u32,
+        hold_ns: u32,
+        clock_hz: u32,
+    ) -> Self {
+        let setup = clocks_for(Duration::from_nanos(setup_ns.into()), clock_hz);
+        let hold = clocks_for(Duration::from_nanos(hold_ns.into()), clock_hz);
+        self.dummy_clocks(setup)
+            .set_gpio_lower(state, direction)
+            .dummy_clocks(hold)
+    }
+
+    /// Generate a timed waveform on the lower byte GPIO pins.
+    ///
+    /// Each step sets the pins to `state` and holds them for at least
+    /// `duration`, rounded up to a whole number of clock cycles of
+    /// `clock_hz`, which must match the frequency set with [`set_clock`].
+    /// The pins are left in the state of the last step.
+    ///
+    /// # Arguments
+    ///
+    /// * `steps` - Pin states and durations, in order.
+    /// * `dir` - GPIO direction mask, used for every step.
+    /// * `clock_hz` - Frequency of the MPSSE clock.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ftdi_mpsse::MpsseCmdBuilder;
+    /// use std::time::Duration;
+    ///
+    /// let us = Duration::from_micros(1);
+    /// let cmd = MpsseCmdBuilder::new().gpio_waveform(&[(0x10, us), (0x00, us * 2)], 0x10, 2_000_000);
+    /// assert_eq!(
+    ///     cmd.as_slice(),
+    ///     [0x80, 0x10, 0x10, 0x8E, 1, 0x80, 0x00, 0x10, 0x8E, 3]
+    /// );
+    /// ```
+    ///
+    /// [`set_clock`]: MpsseCmdBuilder::set_clock
+    pub fn gpio_waveform(mut self, steps: &[(u8, Duration)], dir: u8, clock_hz: u32) -> Self {
+        for &(state, duration) in steps {
+            self = self
+                .set_gpio_lower(state, dir)
+                .dummy_clocks(clocks_for(duration, clock_hz));
+        }
+        self
+    }
+}
@@ -0,0 +1,176 @@
+use ftdi_mpsse::*;
+
+#[test]
+fn gpio_reads() {
+    let cmd = MpsseCmdBuilder::new()
+        .gpio_lower()
+        .set_gpio_lower(0x00, 0xFF)
+        .clock_data(ClockData::MsbPosIn, &[0x12, 0x34])
+        .gpio_lower()
+        .clock_bits_in(ClockBitsIn::MsbPos, 3)
+        .gpio_upper()
+        .send_immediate();
+    assert_eq!(
+        cmd.gpio_reads(),
+        vec![
+            (GpioByte::Lower, 0),
+            (GpioByte::Lower, 3),
+            (GpioByte::Upper, 5)
+        ]
+    );
+    assert!(MpsseCmdBuilder::new()
+        .enable_loopback()
+        .gpio_reads()
+        .is_empty());
+}
+
+#[test]
+fn opcode_histogram() {
+    let cmd = MpsseCmdBuilder::new()
+        .enable_loopback()
+        .clock_data_out(ClockDataOut::MsbPos, &[0x87, 0x87])
+        .clock_bits_in(ClockBitsIn::MsbPos, 2)
+        .clock_data_out(ClockDataOut::MsbPos, &[0x81])
+        .send_immediate();
+    assert_eq!(
+        cmd.opcode_histogram(),
+        vec![
+            (ClockDataOut::MsbPos.into(), 2),
+            (ClockBitsIn::MsbPos.into(), 1),
+            (MpsseCmd::EnableLoopback.into(), 1),
+            (MpsseCmd::SendImmediate.into(), 1),
+        ]
+    );
+}
+
+#[test]
+fn is_read_only() {
+    assert!(MpsseCmdBuilder::new().is_read_only());
+    assert!(MpsseCmdBuilder::new()
+        .gpio_lower()
+        .wait_on_io_high()
+        .clock_data_in(ClockDataIn::MsbPos, 4)
+        .clock_bits_in(ClockBitsIn::LsbNeg, 3)
+        .gpio_upper()
+        .send_immediate()
+        .is_read_only());
+
+    let writes = [
+        MpsseCmdBuilder::new().set_gpio_lower(0x00, 0x00),
+        MpsseCmdBuilder::new().set_gpio_upper(0x00, 0x00),
+        MpsseCmdBuilder::new().clock_data_out(ClockDataOut::MsbNeg, &[0x00]),
+        MpsseCmdBuilder::new().clock_data(ClockData::MsbPosIn, &[0x00]),
+        MpsseCmdBuilder::new().clock_tms_out(ClockTMSOut::NegEdge, 0x00, false, 1),
+        MpsseCmdBuilder::new().enable_loopback(),
+        MpsseCmdBuilder::new().set_clock(29, None),
+        MpsseCmdBuilder::new().enable_3phase_data_clocking(),
+        MpsseCmdBuilder::new().enable_adaptive_data_clocking(),
+        MpsseCmdBuilder::with_vec(vec![0xAB]),
+        // truncated read
+        MpsseCmdBuilder::with_vec(vec![0x20, 0x00]),
+    ];
+    for cmd in writes.iter() {
+        assert!(!cmd.is_read_only(), "{:02X?}", cmd.as_slice());
+    }
+}
+
+#[test]
+fn worst_case_latency() {
+    let us = std::time::Duration::from_micros(1);
+    let cmd = MpsseCmdBuilder::new()
+        // 8 bits
+        .clock_data(ClockData::MsbPosIn, &[0x00])
+        // 3 bits
+        .clock_bits_out(ClockBitsOut::MsbNeg, 0x00, 3)
+        // 5 bits
+        .clock_tms_out(ClockTMSOut::NegEdge, 0x00, true, 5)
+        .send_immediate()
+        .gpio_lower()
+        .send_immediate();
+    assert_eq!(
+        cmd.worst_case_latency(1_000_000, 1000 * us, 250 * us),
+        2516 * us
+    );
+    assert_eq!(
+        MpsseCmdBuilder::new()
+            .set_gpio_lower(0x00, 0x0B)
+            .worst_case_latency(1_000_000, us, us),
+        std::time::Duration::ZERO
+    );
+    // clock only commands
+    let cmd = MpsseCmdBuilder::with_vec(vec![0x8E, 0x07, 0x8F, 0x01, 0x00, 0x9C, 0x00, 0x00, 0x88]);
+    assert_eq!(cmd.worst_case_latency(1_000_000, us, us), 32 * us);
+}
+
+#[test]
+fn estimate_duration() {
+    let cmd = MpsseCmdBuilder::new()
+        .set_gpio_lower(0x00, 0x0B)
+        // 16 bits
+        .clock_data_out(ClockDataOut::MsbNeg, &[0x00, 0x00])
+        // 8 bits
+        .clock_data_in(ClockDataIn::MsbPos, 1)
+        // 7 bits
+        .clock_bits(ClockBits::MsbPosIn, 0x00, 7)
+        // 2 bits
+        .clock_tms(ClockTMS::NegTMSPosTDO, 0x00, false, 2)
+        // 16 clocks
+        .clock_cycles_bytes(2)
+        .gpio_lower()
+        .send_immediate();
+    assert_eq!(cmd.estimate_clock_cycles(), 49);
+    assert_eq!(
+        cmd.estimate_duration(3_000_000),
+        std::time::Duration::from_nanos(16334)
+    );
+    assert_eq!(MpsseCmdBuilder::new().estimate_clock_cycles(), 0);
+    assert_eq!(
+        MpsseCmdBuilder::new().estimate_duration(1),
+        std::time::Duration::ZERO
+    );
+}
+
+#[test]
+fn is_idempotent() {
+    assert!(MpsseCmdBuilder::new().is_idempotent());
+    assert!(MpsseCmdBuilder::new()
+        .gpio_lower()
+        .set_gpio_upper(0x00, 0xFF)
+        .set_clock(5, Some(false))
+        .send_immediate()
+        .is_idempotent());
+
+    let dependent = [
+        MpsseCmdBuilder::new().clock_data_in(ClockDataIn::MsbPos, 1),
+        MpsseCmdBuilder::new().clock_tms_out(ClockTMSOut::NegEdge, 0x00, true, 5),
+        MpsseCmdBuilder::with_vec(vec![0x8E, 0x07]),
+        MpsseCmdBuilder::new().clock_until_io_low(),
+        MpsseCmdBuilder::new()
+            .set_gpio_upper(0x00, 0xFF)
+            .clock_data_out(ClockDataOut::MsbNeg, &[0x00]),
+        // truncated
+        MpsseCmdBuilder::with_vec(vec![0x80, 0x00]),
+    ];
+    for cmd in dependent.iter() {
+        assert!(!cmd.is_idempotent(), "{:02X?}", cmd.as_slice());
+    }
+}
+
+#[test]
+fn clock_changes() {
+    let cmd = MpsseCmdBuilder::new()
+        .set_clock(29, Some(false))
+        .clock_data_out(ClockDataOut::MsbNeg, &[0x86, 0x01, 0x02])
+        .set_clock_hz(400, 60_000_000);
+    assert_eq!(cmd.clock_changes(), vec![(1, 29), (11, 0x3A97)]);
+    assert!(cmd.changes_clock());
+
+    let divide_only = MpsseCmdBuilder::with_vec(vec![0x8B]);
+    assert!(divide_only.clock_changes().is_empty());
+    assert!(divide_only.changes_clock());
+
+    // truncated divisor
+    let truncated = MpsseCmdBuilder::with_vec(vec![0x86, 0x01]);
+    assert!(truncated.clock_changes().is_empty());
+    assert!(!truncated.changes_clock());
+}
@@ -0,0 +1,49 @@
+#![cfg(feature = "async")]
+use ftdi_mpsse::*;
+use std::collections::VecDeque;
+
+/// Asynchronous executor recording sent data and replaying canned responses.
+#[derive(Default)]
+struct AsyncScripted {
+    sent: Vec<Vec<u8>>,
+    responses: VecDeque<u8>,
+}
+
+impl AsyncMpsseCmdExecutor for AsyncScripted {
+    type Error = &'static str;
+
+    async fn init(&mut self, _settings: &MpsseSettings) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    async fn send(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        self.sent.push(data.to_vec());
+        Ok(())
+    }
+
+    async fn recv(&mut self, data: &mut [u8]) -> Result<(), Self::Error> {
+        for byte in data.iter_mut() {
+            *byte = self.responses.pop_front().ok_or("timeout")?;
+        }
+        Ok(())
+    }
+}
+
+async fn read_gpio<E: AsyncMpsseCmdExecutor>(mut exec: E) -> Result<u8, E::Error> {
+    exec.init(&MpsseSettings::default()).await?;
+    let cmd = MpsseCmdBuilder::new().gpio_lower().send_immediate();
+    let mut rx = [0; 1];
+    exec.xfer(cmd.as_slice(), &mut rx).await?;
+    Ok(rx[0])
+}
+
+#[test]
+fn async_xfer() {
+    let mut exec = AsyncScripted {
+        responses: vec![0x12].into(),
+        ..AsyncScripted::default()
+    };
+    assert_eq!(pollster::block_on(read_gpio(&mut exec)), Ok(0x12));
+    assert_eq!(pollster::block_on(read_gpio(&mut exec)), Err("timeout"));
+    assert_eq!(exec.sent, [vec![0x81, 0x87], vec![0x81, 0x87]]);
+}
@@ -0,0 +1,76 @@
+#![cfg(feature = "std")]
+mod common;
+
+use common::Scripted;
+use ftdi_mpsse::*;
+
+#[test]
+fn throughput_benchmark_chunks() {
+    let mut exec = Scripted::with_responses(&[0, 1, 2, 3, 0, 1, 2, 3, 0, 0xFF]);
+    let report = throughput_benchmark(&mut exec, 10, 4).unwrap();
+    assert_eq!(report.bytes, 10);
+    assert_eq!(report.chunk_latencies.len(), 3);
+    assert_eq!(report.mismatches, 1);
+    assert!(report.min_chunk_latency() <= report.mean_chunk_latency());
+    assert!(report.mean_chunk_latency() <= report.max_chunk_latency());
+    assert_eq!(
+        exec.sent,
+        [
+            vec![0x84],
+            vec![0x31, 0x03, 0x00, 0, 1, 2, 3, 0x87],
+            vec![0x31, 0x03, 0x00, 0, 1, 2, 3, 0x87],
+            vec![0x31, 0x01, 0x00, 0, 1, 0x87],
+            vec![0x85],
+        ]
+    );
+}
+
+#[test]
+fn throughput_benchmark_empty() {
+    let mut exec = Scripted::default();
+    let report = throughput_benchmark(&mut exec, 0, 512).unwrap();
+    assert!(report.chunk_latencies.is_empty());
+    assert_eq!(report.mean_chunk_latency(), None);
+    assert_eq!(report.min_chunk_latency(), None);
+}
+
+#[test]
+fn throughput_benchmark_error() {
+    let mut exec = Scripted::with_responses(&[0, 1]);
+    assert_eq!(throughput_benchmark(&mut exec, 8, 4), Err("timeout"));
+}
+
+#[test]
+#[should_panic(expected = "chunk length must be between 1 and u16::MAX + 1")]
+fn throughput_benchmark_zero_chunk() {
+    let _ = throughput_benchmark(&mut Scripted::default(), 8, 0);
+}
+
+#[test]
+fn measure_rtt_round_trips() {
+    let mut exec = Scripted::with_responses(&[0xA5; 3]);
+    let report = measure_rtt(&mut exec, 3).unwrap();
+    assert_eq!(report.round_trips.len(), 3);
+    assert!(report.min() <= report.mean());
+    assert!(report.mean() <= report.max());
+    assert_eq!(
+        report.jitter(),
+        Some(report.max().unwrap() - report.min().unwrap())
+    );
+    assert_eq!(exec.sent.len(), 5);
+    assert_eq!(exec.sent[0], [0x84]);
+    assert_eq!(exec.sent[1], [0x31, 0x00, 0x00, 0xA5, 0x87]);
+    assert_eq!(exec.sent[4], [0x85]);
+}
+
+#[test]
+fn measure_rtt_no_iterations() {
+    let report = measure_rtt(&mut Scripted::default(), 0).unwrap();
+    assert_eq!(report.mean(), None);
+    assert_eq!(report.jitter(), None);
+}
+
+#[test]
+fn measure_rtt_error() {
+    assert_eq!(measure_rtt(&mut Scripted::default(), 1), Err("timeout"));
+}
@@ -0,0 +1,515 @@
+use ftdi_mpsse::*;
+
+#[test]
+fn clock_data_out_bitswap() {
+    let data: Vec<u8> = (0..=255).collect();
+    let reversed: Vec<u8> = data.iter().map(|b| b.reverse_bits()).collect();
+    assert_eq!(
+        MpsseCmdBuilder::new()
+            .clock_data_out_bitswap(ClockDataOut::MsbPos, &data)
+            .as_slice(),
+        MpsseCmdBuilder::new()
+            .clock_data_out(ClockDataOut::MsbPos, &reversed)
+            .as_slice()
+    );
+    assert!(MpsseCmdBuilder::new()
+        .clock_data_out_bitswap(ClockDataOut::LsbNeg, &[])
+        .as_slice()
+        .is_empty());
+}
+
+#[test]
+fn reverse_bits_table() {
+    for byte in 0..=255u8 {
+        let mut reference = 0;
+        for bit in 0..8 {
+            if byte & (1 << bit) != 0 {
+                reference |= 0x80 >> bit;
+            }
+        }
+        assert_eq!(reverse_bits(byte), reference, "{:#04X}", byte);
+    }
+}
+
+#[test]
+#[should_panic(expected = "data length cannot exceed u16::MAX + 1")]
+fn clock_data_out_bitswap_too_long() {
+    let _ = MpsseCmdBuilder::new().clock_data_out_bitswap(ClockDataOut::MsbPos, &[0; 65537]);
+}
+
+#[test]
+fn clock_data_out_iter() {
+    let data: Vec<u8> = (0..=255).cycle().take(70000).collect();
+    for &len in [0, 1, 300, 65536].iter() {
+        assert_eq!(
+            MpsseCmdBuilder::new().clock_data_out_iter(
+                ClockDataOut::LsbPos,
+                len,
+                data[..len].iter().copied()
+            ),
+            MpsseCmdBuilder::new().clock_data_out(ClockDataOut::LsbPos, &data[..len])
+        );
+    }
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "iterator yielded less than len bytes")]
+fn clock_data_out_iter_short() {
+    let _ = MpsseCmdBuilder::new().clock_data_out_iter(ClockDataOut::MsbNeg, 3, vec![1, 2]);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "iterator yielded more than len bytes")]
+fn clock_data_out_iter_long() {
+    let _ = MpsseCmdBuilder::new().clock_data_out_iter(ClockDataOut::MsbNeg, 1, vec![1, 2]);
+}
+
+#[test]
+#[should_panic(expected = "data length cannot exceed u16::MAX + 1")]
+fn clock_data_out_iter_too_long() {
+    let _ = MpsseCmdBuilder::new().clock_data_out_iter(ClockDataOut::MsbNeg, 65537, 0..);
+}
+
+#[test]
+fn clock_tms_out_seq() {
+    // 0b1010_0110_0101_1100_0011, first bit in bit 0
+    let bits: Vec<bool> = (0..20).map(|i| 0xA65C3u32 >> i & 1 == 1).collect();
+    let cmd = MpsseCmdBuilder::new().clock_tms_out_seq(ClockTMSOut::PosEdge, &bits, true);
+    assert_eq!(
+        cmd.as_slice(),
+        [0x4A, 0x06, 0xC3, 0x4A, 0x06, 0xCB, 0x4A, 0x05, 0xA9]
+    );
+    assert_eq!(
+        decode(cmd.as_slice())
+            .unwrap()
+            .iter()
+            .map(|op| match op {
+                MpsseOp::ClockTmsOut { len, tdi: true, .. } => *len,
+                _ => panic!("unexpected command {:?}", op),
+            })
+            .collect::<Vec<_>>(),
+        [7, 7, 6]
+    );
+    assert!(MpsseCmdBuilder::new()
+        .clock_tms_out_seq(ClockTMSOut::PosEdge, &[], false)
+        .as_slice()
+        .is_empty());
+}
+
+#[test]
+fn to_init_commands() {
+    let mut settings = MpsseSettings::default();
+    let cmd = settings.to_init_commands();
+    assert_eq!(cmd.as_slice(), [0x84, 0xAB, 0x87, 0x85]);
+    assert_eq!(cmd.expected_loopback_response(), [0xFA, 0xAB]);
+
+    settings.clock_frequency = Some(30_000_000);
+    assert_eq!(
+        &settings.to_init_commands().as_slice()[4..],
+        [0x8A, 0x86, 0x00, 0x00]
+    );
+    settings.clock_frequency = Some(92);
+    assert_eq!(
+        &settings.to_init_commands().as_slice()[4..],
+        [0x8B, 0x86, 0xC0, 0xFE]
+    );
+}
+
+#[test]
+#[should_panic(expected = "clock frequency must be between 92 Hz and 30 MHz")]
+fn to_init_commands_bad_frequency() {
+    let settings = MpsseSettings {
+        clock_frequency: Some(0),
+        ..MpsseSettings::default()
+    };
+    settings.to_init_commands();
+}
+
+#[test]
+fn read_token_merge() {
+    let cmd = MpsseCmdBuilder::new();
+    let (cmd, ack) = cmd.swd_read_ack();
+    let cmd = cmd.gpio_lower();
+    let (cmd, marker) = cmd.sync_marker();
+    let (_, data) = cmd.swd_read_data();
+    let merged = ReadToken::merge(&[ack, ReadToken::new(1, 1), marker, data]).unwrap();
+    assert_eq!(merged.offset(), 0);
+    assert_eq!(merged.len(), 4 + data.len());
+
+    assert_eq!(ReadToken::merge(&[]), None);
+    assert_eq!(
+        ReadToken::merge(&[ReadToken::new(4, 0)]),
+        Some(ReadToken::new(4, 0))
+    );
+    assert_eq!(
+        ReadToken::merge(&[ReadToken::new(0, 2), ReadToken::new(3, 1)]),
+        None
+    );
+}
+
+#[test]
+fn read_len() {
+    assert_eq!(MpsseCmdBuilder::new().read_len(), 0);
+    let cmd = MpsseCmdBuilder::new()
+        // payload bytes that look like reads
+        .clock_data_out(ClockDataOut::MsbNeg, &[0x81, 0x83, 0x20, 0x00, 0x10])
+        .clock_bits_out(ClockBitsOut::MsbNeg, 0x81, 8)
+        .clock_data_in(ClockDataIn::LsbPos, 300)
+        .clock_bits(ClockBits::LsbPosIn, 0x00, 2)
+        .gpio_upper();
+    assert_eq!(cmd.read_len(), 302);
+    // unrecognized opcode echo
+    assert_eq!(MpsseCmdBuilder::with_vec(vec![0xAA]).read_len(), 2);
+    // truncated command
+    assert_eq!(
+        MpsseCmdBuilder::with_vec(vec![0x81, 0x20, 0x00]).read_len(),
+        1
+    );
+}
+
+#[test]
+fn clock_data_in_chunks() {
+    let cmd = MpsseCmdBuilder::new().clock_data_in(ClockDataIn::LsbNeg, 2 * 65536 + 1);
+    assert_eq!(
+        cmd.as_slice(),
+        [0x2C, 0xFF, 0xFF, 0x2C, 0xFF, 0xFF, 0x2C, 0x00, 0x00]
+    );
+    assert_eq!(cmd.read_len(), 2 * 65536 + 1);
+    assert_eq!(
+        MpsseCmdBuilder::new()
+            .clock_data_in(ClockDataIn::MsbPos, 65536)
+            .as_slice(),
+        [0x20, 0xFF, 0xFF]
+    );
+    assert!(MpsseCmdBuilder::new()
+        .clock_data_in(ClockDataIn::MsbPos, 0)
+        .as_slice()
+        .is_empty());
+}
+
+#[test]
+fn set_clock_hz() {
+    let clock = |target, base| MpsseCmdBuilder::new().set_clock_hz(target, base).0;
+    assert_eq!(clock(30_000_000, 60_000_000), [0x8A, 0x86, 0x00, 0x00]);
+    assert_eq!(clock(100_000_000, 60_000_000), [0x8A, 0x86, 0x00, 0x00]);
+    // 60 MHz / 14 = 4.29 MHz is nearer to 4.5 MHz than 60 MHz / 12 = 5 MHz
+    assert_eq!(clock(4_500_000, 60_000_000), [0x8A, 0x86, 0x06, 0x00]);
+    // lowest frequency without the divide by 5 is 30 MHz / 65536 = 457.8 Hz
+    assert_eq!(clock(458, 60_000_000), [0x8A, 0x86, 0xDD, 0xFF]);
+    assert_eq!(clock(400, 60_000_000), [0x8B, 0x86, 0x97, 0x3A]);
+    assert_eq!(clock(1, 60_000_000), [0x8B, 0x86, 0xFF, 0xFF]);
+    assert_eq!(clock(6_000_000, 12_000_000), [0x86, 0x00, 0x00]);
+}
+
+#[test]
+#[should_panic(expected = "clock frequency cannot be zero")]
+fn set_clock_hz_zero() {
+    let _ = MpsseCmdBuilder::new().set_clock_hz(0, 60_000_000);
+}
+
+#[test]
+fn drive_only_zero() {
+    let cmd = MpsseCmdBuilder::new()
+        .enable_drive_only_zero(0x03, 0x40)
+        .disable_drive_only_zero();
+    assert_eq!(cmd.as_slice(), [0x9E, 0x03, 0x40, 0x9E, 0x00, 0x00]);
+}
+
+#[test]
+fn clock_cycles() {
+    let cmd = MpsseCmdBuilder::new()
+        .clock_cycles_bits(8)
+        .clock_cycles_bits(0)
+        .clock_cycles_bytes(u16::MAX)
+        .clock_cycles_bytes(0);
+    assert_eq!(cmd.as_slice(), [0x8E, 0x07, 0x8F, 0xFE, 0xFF]);
+    assert_eq!(cmd.read_len(), 0);
+}
+
+#[test]
+#[should_panic(expected = "clock length cannot exceed 8")]
+fn clock_cycles_bits_too_long() {
+    let _ = MpsseCmdBuilder::new().clock_cycles_bits(9);
+}
+
+#[test]
+fn clock_until_io() {
+    let cmd = MpsseCmdBuilder::new()
+        .clock_until_io_high()
+        .clock_until_io_low()
+        .clock_n8_until_io_high(1)
+        .clock_n8_until_io_low(0x1234)
+        .clock_n8_until_io_low(0);
+    assert_eq!(
+        cmd.as_slice(),
+        [0x94, 0x95, 0x9C, 0x00, 0x00, 0x9D, 0x33, 0x12]
+    );
+    assert_eq!(cmd.read_len(), 0);
+}
+
+#[test]
+fn default_mode() {
+    let cmd = MpsseCmdBuilder::new()
+        .with_default_mode(
+            ClockDataOut::LsbNeg,
+            ClockDataIn::LsbPos,
+            ClockData::LsbPosIn,
+        )
+        .clock_data_default(&[0x01])
+        .with_default_mode(
+            ClockDataOut::MsbNeg,
+            ClockDataIn::MsbPos,
+            ClockData::MsbPosIn,
+        )
+        .clock_data_default(&[0x02])
+        .clock_data_out_default(&[0x03])
+        .clock_data_in_default(1);
+    assert_eq!(
+        cmd.inner().as_slice(),
+        MpsseCmdBuilder::new()
+            .clock_data(ClockData::LsbPosIn, &[0x01])
+            .clock_data(ClockData::MsbPosIn, &[0x02])
+            .clock_data_out(ClockDataOut::MsbNeg, &[0x03])
+            .clock_data_in(ClockDataIn::MsbPos, 1)
+            .as_slice()
+    );
+}
+
+#[test]
+fn default_mode_map() {
+    let cmd = MpsseCmdBuilder::new()
+        .with_default_mode(
+            ClockDataOut::MsbNeg,
+            ClockDataIn::MsbPos,
+            ClockData::MsbPosIn,
+        )
+        .map(|cmd| cmd.set_gpio_lower(0x00, 0x0B))
+        .clock_data_out_default(&[0x06])
+        .map(|cmd| cmd.set_gpio_lower(0x08, 0x0B));
+    assert_eq!(
+        MpsseCmdBuilder::from(cmd),
+        MpsseCmdBuilder::new()
+            .set_gpio_lower(0x00, 0x0B)
+            .clock_data_out(ClockDataOut::MsbNeg, &[0x06])
+            .set_gpio_lower(0x08, 0x0B)
+    );
+}
+
+#[test]
+fn patch() {
+    let mut cmd = MpsseCmdBuilder::new().set_gpio_lower(0x00, 0x0B);
+    cmd.patch(1, &[0x08]).unwrap();
+    cmd.patch(3, &[]).unwrap();
+    assert_eq!(cmd.as_slice(), [0x80, 0x08, 0x0B]);
+    let err = cmd.patch(2, &[0x00, 0x00]).unwrap_err();
+    assert_eq!(
+        err,
+        PatchError {
+            offset: 2,
+            len: 2,
+            stream_len: 3
+        }
+    );
+    assert!(cmd.patch(usize::MAX, &[0x00]).is_err());
+    assert_eq!(cmd.as_slice(), [0x80, 0x08, 0x0B]);
+}
+
+#[test]
+fn try_length_checks() {
+    let err = MpsseCmdBuilder::new()
+        .gpio_lower()
+        .try_clock_data_out(ClockDataOut::MsbNeg, &[0; 65537])
+        .err()
+        .unwrap();
+    assert_eq!(
+        err.error(),
+        MpsseBuildError::DataTooLong {
+            got: 65537,
+            max: 65536
+        }
+    );
+    assert_eq!(err.to_string(), "length 65537 exceeds the maximum of 65536");
+    // the builder is returned unchanged
+    assert_eq!(err.inner().as_slice(), [0x81]);
+    assert_eq!(err.into_inner(), MpsseCmdBuilder::new().gpio_lower());
+    assert!(MpsseCmdBuilder::new()
+        .try_clock_data(ClockData::MsbPosIn, &[0; 65537])
+        .is_err());
+    assert!(MpsseCmdBuilder::new()
+        .try_clock_data_out_bitswap(ClockDataOut::MsbNeg, &[0; 65537])
+        .is_err());
+    assert_eq!(
+        MpsseCmdBuilder::new()
+            .try_clock_bits(ClockBits::MsbPosIn, 0x00, 9)
+            .map_err(|err| err.error())
+            .err(),
+        Some(MpsseBuildError::DataTooLong { got: 9, max: 8 })
+    );
+    assert!(MpsseCmdBuilder::new()
+        .try_clock_bits_out(ClockBitsOut::MsbNeg, 0x00, 9)
+        .is_err());
+    assert!(MpsseCmdBuilder::new()
+        .try_clock_bits_in(ClockBitsIn::MsbPos, 9)
+        .is_err());
+    assert!(MpsseCmdBuilder::new().try_clock_cycles_bits(9).is_err());
+    assert_eq!(
+        MpsseCmdBuilder::new()
+            .try_clock_tms_out(ClockTMSOut::NegEdge, 0x00, false, 8)
+            .map_err(|err| err.error())
+            .err(),
+        Some(MpsseBuildError::DataTooLong { got: 8, max: 7 })
+    );
+    assert!(MpsseCmdBuilder::new()
+        .try_clock_tms(ClockTMS::NegTMSPosTDO, 0x00, false, 8)
+        .is_err());
+
+    let cmd = MpsseCmdBuilder::new()
+        .try_clock_data_out(ClockDataOut::MsbNeg, &[0x06])
+        .and_then(|cmd| cmd.try_clock_bits_out(ClockBitsOut::MsbNeg, 0x80, 1))
+        .and_then(|cmd| cmd.try_clock_tms_out(ClockTMSOut::NegEdge, 0x01, false, 1))
+        .unwrap();
+    assert_eq!(
+        cmd.as_slice(),
+        [0x11, 0x00, 0x00, 0x06, 0x13, 0x00, 0x80, 0x4B, 0x00, 0x01]
+    );
+}
+
+#[test]
+fn append() {
+    let init = MpsseCmdBuilder::new().set_gpio_lower(0x08, 0x0B);
+    let write = MpsseCmdBuilder::new().clock_data_out(ClockDataOut::MsbNeg, &[0x06]);
+    let cmd = init
+        .append(write)
+        .append_slice(&[0x87])
+        .clock_data_out(ClockDataOut::MsbNeg, &[0x04]);
+    assert_eq!(
+        cmd.as_slice(),
+        [0x80, 0x08, 0x0B, 0x11, 0x00, 0x00, 0x06, 0x87, 0x11, 0x00, 0x00, 0x04]
+    );
+
+    let cmd = MpsseCmdBuilder::new().append(MpsseCmdBuilder::with_vec(vec![0x87]));
+    assert_eq!(cmd.as_slice(), [0x87]);
+
+    let cmd = MpsseCmdBuilder::new()
+        .disable_loopback()
+        .append(MpsseCmdBuilder::new().enable_loopback());
+    assert_eq!(cmd.as_slice(), [0x85, 0x84]);
+
+    // the reserved capacity of an empty builder is kept
+    let cmd = MpsseCmdBuilder::with_capacity(4096).append(MpsseCmdBuilder::new().gpio_lower());
+    assert_eq!(cmd.as_slice(), [0x81]);
+    assert!(cmd.0.capacity() >= 4096);
+}
+
+#[test]
+fn with_capacity() {
+    let cmd = MpsseCmdBuilder::with_capacity(100);
+    assert!(cmd.0.capacity() >= 100);
+    assert_eq!(cmd, MpsseCmdBuilder::new());
+
+    // the reserved room is not lost when data is clocked
+    let data = [0xA5; 64];
+    let cmd = cmd
+        .clock_data_out(ClockDataOut::MsbNeg, &data)
+        .clock_data(ClockData::MsbPosIn, &data[..4]);
+    assert!(cmd.0.capacity() >= 100);
+    assert_eq!(cmd.as_slice().len(), 3 + 64 + 3 + 4);
+    assert_eq!(
+        MpsseCmdBuilder::new()
+            .set_clock(0x1234, Some(true))
+            .as_slice(),
+        [0x8B, 0x86, 0x34, 0x12]
+    );
+}
+
+#[test]
+fn builder_traits() {
+    assert_eq!(MpsseCmdBuilder::default(), MpsseCmdBuilder::new());
+
+    let cmd = MpsseCmdBuilder::new().gpio_lower();
+    let copy = cmd.clone().send_immediate();
+    assert_eq!(cmd.as_slice(), [0x81]);
+    assert_eq!(copy.as_slice(), [0x81, 0x87]);
+    assert_ne!(cmd, copy);
+    assert_eq!(cmd, cmd.clone());
+
+    assert_eq!(format!("{:?}", copy), "MpsseCmdBuilder { bytes: [81 87] }");
+
+    // wrappers show the state they compare
+    let modes = cmd.with_default_mode(
+        ClockDataOut::MsbNeg,
+        ClockDataIn::MsbPos,
+        ClockData::MsbPosIn,
+    );
+    assert_eq!(
+        format!("{:?}", modes),
+        "DefaultModeBuilder { cmd: MpsseCmdBuilder { bytes: [81] }, out: MsbNeg, in_: MsbPos, both: MsbPosIn }"
+    );
+    assert_ne!(
+        modes,
+        modes.clone().with_default_mode(
+            ClockDataOut::LsbNeg,
+            ClockDataIn::MsbPos,
+            ClockData::MsbPosIn
+        )
+    );
+}
+
+#[test]
+fn builder_conversions() {
+    fn len(bytes: impl AsRef<[u8]>) -> usize {
+        bytes.as_ref().len()
+    }
+
+    let cmd = MpsseCmdBuilder::new()
+        .set_gpio_lower(0x08, 0x0B)
+        .send_immediate();
+    assert_eq!(len(&cmd), 4);
+    assert_eq!(cmd[3], 0x87);
+    assert_eq!(&cmd[1..3], [0x08, 0x0B]);
+    assert_eq!(cmd.iter().filter(|&&byte| byte == 0x08).count(), 1);
+
+    let bytes: Vec<u8> = cmd.clone().into_iter().rev().collect();
+    assert_eq!(bytes, [0x87, 0x0B, 0x08, 0x80]);
+    assert_eq!(Vec::from(cmd), [0x80, 0x08, 0x0B, 0x87]);
+}
+
+#[test]
+fn raw_escape_hatches() {
+    let cmd = MpsseCmdBuilder::with_capacity(128);
+    assert!(cmd.as_slice().is_empty());
+    assert!(cmd.0.capacity() >= 128);
+
+    let cmd = cmd.gpio_lower().append_slice(&[0x9E, 0x00, 0x00]);
+    assert_eq!(cmd.as_slice(), [0x81, 0x9E, 0x00, 0x00]);
+}
+
+#[test]
+fn raw_cmd() {
+    let cmd = MpsseCmdBuilder::new()
+        .raw_cmd(MpsseCmd::GetDataBitsLowbyte, &[])
+        .raw_cmd(MpsseCmd::SetClockFrequency, &[0x05, 0x00]);
+    assert_eq!(cmd, MpsseCmdBuilder::new().gpio_lower().set_clock(5, None));
+    assert_eq!(cmd.read_len(), 1);
+}
+
+#[test]
+fn mpsse_cmd_try_from() {
+    use std::convert::TryFrom;
+
+    let mut decoded = 0;
+    for opcode in 0..=255u8 {
+        match MpsseCmd::try_from(opcode) {
+            Ok(cmd) => {
+                assert_eq!(u8::from(cmd), opcode);
+                decoded += 1;
+            }
+            Err(err) => assert_eq!(err, opcode),
+        }
+    }
+    // update when adding MpsseCmd variants
+    assert_eq!(decoded, 23);
+    assert_eq!(MpsseCmd::try_from(0x31), Err(0x31));
+}
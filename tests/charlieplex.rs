@@ -0,0 +1,59 @@
+use ftdi_mpsse::*;
+
+#[test]
+fn patterns() {
+    let leds = Charlieplex::new(&[0, 1, 2]);
+    assert_eq!(leds.led_count(), 6);
+    let patterns: Vec<(u8, u8)> = (0..leds.led_count()).map(|led| leds.pattern(led)).collect();
+    assert_eq!(
+        patterns,
+        [
+            (0x01, 0x03),
+            (0x01, 0x05),
+            (0x02, 0x03),
+            (0x02, 0x06),
+            (0x04, 0x05),
+            (0x04, 0x06),
+        ]
+    );
+}
+
+#[test]
+fn patterns_unique() {
+    let leds = Charlieplex::new(&[7, 0, 3, 5, 1]);
+    assert_eq!(leds.led_count(), 20);
+    let mut patterns: Vec<(u8, u8)> = (0..leds.led_count()).map(|led| leds.pattern(led)).collect();
+    for (state, direction) in patterns.iter() {
+        // one pin high, one pin low, the rest tristated
+        assert_eq!(state.count_ones(), 1);
+        assert_eq!(direction.count_ones(), 2);
+        assert_eq!(state & direction, *state);
+        assert_eq!(direction & !0xAB, 0);
+    }
+    patterns.sort_unstable();
+    patterns.dedup();
+    assert_eq!(patterns.len(), 20);
+}
+
+#[test]
+fn light() {
+    let leds = Charlieplex::new(&[4, 5]);
+    let cmd = leds.light(MpsseCmdBuilder::new(), 1, 16);
+    let cmd = leds.off(cmd);
+    assert_eq!(
+        cmd.as_slice(),
+        [0x80, 0x20, 0x30, 0x8F, 0x01, 0x00, 0x80, 0x00, 0x00]
+    );
+}
+
+#[test]
+#[should_panic(expected = "LED index out of range")]
+fn led_out_of_range() {
+    Charlieplex::new(&[0, 1]).pattern(2);
+}
+
+#[test]
+#[should_panic(expected = "charlieplex pins must be unique")]
+fn duplicate_pins() {
+    Charlieplex::new(&[0, 1, 0]);
+}
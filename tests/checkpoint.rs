@@ -0,0 +1,34 @@
+use ftdi_mpsse::*;
+
+#[test]
+fn rollback() {
+    let mut cmd = MpsseCmdBuilder::new().set_gpio_lower(0x08, 0x0B);
+    let checkpoint = cmd.checkpoint();
+    assert_eq!(checkpoint.stream_len(), 3);
+
+    cmd = cmd.clock_data_out(ClockDataOut::MsbNeg, &[0x06]);
+    cmd.rollback(checkpoint).unwrap();
+    assert_eq!(cmd.as_slice(), [0x80, 0x08, 0x0B]);
+    // a checkpoint can be rolled back to again
+    cmd.rollback(checkpoint).unwrap();
+    assert_eq!(cmd.as_slice(), [0x80, 0x08, 0x0B]);
+}
+
+#[test]
+fn rollback_stale_checkpoint() {
+    let mut cmd = MpsseCmdBuilder::new();
+    let start = cmd.checkpoint();
+    cmd = cmd.gpio_lower().gpio_upper();
+    let late = cmd.checkpoint();
+    cmd.rollback(start).unwrap();
+    assert_eq!(
+        cmd.rollback(late),
+        Err(RollbackError {
+            checkpoint_len: 2,
+            stream_len: 0
+        })
+    );
+
+    let other = MpsseCmdBuilder::new().send_immediate().checkpoint();
+    assert!(MpsseCmdBuilder::new().rollback(other).is_err());
+}
@@ -0,0 +1,65 @@
+use ftdi_mpsse::{
+    optimal_chunk_size, ChunkedExecutor, ClockDataOut, MpsseCmdBuilder, MpsseCmdExecutor,
+    MpsseSettings,
+};
+use std::time::Duration;
+
+mod common;
+
+use common::Scripted;
+
+#[test]
+fn optimal_chunk_size_latency() {
+    let ms = Duration::from_millis(1);
+    assert_eq!(optimal_chunk_size(1 << 20, 65536, ms, 8_000_000), 1000);
+    assert_eq!(optimal_chunk_size(1 << 20, 65536, ms * 4, 8_000_000), 4000);
+    assert_eq!(optimal_chunk_size(1 << 20, 65536, ms, 30_000_000), 3750);
+}
+
+#[test]
+fn optimal_chunk_size_limits() {
+    let s = Duration::from_secs(1);
+    // USB transfer size
+    assert_eq!(optimal_chunk_size(1 << 20, 4096, s, 30_000_000), 4096);
+    // clocking command length
+    assert_eq!(optimal_chunk_size(1 << 20, 1 << 20, s, 30_000_000), 65536);
+    // transfer length
+    assert_eq!(optimal_chunk_size(100, 4096, s, 30_000_000), 100);
+    // at least one byte
+    assert_eq!(optimal_chunk_size(100, 4096, Duration::ZERO, 30_000_000), 1);
+    assert_eq!(optimal_chunk_size(100, 0, s, 30_000_000), 1);
+    assert_eq!(optimal_chunk_size(0, 4096, s, 30_000_000), 0);
+}
+
+#[test]
+fn chunked_executor() {
+    let mut exec = ChunkedExecutor::new(Scripted::with_responses(&[0; 10]));
+    assert_eq!(exec.chunk_size(), 4096);
+    exec.init(&MpsseSettings {
+        in_transfer_size: 4,
+        ..MpsseSettings::default()
+    })
+    .unwrap();
+    assert_eq!(exec.chunk_size(), 4);
+
+    // commands are never split
+    let cmd = MpsseCmdBuilder::new()
+        .set_gpio_lower(0x00, 0x0B)
+        .clock_data_out(ClockDataOut::MsbNeg, &[1, 2])
+        .send_immediate()
+        .disable_loopback();
+    exec.send(cmd.as_slice()).unwrap();
+    assert_eq!(
+        exec.inner().sent,
+        [
+            vec![0x80, 0x00, 0x0B],
+            vec![0x11, 0x01, 0x00, 1, 2],
+            vec![0x87, 0x85]
+        ]
+    );
+
+    let mut data = [0xFF; 10];
+    exec.recv(&mut data).unwrap();
+    assert_eq!(data, [0; 10]);
+    assert!(exec.inner().responses.is_empty());
+}
@@ -0,0 +1,52 @@
+use ftdi_mpsse::{MpsseCmdExecutor, MpsseSettings};
+use std::collections::VecDeque;
+
+/// Executor recording sent data and replaying canned responses.
+#[derive(Default)]
+pub struct Scripted {
+    pub sent: Vec<Vec<u8>>,
+    pub responses: VecDeque<u8>,
+    /// Number of the following receives failing with a timeout.
+    pub failures: usize,
+    /// Number of purges.
+    pub purges: usize,
+}
+
+impl Scripted {
+    pub fn with_responses(responses: &[u8]) -> Scripted {
+        Scripted {
+            sent: Vec::new(),
+            responses: responses.iter().copied().collect(),
+            ..Scripted::default()
+        }
+    }
+}
+
+impl MpsseCmdExecutor for Scripted {
+    type Error = &'static str;
+
+    fn init(&mut self, _settings: &MpsseSettings) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn send(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        self.sent.push(data.to_vec());
+        Ok(())
+    }
+
+    fn recv(&mut self, data: &mut [u8]) -> Result<(), Self::Error> {
+        if self.failures > 0 {
+            self.failures -= 1;
+            return Err("timeout");
+        }
+        for byte in data.iter_mut() {
+            *byte = self.responses.pop_front().ok_or("timeout")?;
+        }
+        Ok(())
+    }
+
+    fn purge(&mut self) -> Result<(), Self::Error> {
+        self.purges += 1;
+        Ok(())
+    }
+}
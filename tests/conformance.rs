@@ -0,0 +1,37 @@
+mod common;
+
+use common::Scripted;
+use ftdi_mpsse::conformance::{test_suite, CheckResult};
+
+#[test]
+fn conformance_passed() {
+    let mut exec = Scripted::with_responses(&[0x00, 0xFF, 0xA5, 0x3C, 0xA5, 0xFA, 0xAB]);
+    let report = test_suite(&mut exec);
+    assert!(report.passed(), "{:?}", report);
+    let names: Vec<_> = report.checks.iter().map(|(name, _)| *name).collect();
+    assert_eq!(names, ["init", "loopback", "gpio", "bad_command"]);
+    assert_eq!(
+        exec.sent,
+        vec![
+            vec![0x84, 0x31, 0x03, 0x00, 0x00, 0xFF, 0xA5, 0x3C, 0x85, 0x87],
+            vec![0x80, 0xA5, 0xFF, 0x81, 0x80, 0x00, 0x00, 0x87],
+            vec![0xAB, 0x87],
+        ]
+    );
+}
+
+#[test]
+fn conformance_failed() {
+    let mut exec = Scripted::with_responses(&[0x00, 0xFF, 0xA5, 0x3C, 0x25, 0xFA]);
+    let report = test_suite(&mut exec);
+    assert!(!report.passed());
+    assert!(report.checks[1].1.is_passed());
+    assert_eq!(
+        report.checks[2].1,
+        CheckResult::Failed {
+            expected: vec![0xA5],
+            actual: vec![0x25]
+        }
+    );
+    assert_eq!(report.checks[3].1, CheckResult::Error("timeout"));
+}
@@ -0,0 +1,80 @@
+use ftdi_mpsse::*;
+
+mod common;
+use common::Scripted;
+
+#[test]
+fn checksums() {
+    assert_eq!(CrcKind::Crc8.checksum(&[]), 0x00);
+    assert_eq!(CrcKind::Crc8.checksum(b"123456789"), 0xF4);
+    assert_eq!(CrcKind::Crc16Ccitt.checksum(&[]), 0xFFFF);
+    assert_eq!(CrcKind::Crc16Ccitt.checksum(b"123456789"), 0x29B1);
+    assert_eq!(CrcKind::Crc8.byte_len(), 1);
+    assert_eq!(CrcKind::Crc16Ccitt.byte_len(), 2);
+}
+
+#[test]
+fn checked_transaction_retries() {
+    let good = CrcKind::Crc16Ccitt.checksum(&[0x12, 0x34]).to_be_bytes();
+    // first response corrupted
+    let mut exec =
+        Scripted::with_responses(&[0x12, 0x35, good[0], good[1], 0x12, 0x34, good[0], good[1]]);
+    let device = SpiDevice::new(3, SpiMode::Mode0);
+    let response =
+        spi_checked_transaction(&mut exec, &device, &[0xAB, 0x00], CrcKind::Crc16Ccitt, 1).unwrap();
+    assert_eq!(response, [0x12, 0x34]);
+    assert_eq!(exec.sent.len(), 2);
+
+    let request_crc = CrcKind::Crc16Ccitt.checksum(&[0xAB, 0x00]).to_be_bytes();
+    let expected = MpsseCmdBuilder::new()
+        .set_gpio_lower(0x00, 0x0B)
+        .clock_data(
+            ClockData::MsbPosIn,
+            &[0xAB, 0x00, request_crc[0], request_crc[1]],
+        )
+        .set_gpio_lower(0x08, 0x0B)
+        .send_immediate();
+    assert_eq!(exec.sent[0], expected.as_slice());
+}
+
+#[test]
+fn checked_transaction_idle_clock_high() {
+    let crc = CrcKind::Crc8.checksum(&[0x42]) as u8;
+    let mut exec = Scripted::with_responses(&[0x42, crc]);
+    let device = SpiDevice::new(4, SpiMode::Mode3);
+    let response = spi_checked_transaction(&mut exec, &device, &[0x00], CrcKind::Crc8, 0).unwrap();
+    assert_eq!(response, [0x42]);
+    let request_crc = CrcKind::Crc8.checksum(&[0x00]) as u8;
+    let expected = MpsseCmdBuilder::new()
+        .set_gpio_lower(0x01, 0x13)
+        .clock_data(ClockData::MsbPosIn, &[0x00, request_crc])
+        .set_gpio_lower(0x11, 0x13)
+        .send_immediate();
+    assert_eq!(exec.sent, [expected.as_slice()]);
+}
+
+#[test]
+fn checked_transaction_crc_mismatch() {
+    let mut exec = Scripted::with_responses(&[0xFF; 6]);
+    let result = spi_checked_transaction(
+        &mut exec,
+        &SpiDevice::new(4, SpiMode::Mode0),
+        &[0x01],
+        CrcKind::Crc8,
+        2,
+    );
+    assert_eq!(result, Err(RetryError::CrcMismatch { attempts: 3 }));
+}
+
+#[test]
+fn checked_transaction_executor_error() {
+    let mut exec = Scripted::default();
+    let result = spi_checked_transaction(
+        &mut exec,
+        &SpiDevice::new(4, SpiMode::Mode0),
+        &[0x01],
+        CrcKind::Crc8,
+        2,
+    );
+    assert_eq!(result, Err(RetryError::Executor("timeout")));
+}
@@ -0,0 +1,203 @@
+use ftdi_mpsse::*;
+
+#[test]
+fn decode_builder_commands() {
+    let cmd = MpsseCmdBuilder::new()
+        .set_gpio_lower(0x08, 0x0B)
+        .set_gpio_upper(0x01, 0x03)
+        .gpio_lower()
+        .gpio_upper()
+        .enable_loopback()
+        .disable_loopback()
+        .set_clock(0x1234, Some(true))
+        .clock_data_out(ClockDataOut::LsbNeg, &[0x01, 0x02])
+        .clock_data_in(ClockDataIn::MsbPos, 300)
+        .clock_data(ClockData::MsbNegIn, &[0x03])
+        .clock_bits_out(ClockBitsOut::MsbNeg, 0xC0, 2)
+        .clock_bits_in(ClockBitsIn::LsbPos, 8)
+        .clock_bits(ClockBits::LsbNegIn, 0x05, 3)
+        .clock_tms_out(ClockTMSOut::NegEdge, 0x03, true, 3)
+        .clock_tms(ClockTMS::PosTMSNegTDO, 0x01, false, 1)
+        .wait_on_io_high()
+        .wait_on_io_low()
+        .enable_3phase_data_clocking()
+        .disable_3phase_data_clocking()
+        .clock_cycles_bits(5)
+        .clock_cycles_bytes(512)
+        .clock_until_io_high()
+        .clock_until_io_low()
+        .clock_n8_until_io_high(2)
+        .clock_n8_until_io_low(3)
+        .enable_drive_only_zero(0x06, 0x00)
+        .send_immediate();
+    assert_eq!(
+        decode(cmd.as_slice()),
+        Ok(vec![
+            MpsseOp::SetGpioLower {
+                state: 0x08,
+                direction: 0x0B
+            },
+            MpsseOp::SetGpioUpper {
+                state: 0x01,
+                direction: 0x03
+            },
+            MpsseOp::GpioLower,
+            MpsseOp::GpioUpper,
+            MpsseOp::EnableLoopback,
+            MpsseOp::DisableLoopback,
+            MpsseOp::EnableClockDivide,
+            MpsseOp::SetClock { divisor: 0x1234 },
+            MpsseOp::ClockDataOut {
+                mode: ClockDataOut::LsbNeg,
+                data: 17..19,
+            },
+            MpsseOp::ClockDataIn {
+                mode: ClockDataIn::MsbPos,
+                len: 300,
+            },
+            MpsseOp::ClockData {
+                mode: ClockData::MsbNegIn,
+                data: 25..26,
+            },
+            MpsseOp::ClockBitsOut {
+                mode: ClockBitsOut::MsbNeg,
+                data: 0xC0,
+                len: 2,
+            },
+            MpsseOp::ClockBitsIn {
+                mode: ClockBitsIn::LsbPos,
+                len: 8,
+            },
+            MpsseOp::ClockBits {
+                mode: ClockBits::LsbNegIn,
+                data: 0x05,
+                len: 3,
+            },
+            MpsseOp::ClockTmsOut {
+                mode: ClockTMSOut::NegEdge,
+                data: 0x03,
+                tdi: true,
+                len: 3,
+            },
+            MpsseOp::ClockTms {
+                mode: ClockTMS::PosTMSNegTDO,
+                data: 0x01,
+                tdi: false,
+                len: 1,
+            },
+            MpsseOp::WaitOnIOHigh,
+            MpsseOp::WaitOnIOLow,
+            MpsseOp::Enable3PhaseClocking,
+            MpsseOp::Disable3PhaseClocking,
+            MpsseOp::ClockCyclesBits { len: 5 },
+            MpsseOp::ClockCyclesBytes { len: 512 },
+            MpsseOp::ClockUntilIOHigh,
+            MpsseOp::ClockUntilIOLow,
+            MpsseOp::ClockN8UntilIOHigh { len: 2 },
+            MpsseOp::ClockN8UntilIOLow { len: 3 },
+            MpsseOp::EnableDriveOnlyZero {
+                lower_mask: 0x06,
+                upper_mask: 0x00,
+            },
+            MpsseOp::SendImmediate,
+        ])
+    );
+}
+
+#[test]
+fn decode_bad_command_and_adaptive() {
+    assert_eq!(
+        decode(&[0xAA, 0x96, 0x97, 0x8A]),
+        Ok(vec![
+            MpsseOp::BadCommand { opcode: 0xAA },
+            MpsseOp::EnableAdaptiveClocking,
+            MpsseOp::DisableAdaptiveClocking,
+            MpsseOp::DisableClockDivide,
+        ])
+    );
+    assert_eq!(decode(&[]), Ok(vec![]));
+}
+
+#[test]
+fn decode_errors() {
+    assert_eq!(
+        decode(&[0x87, 0x10, 0x01, 0x00, 0x12]),
+        Err(DecodeError::Truncated { offset: 1 })
+    );
+    assert_eq!(
+        decode(&[0x87, 0x80, 0x00]),
+        Err(DecodeError::Truncated { offset: 1 })
+    );
+    let err = decode(&[0x87, 0x30, 0x00, 0x00, 0x12]).unwrap_err();
+    assert_eq!(
+        err,
+        DecodeError::UnsupportedClocking {
+            offset: 1,
+            opcode: 0x30
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "unsupported data clocking opcode 0x30 at offset 1"
+    );
+}
+
+#[test]
+fn display() {
+    assert_eq!(MpsseCmdBuilder::new().to_string(), "");
+
+    let cmd = MpsseCmdBuilder::new()
+        .set_clock(5, Some(true))
+        .set_gpio_upper(0x01, 0x03)
+        .clock_data(ClockData::LsbPosIn, &[0xAB])
+        .clock_data_in(ClockDataIn::MsbNeg, 4)
+        .clock_bits_out(ClockBitsOut::MsbNeg, 0xA0, 3)
+        .clock_tms_out(ClockTMSOut::NegEdge, 0x03, true, 2)
+        .gpio_lower()
+        .sync_bad_command()
+        .append_slice(&[0x14, 0x00, 0x00, 0x55, 0x8F, 0x01]);
+    assert_eq!(
+        cmd.to_string().lines().collect::<Vec<_>>(),
+        [
+            "enable_clock_divide()",
+            "set_clock(divisor=5)",
+            "set_gpio_upper(state=0x01, dir=0x03)",
+            "clock_data(LsbPosIn, 1 bytes: AB)",
+            "clock_data_in(MsbNeg, 4 bytes)",
+            "clock_bits_out(MsbNeg, data=0xA0, 3 bits)",
+            "clock_tms_out(NegEdge, data=0x03, tdi=true, 2 bits)",
+            "gpio_lower()",
+            "bad_command(0xAA)",
+            "raw(14 00 00 55)",
+            "raw(8F 01)",
+        ]
+    );
+}
+
+#[test]
+fn decode_bit_length_overflow() {
+    assert_eq!(
+        decode(&[0x8E, 0xFF]),
+        Ok(vec![MpsseOp::ClockCyclesBits { len: 256 }])
+    );
+    assert_eq!(
+        decode(&[0x12, 0xFF, 0x00]),
+        Ok(vec![MpsseOp::ClockBitsOut {
+            mode: ClockBitsOut::MsbPos,
+            data: 0x00,
+            len: 256,
+        }])
+    );
+}
+
+#[test]
+fn display_bit_length_overflow() {
+    assert_eq!(
+        MpsseCmdBuilder::with_vec(vec![0x8E, 0xFF]).to_string(),
+        "clock_cycles_bits(256 cycles)"
+    );
+    assert_eq!(
+        MpsseCmdBuilder::with_vec(vec![0x12, 0xFF, 0x00]).to_string(),
+        "clock_bits_out(MsbPos, data=0x00, 256 bits)"
+    );
+}
@@ -0,0 +1,38 @@
+use ftdi_mpsse::{FtdiDevice, MpsseCmdBuilder};
+
+#[test]
+fn capabilities() {
+    let h_series = [FtdiDevice::Ft232h, FtdiDevice::Ft2232h, FtdiDevice::Ft4232h];
+    for &device in h_series.iter() {
+        assert!(device.supports_3phase(), "{:?}", device);
+        assert!(device.supports_adaptive(), "{:?}", device);
+        assert_eq!(device.base_clock_hz(), 60_000_000);
+        assert_eq!(device.max_clock_hz(), 30_000_000);
+    }
+    assert!(FtdiDevice::Ft232h.has_upper_gpio());
+    assert!(FtdiDevice::Ft2232h.has_upper_gpio());
+    assert!(!FtdiDevice::Ft4232h.has_upper_gpio());
+
+    let d = FtdiDevice::Ft2232d;
+    assert!(d.has_upper_gpio());
+    assert!(!d.supports_3phase());
+    assert!(!d.supports_adaptive());
+    assert_eq!(d.base_clock_hz(), 12_000_000);
+    assert_eq!(d.max_clock_hz(), 6_000_000);
+}
+
+#[test]
+fn set_clock_hz_for() {
+    for &device in [FtdiDevice::Ft2232h, FtdiDevice::Ft2232d].iter() {
+        assert_eq!(
+            MpsseCmdBuilder::new().set_clock_hz_for(1_000_000, device),
+            MpsseCmdBuilder::new().set_clock_hz(1_000_000, device.base_clock_hz())
+        );
+    }
+    assert_eq!(
+        MpsseCmdBuilder::new()
+            .set_clock_hz_for(6_000_000, FtdiDevice::Ft2232d)
+            .as_slice(),
+        [0x86, 0x00, 0x00]
+    );
+}
@@ -0,0 +1,154 @@
+use ftdi_mpsse::*;
+
+mod common;
+
+use common::Scripted;
+
+#[test]
+fn read_struct() {
+    let mut exec = Scripted::with_responses(&[0x78, 0x56, 0x34, 0x12, 0xAA, 0xBB]);
+    let value: u32 = exec.read_struct(ClockDataIn::MsbNeg).unwrap();
+    assert_eq!(value, u32::from_ne_bytes([0x78, 0x56, 0x34, 0x12]));
+    let array: [u8; 2] = exec.read_struct(ClockDataIn::LsbPos).unwrap();
+    assert_eq!(array, [0xAA, 0xBB]);
+    assert_eq!(
+        exec.sent,
+        vec![
+            vec![
+                ClockDataIn::MsbNeg as u8,
+                3,
+                0,
+                MpsseCmd::SendImmediate as u8
+            ],
+            vec![
+                ClockDataIn::LsbPos as u8,
+                1,
+                0,
+                MpsseCmd::SendImmediate as u8
+            ],
+        ]
+    );
+}
+
+#[test]
+#[cfg(feature = "bytemuck")]
+fn read_struct_pod() {
+    let mut exec = Scripted::with_responses(&[0x34, 0x12, 0x78, 0x56, 0x42]);
+    let PodValue(value) = exec
+        .read_struct::<PodValue<[u16; 2]>>(ClockDataIn::MsbNeg)
+        .unwrap();
+    assert_eq!(
+        value,
+        [
+            u16::from_ne_bytes([0x34, 0x12]),
+            u16::from_ne_bytes([0x78, 0x56])
+        ]
+    );
+    // the concrete impls stay available with the feature enabled
+    let byte: u8 = exec.read_struct(ClockDataIn::MsbNeg).unwrap();
+    assert_eq!(byte, 0x42);
+}
+
+#[test]
+fn framed() {
+    let mut exec = FramedExecutor::new(
+        Scripted::with_responses(&[0x42]),
+        MpsseCmdBuilder::new().set_gpio_lower(0x00, 0x0B),
+        MpsseCmdBuilder::new().set_gpio_lower(0x08, 0x0B),
+    );
+    exec.send(&[MpsseCmd::EnableLoopback as u8]).unwrap();
+    let mut buf = [0; 1];
+    exec.xfer(&[MpsseCmd::GetDataBitsLowbyte as u8], &mut buf)
+        .unwrap();
+    assert_eq!(buf, [0x42]);
+    assert_eq!(
+        exec.into_inner().sent,
+        vec![
+            vec![0x80, 0x00, 0x0B, 0x84, 0x80, 0x08, 0x0B],
+            vec![0x80, 0x00, 0x0B, 0x81, 0x80, 0x08, 0x0B],
+        ]
+    );
+}
+
+#[test]
+#[should_panic(expected = "prologue and epilogue cannot read data")]
+fn framed_read_assert() {
+    FramedExecutor::new(
+        Scripted::default(),
+        MpsseCmdBuilder::new(),
+        MpsseCmdBuilder::new().gpio_lower(),
+    );
+}
+
+#[test]
+fn xfer_with_progress() {
+    let responses: Vec<u8> = (0..10000).map(|i| i as u8).collect();
+    let mut exec = Scripted::with_responses(&responses);
+    let mut reports = Vec::new();
+    let mut rx = vec![0; 10000];
+    exec.xfer_with_progress(&[0x87], &mut rx, |done, total| reports.push((done, total)))
+        .unwrap();
+    assert_eq!(rx, responses);
+    assert_eq!(exec.sent, vec![vec![0x87]]);
+    assert_eq!(reports, vec![(4096, 10000), (8192, 10000), (10000, 10000)]);
+
+    let mut exec = Scripted::with_responses(&[0x00; 10]);
+    let mut reports = Vec::new();
+    assert_eq!(
+        exec.xfer_with_progress(&[0x87], &mut [0; 5000], |done, _| reports.push(done)),
+        Err("timeout")
+    );
+    assert!(reports.is_empty());
+}
+
+#[test]
+fn stream_out() {
+    let mut exec = Scripted::default();
+    let chunks = vec![vec![0x01, 0x02], vec![], vec![0xAA; 65537]];
+    exec.stream_out(ClockDataOut::MsbNeg, chunks).unwrap();
+    assert_eq!(exec.sent.len(), 2);
+    assert_eq!(exec.sent[0], [0x11, 0x01, 0x00, 0x01, 0x02]);
+    assert_eq!(exec.sent[1].len(), 3 + 65536 + 3 + 1);
+    assert_eq!(exec.sent[1][..3], [0x11, 0xFF, 0xFF]);
+    assert_eq!(exec.sent[1][65539..], [0x11, 0x00, 0x00, 0xAA]);
+
+    // chunks are generated lazily
+    let mut exec = Scripted::default();
+    let mut generated = 0;
+    exec.stream_out(
+        ClockDataOut::LsbPos,
+        (0..3u8).map(|i| {
+            generated += 1;
+            vec![i]
+        }),
+    )
+    .unwrap();
+    assert_eq!(generated, 3);
+    assert_eq!(exec.sent[2], [0x18, 0x00, 0x00, 0x02]);
+}
+
+#[test]
+fn xfer_checked() {
+    let cmd = MpsseCmdBuilder::new()
+        .gpio_lower()
+        .gpio_upper()
+        .send_immediate();
+    let mut exec = Scripted::with_responses(&[0x12, 0x34]);
+    assert_eq!(
+        exec.xfer_checked(&cmd, &mut [0; 3]),
+        Err(XferError::ReadLenMismatch {
+            expected: 2,
+            got: 3
+        })
+    );
+    assert!(exec.sent.is_empty());
+
+    let mut rx = [0; 2];
+    exec.xfer_checked(&cmd, &mut rx).unwrap();
+    assert_eq!(rx, [0x12, 0x34]);
+    assert_eq!(exec.sent, vec![cmd.as_slice().to_vec()]);
+    assert_eq!(
+        exec.xfer_checked(&cmd, &mut rx),
+        Err(XferError::Executor("timeout"))
+    );
+}
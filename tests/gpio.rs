@@ -0,0 +1,55 @@
+use ftdi_mpsse::*;
+
+#[test]
+fn gpio_walking_ones() {
+    let (cmd, tokens) = MpsseCmdBuilder::new().gpio_upper().gpio_walking_ones(0x13);
+    assert_eq!(
+        cmd.as_slice(),
+        [
+            0x83, // GPIO read
+            0x80, 0x01, 0x13, 0x81, // pin 0
+            0x80, 0x02, 0x13, 0x81, // pin 1
+            0x80, 0x10, 0x13, 0x81, // pin 4
+            0x80, 0x00, 0x13, // all low
+        ]
+    );
+    let offsets: Vec<_> = tokens.iter().map(ReadToken::offset).collect();
+    assert_eq!(offsets, [1, 2, 3]);
+
+    let (cmd, tokens) = MpsseCmdBuilder::new().gpio_walking_ones(0x00);
+    assert_eq!(cmd.as_slice(), [0x80, 0x00, 0x00]);
+    assert!(tokens.is_empty());
+}
+
+#[test]
+fn gpio_walking_ones_failures() {
+    // pins outside of the mask are ignored
+    assert!(ftdi_mpsse::gpio_walking_ones_failures(0x13, &[0x01, 0xEE, 0x30]).is_empty());
+    // pin 1 shorted to pin 4, pin 4 has no reading
+    assert_eq!(
+        ftdi_mpsse::gpio_walking_ones_failures(0x13, &[0x01, 0x12]),
+        vec![1, 4]
+    );
+}
+
+#[test]
+fn gpio_state() {
+    let mut gpio = GpioState::new(0x01, 0x03);
+    gpio.set_output(7);
+    gpio.write_pin(7, true);
+    gpio.write_pin(0, false);
+    gpio.set_input(1);
+    assert_eq!(gpio, GpioState::new(0x80, 0x81));
+    assert!(gpio.read_pin(7));
+    assert!(!gpio.read_pin(0));
+
+    let cmd = gpio.apply_lower(MpsseCmdBuilder::new());
+    let cmd = gpio.apply_upper(cmd);
+    assert_eq!(cmd.as_slice(), [0x80, 0x80, 0x81, 0x82, 0x80, 0x81]);
+}
+
+#[test]
+#[should_panic(expected = "pin number cannot exceed 7")]
+fn gpio_state_pin_out_of_range() {
+    GpioState::default().write_pin(8, true);
+}
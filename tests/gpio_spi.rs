@@ -0,0 +1,50 @@
+use ftdi_mpsse::*;
+
+#[test]
+fn gpio_spi_mode0() {
+    let spi = GpioSpi::new(4, 5, 6, 7, SpiMode::Mode0);
+    assert_eq!(spi.mode(), SpiMode::Mode0);
+    assert_eq!(
+        spi.init(MpsseCmdBuilder::new()).as_slice(),
+        [0x80, 0x80, 0xB0]
+    );
+
+    let (cmd, token) = spi.transfer(MpsseCmdBuilder::new().gpio_lower(), &[0x80]);
+    let mut expected = vec![0x81, 0x80, 0x00, 0xB0];
+    expected.extend_from_slice(&[0x80, 0x20, 0xB0, 0x80, 0x30, 0xB0, 0x81]);
+    for _ in 0..7 {
+        expected.extend_from_slice(&[0x80, 0x00, 0xB0, 0x80, 0x10, 0xB0, 0x81]);
+    }
+    expected.extend_from_slice(&[0x80, 0x00, 0xB0, 0x80, 0x80, 0xB0]);
+    assert_eq!(cmd.as_slice(), expected.as_slice());
+    assert_eq!(token.range(), 1..9);
+}
+
+#[test]
+fn gpio_spi_mode3_write() {
+    let spi = GpioSpi::new(0, 1, 2, 3, SpiMode::Mode3).lsb_first(true);
+    assert_eq!(
+        spi.init(MpsseCmdBuilder::new()).as_slice(),
+        [0x80, 0x09, 0x0B]
+    );
+
+    let cmd = spi.write(MpsseCmdBuilder::new(), &[0x01]);
+    let mut expected = vec![0x80, 0x01, 0x0B];
+    // LSB first, clock falls and data changes on the leading edge
+    expected.extend_from_slice(&[0x80, 0x02, 0x0B, 0x80, 0x03, 0x0B]);
+    for _ in 0..7 {
+        expected.extend_from_slice(&[0x80, 0x00, 0x0B, 0x80, 0x01, 0x0B]);
+    }
+    expected.extend_from_slice(&[0x80, 0x01, 0x0B, 0x80, 0x09, 0x0B]);
+    assert_eq!(cmd.as_slice(), expected.as_slice());
+    assert!(cmd.gpio_reads().is_empty());
+}
+
+#[test]
+fn gpio_spi_decode() {
+    let samples = [0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0xFB, 0x04, 0xFF];
+    let msb = GpioSpi::new(0, 1, 2, 3, SpiMode::Mode0);
+    assert_eq!(msb.decode(&samples), [0x81]);
+    assert_eq!(msb.lsb_first(true).decode(&samples), [0x81]);
+    assert_eq!(msb.lsb_first(true).decode(&samples[1..]), [0xC0]);
+}
@@ -0,0 +1,94 @@
+use ftdi_mpsse::{ClockDataOut, CommandGroup, GroupedBuilder, MpsseCmdBuilder};
+
+#[test]
+fn nested_groups() {
+    let cmd = GroupedBuilder::new(MpsseCmdBuilder::new())
+        .map(|cmd| cmd.set_clock(2, Some(false)))
+        .group("outer", |cmd| {
+            cmd.group("first", |cmd| cmd.map(MpsseCmdBuilder::gpio_lower))
+                .group("second", |cmd| {
+                    cmd.group("empty", |cmd| cmd)
+                        .map(|cmd| cmd.clock_data_out(ClockDataOut::MsbNeg, &[0x01]))
+                })
+        })
+        .group("last", |cmd| cmd.map(MpsseCmdBuilder::send_immediate));
+    let spans: Vec<(&str, usize, usize, usize)> = cmd
+        .groups()
+        .iter()
+        .map(|group| {
+            (
+                group.name.as_str(),
+                group.range.start,
+                group.range.end,
+                group.depth,
+            )
+        })
+        .collect();
+    assert_eq!(
+        spans,
+        [
+            ("outer", 4, 9, 0),
+            ("first", 4, 5, 1),
+            ("second", 5, 9, 1),
+            ("empty", 5, 5, 2),
+            ("last", 9, 10, 0),
+        ]
+    );
+}
+
+#[test]
+fn group_does_not_change_commands() {
+    let grouped = GroupedBuilder::from(MpsseCmdBuilder::new())
+        .group("gpio", |cmd| cmd.map(|cmd| cmd.gpio_upper()));
+    assert_eq!(
+        grouped.inner().as_slice(),
+        MpsseCmdBuilder::new().gpio_upper().as_slice()
+    );
+    assert_eq!(
+        grouped.groups(),
+        [CommandGroup {
+            name: "gpio".to_string(),
+            range: 0..1,
+            depth: 0,
+        }]
+    );
+    let (cmd, groups) = grouped.clone().into_parts();
+    assert_eq!(cmd, MpsseCmdBuilder::from(grouped));
+    assert_eq!(groups.len(), 1);
+}
+
+#[test]
+fn replaced_builder_drops_groups() {
+    let cmd = GroupedBuilder::default().group("replaced", |_| {
+        GroupedBuilder::new(MpsseCmdBuilder::new().gpio_lower())
+    });
+    assert!(cmd.groups().is_empty());
+
+    let cmd = GroupedBuilder::default().group("replaced", |_| {
+        GroupedBuilder::default()
+            .group("other", |cmd| cmd.map(|cmd| cmd.gpio_lower()))
+            .map(|cmd| cmd.gpio_upper())
+    });
+    assert_eq!(
+        cmd.groups(),
+        [CommandGroup {
+            name: "other".to_string(),
+            range: 0..1,
+            depth: 0,
+        }]
+    );
+}
+
+#[test]
+fn append_groups() {
+    let init = GroupedBuilder::new(MpsseCmdBuilder::new().set_gpio_lower(0x08, 0x0B));
+    let write = GroupedBuilder::default().group("write", |cmd| {
+        cmd.map(|cmd| cmd.clock_data_out(ClockDataOut::MsbNeg, &[0x06]))
+    });
+    let cmd = init.append(write);
+    assert_eq!(
+        cmd.inner().as_slice(),
+        [0x80, 0x08, 0x0B, 0x11, 0x00, 0x00, 0x06]
+    );
+    assert_eq!(cmd.groups()[0].range, 3..7);
+}
@@ -0,0 +1,167 @@
+#![cfg(feature = "embedded-hal")]
+use embedded_hal::spi::Operation;
+use ftdi_mpsse::*;
+
+#[test]
+fn spi_transaction() {
+    let dev = SpiDevice::new(3, SpiMode::Mode1);
+    let mut read = [0; 2];
+    let mut transfer = [0; 3];
+    let mut in_place = [0x55];
+    let (cmd, tokens) = dev.transaction(
+        MpsseCmdBuilder::new(),
+        &[
+            Operation::Write(&[0x0B, 0x00]),
+            Operation::DelayNs(1000),
+            Operation::Read(&mut read),
+            Operation::Transfer(&mut transfer, &[0x01, 0x02]),
+            Operation::TransferInPlace(&mut in_place),
+        ],
+    );
+    assert_eq!(
+        cmd.as_slice(),
+        [
+            0x80, 0x00, 0x0B, // CS low
+            0x10, 0x01, 0x00, 0x0B, 0x00, // write
+            0x24, 0x01, 0x00, // read
+            0x34, 0x02, 0x00, 0x01, 0x02, 0x00, // transfer, padded
+            0x34, 0x00, 0x00, 0x55, // transfer in place
+            0x80, 0x08, 0x0B, // CS high
+        ]
+    );
+    let ranges: Vec<_> = tokens.iter().map(ReadToken::range).collect();
+    assert_eq!(ranges, vec![0..2, 2..5, 5..6]);
+}
+
+#[test]
+fn spi_transaction_long_read() {
+    let dev = SpiDevice::new(3, SpiMode::Mode0);
+    let mut read = vec![0; 70000];
+    let (cmd, tokens) = dev.transaction(MpsseCmdBuilder::new(), &[Operation::Read(&mut read)]);
+    assert_eq!(
+        cmd.as_slice(),
+        [
+            0x80, 0x00, 0x0B, // CS low
+            0x20, 0xFF, 0xFF, // 65536 bytes
+            0x20, 0x6F, 0x11, // 4464 bytes
+            0x80, 0x08, 0x0B, // CS high
+        ]
+    );
+    assert_eq!(tokens[0].range(), 0..70000);
+}
+
+mod common;
+
+use common::Scripted;
+use embedded_hal::spi::SpiBus;
+
+#[test]
+fn mpsse_spi_bus() {
+    let exec = Scripted::with_responses(&[0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77]);
+    let mut spi = MpsseSpi::new(exec, SpiMode::Mode0);
+    assert_eq!(spi.mode(), SpiMode::Mode0);
+
+    spi.write(&[0x06]).unwrap();
+    let mut read = [0; 2];
+    spi.read(&mut read).unwrap();
+    assert_eq!(read, [0x11, 0x22]);
+
+    // write longer than read
+    let mut short = [0; 1];
+    spi.transfer(&mut short, &[0x01, 0x02]).unwrap();
+    assert_eq!(short, [0x33]);
+    // read longer than write
+    let mut long = [0; 2];
+    spi.transfer(&mut long, &[0x03]).unwrap();
+    assert_eq!(long, [0x55, 0x66]);
+
+    let mut in_place = [0xA5];
+    spi.transfer_in_place(&mut in_place).unwrap();
+    assert_eq!(in_place, [0x77]);
+
+    spi.read(&mut []).unwrap();
+    spi.flush().unwrap();
+
+    let exec = spi.into_inner();
+    assert_eq!(
+        exec.sent,
+        [
+            vec![0x11, 0x00, 0x00, 0x06],
+            vec![0x20, 0x01, 0x00, 0x87],
+            vec![0x31, 0x01, 0x00, 0x01, 0x02, 0x87],
+            vec![0x31, 0x01, 0x00, 0x03, 0x00, 0x87],
+            vec![0x31, 0x00, 0x00, 0xA5, 0x87],
+        ]
+    );
+}
+
+#[test]
+fn mpsse_spi_error() {
+    use embedded_hal::spi::{Error, ErrorKind};
+
+    let mut spi = MpsseSpi::new(Scripted::default(), SpiMode::Mode2);
+    let err = spi.read(&mut [0; 1]).unwrap_err();
+    assert_eq!(err, HalError("timeout"));
+    assert_eq!(err.kind(), ErrorKind::Other);
+}
+
+#[test]
+fn mpsse_gpio_pins() {
+    use embedded_hal::digital::{InputPin, OutputPin, StatefulOutputPin};
+
+    let gpio = MpsseGpio::new(Scripted::with_responses(&[0x10, 0xFE]));
+    let mut led = gpio.pin(GpioByte::Lower, 4);
+    let mut cs = gpio.pin(GpioByte::Lower, 3);
+    let mut enable = gpio.pin(GpioByte::Upper, 7);
+    let mut button = gpio.pin(GpioByte::Upper, 0);
+
+    cs.set_high().unwrap();
+    led.set_high().unwrap();
+    cs.set_low().unwrap();
+    enable.set_high().unwrap();
+    assert_eq!(gpio.state(GpioByte::Lower), (0x10, 0x18));
+    assert_eq!(gpio.state(GpioByte::Upper), (0x80, 0x80));
+    assert!(led.is_set_high().unwrap());
+    assert!(cs.is_set_low().unwrap());
+
+    assert!(led.is_high().unwrap());
+    assert!(button.is_low().unwrap());
+}
+
+#[test]
+fn mpsse_gpio_commands() {
+    use embedded_hal::digital::{InputPin, OutputPin};
+
+    let mut exec = Scripted::default();
+    {
+        let gpio = MpsseGpio::new(&mut exec);
+        let mut pin = gpio.pin(GpioByte::Upper, 2);
+        pin.set_high().unwrap();
+        pin.set_low().unwrap();
+        let mut input = gpio.pin(GpioByte::Lower, 0);
+        assert_eq!(input.is_high(), Err(HalError("timeout")));
+    }
+    assert_eq!(
+        exec.sent,
+        [
+            vec![0x82, 0x04, 0x04],
+            vec![0x82, 0x00, 0x04],
+            vec![0x81, 0x87]
+        ]
+    );
+}
+
+#[test]
+fn mpsse_gpio_with_state() {
+    use embedded_hal::digital::OutputPin;
+
+    // SPI pins already configured, chip select high
+    let mut exec = Scripted::default();
+    {
+        let gpio = MpsseGpio::with_state(&mut exec, 0x08, 0x0B, 0x00, 0x00);
+        gpio.pin(GpioByte::Lower, 4).set_high().unwrap();
+        assert_eq!(gpio.state(GpioByte::Lower), (0x18, 0x1B));
+        assert_eq!(gpio.state(GpioByte::Upper), (0x00, 0x00));
+    }
+    assert_eq!(exec.sent, [vec![0x80, 0x18, 0x1B]]);
+}
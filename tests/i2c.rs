@@ -0,0 +1,106 @@
+use ftdi_mpsse::*;
+
+#[test]
+fn scan() {
+    let (cmd, tokens) = MpsseCmdBuilder::new().i2c_scan(1, 0);
+    assert_eq!(tokens.len(), 0x70);
+    for (i, token) in tokens.iter().enumerate() {
+        assert_eq!(*token, ReadToken::new(i, 1));
+    }
+
+    let probe = |addr: u8| {
+        vec![
+            // START
+            MpsseCmd::SetDataBitsLowbyte as u8,
+            0x03,
+            0x03,
+            MpsseCmd::SetDataBitsLowbyte as u8,
+            0x01,
+            0x03,
+            MpsseCmd::SetDataBitsLowbyte as u8,
+            0x00,
+            0x03,
+            // address and ACK
+            ClockBitsOut::MsbNeg as u8,
+            7,
+            addr << 1,
+            MpsseCmd::SetDataBitsLowbyte as u8,
+            0x00,
+            0x01,
+            ClockBitsIn::MsbPos as u8,
+            0,
+            MpsseCmd::SetDataBitsLowbyte as u8,
+            0x00,
+            0x03,
+            // STOP
+            MpsseCmd::SetDataBitsLowbyte as u8,
+            0x00,
+            0x03,
+            MpsseCmd::SetDataBitsLowbyte as u8,
+            0x01,
+            0x03,
+            MpsseCmd::SetDataBitsLowbyte as u8,
+            0x03,
+            0x03,
+        ]
+    };
+    let expected: Vec<u8> = (0x08..0x78).flat_map(probe).collect();
+    assert_eq!(cmd.as_slice(), expected.as_slice());
+}
+
+#[test]
+fn present_addresses() {
+    let mut readings = [0xFF; 0x70];
+    readings[0] = 0xFE;
+    readings[0x68 - 0x08] = 0x00;
+    assert_eq!(i2c_present_addresses(&readings), vec![0x08, 0x68]);
+    assert!(i2c_present_addresses(&[]).is_empty());
+}
+
+#[test]
+fn fragments() {
+    let i2c = I2c::new(1, 0);
+    let cmd = i2c.start(MpsseCmdBuilder::new());
+    assert_eq!(
+        cmd.as_slice(),
+        [0x80, 0x03, 0x03, 0x80, 0x01, 0x03, 0x80, 0x00, 0x03]
+    );
+
+    let cmd = i2c.repeated_start(MpsseCmdBuilder::new());
+    assert_eq!(
+        cmd.as_slice(),
+        [0x80, 0x02, 0x03, 0x80, 0x03, 0x03, 0x80, 0x01, 0x03, 0x80, 0x00, 0x03]
+    );
+
+    let cmd = i2c.stop(MpsseCmdBuilder::new());
+    assert_eq!(
+        cmd.as_slice(),
+        [0x80, 0x00, 0x03, 0x80, 0x01, 0x03, 0x80, 0x03, 0x03]
+    );
+}
+
+#[test]
+fn write_and_read_bytes() {
+    let i2c = I2c::new(1, 0);
+    let (cmd, ack) = i2c.write_byte_read_ack(MpsseCmdBuilder::new().gpio_lower(), 0xA0);
+    assert_eq!(ack, ReadToken::new(1, 1));
+    let (cmd, data) = i2c.read_byte_send_ack(cmd, false);
+    assert_eq!(data, ReadToken::new(2, 1));
+    let (cmd, last) = i2c.read_byte_send_ack(cmd, true);
+    assert_eq!(last, ReadToken::new(3, 1));
+    assert_eq!(
+        cmd.as_slice(),
+        [
+            0x81, // write byte
+            0x13, 0x07, 0xA0, 0x80, 0x00, 0x01, 0x22, 0x00, 0x80, 0x00, 0x03,
+            // read byte, ACK
+            0x80, 0x00, 0x01, 0x22, 0x07, 0x80, 0x00, 0x03, 0x13, 0x00, 0x00,
+            // read byte, NACK
+            0x80, 0x00, 0x01, 0x22, 0x07, 0x80, 0x00, 0x03, 0x13, 0x00, 0x80,
+        ]
+    );
+
+    assert!(I2c::is_ack(0x00));
+    assert!(I2c::is_ack(0xFE));
+    assert!(!I2c::is_ack(0x01));
+}
@@ -0,0 +1,48 @@
+use ftdi_mpsse::*;
+
+#[test]
+fn i2s_frame_lsb_first() {
+    let cmd = MpsseCmdBuilder::new().i2s_frame(
+        ClockDataOut::LsbNeg,
+        &[0x0102, 0x0304, 0x0506],
+        7,
+        1_000_000,
+    );
+    assert_eq!(
+        cmd.as_slice(),
+        [
+            0x80, 0x00, 0x83, 0x19, 0x01, 0x00, 0x02, 0x01, // left
+            0x80, 0x80, 0x83, 0x19, 0x01, 0x00, 0x04, 0x03, // right
+            0x80, 0x00, 0x83, 0x19, 0x01, 0x00, 0x06, 0x05, // left
+        ]
+    );
+    assert!(MpsseCmdBuilder::new()
+        .i2s_frame(ClockDataOut::MsbPos, &[], 3, 1_000_000)
+        .as_slice()
+        .is_empty());
+}
+
+#[test]
+fn i2s_sample_rate_ratio() {
+    assert_eq!(i2s_sample_rate(30_000_000), 937_500);
+    assert_eq!(i2s_sample_rate(3_072_000), 96_000);
+    assert_eq!(i2s_sample_rate(31), 0);
+}
+
+#[test]
+#[should_panic(expected = "LRCLK pin must be between 2 and 7")]
+fn i2s_frame_bad_pin() {
+    let _ = MpsseCmdBuilder::new().i2s_frame(ClockDataOut::MsbPos, &[0], 8, 1_000_000);
+}
+
+#[test]
+#[should_panic(expected = "LRCLK pin must be between 2 and 7")]
+fn i2s_frame_pin_on_bclk_data() {
+    let _ = MpsseCmdBuilder::new().i2s_frame(ClockDataOut::MsbPos, &[0], 1, 1_000_000);
+}
+
+#[test]
+#[should_panic(expected = "clock frequency must be between 1 Hz and 30 MHz")]
+fn i2s_frame_bad_clock() {
+    let _ = MpsseCmdBuilder::new().i2s_frame(ClockDataOut::MsbPos, &[0], 3, 60_000_000);
+}
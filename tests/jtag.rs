@@ -0,0 +1,110 @@
+use ftdi_mpsse::*;
+
+#[test]
+fn next_state() {
+    let mut state = TapState::RunTestIdle;
+    for tms in [true, true, false, false] {
+        state = state.next(tms);
+    }
+    assert_eq!(state, TapState::ShiftIr);
+    for _ in 0..5 {
+        state = state.next(true);
+    }
+    assert_eq!(state, TapState::TestLogicReset);
+}
+
+#[test]
+fn goto_state() {
+    let tap = JtagTap::new(TapState::RunTestIdle);
+    let cmd = MpsseCmdBuilder::new();
+    let cmd = tap.goto_state(cmd, TapState::RunTestIdle, TapState::RunTestIdle);
+    assert!(cmd.as_slice().is_empty());
+
+    // TMS 0, 1, 1, 0, 0
+    let cmd = tap.goto_state(cmd, TapState::TestLogicReset, TapState::ShiftIr);
+    assert_eq!(cmd.as_slice(), [0x4B, 0x04, 0x06]);
+
+    // TMS 1, 0
+    let cmd = tap.goto_state(MpsseCmdBuilder::new(), TapState::PauseDr, TapState::ShiftDr);
+    assert_eq!(cmd.as_slice(), [0x4B, 0x01, 0x01]);
+
+    let cmd = tap.reset(MpsseCmdBuilder::new());
+    assert_eq!(cmd.as_slice(), [0x4B, 0x04, 0x1F]);
+}
+
+#[test]
+fn shortest_paths_reach_target() {
+    let states = [
+        TapState::TestLogicReset,
+        TapState::RunTestIdle,
+        TapState::SelectDr,
+        TapState::CaptureDr,
+        TapState::ShiftDr,
+        TapState::Exit1Dr,
+        TapState::PauseDr,
+        TapState::Exit2Dr,
+        TapState::UpdateDr,
+        TapState::SelectIr,
+        TapState::CaptureIr,
+        TapState::ShiftIr,
+        TapState::Exit1Ir,
+        TapState::PauseIr,
+        TapState::Exit2Ir,
+        TapState::UpdateIr,
+    ];
+    let tap = JtagTap::new(TapState::RunTestIdle);
+    for from in states {
+        for to in states {
+            let cmd = tap.goto_state(MpsseCmdBuilder::new(), from, to);
+            let mut state = from;
+            for tms in cmd.as_slice().chunks(3) {
+                assert_eq!(tms[0], 0x4B);
+                for bit in 0..=tms[1] {
+                    state = state.next(tms[2] >> bit & 1 != 0);
+                }
+            }
+            assert_eq!(state, to, "{:?} to {:?}", from, to);
+        }
+    }
+}
+
+#[test]
+fn shift_dr() {
+    let tap = JtagTap::new(TapState::RunTestIdle);
+    let (cmd, token) = tap.shift_dr(MpsseCmdBuilder::new().gpio_lower(), &[0x12, 0xB4]);
+    assert_eq!(token, ReadToken::new(1, 3));
+    assert_eq!(
+        cmd.as_slice(),
+        [
+            0x81, // Run-Test/Idle to Shift-DR
+            0x4B, 0x02, 0x01, // first byte
+            0x39, 0x00, 0x00, 0x12, // 7 bits of the last byte
+            0x3B, 0x06, 0xB4, // last bit, to Exit1-DR
+            0x6B, 0x00, 0x81, // to Run-Test/Idle
+            0x4B, 0x01, 0x01,
+        ]
+    );
+}
+
+#[test]
+fn shift_ir_single_byte() {
+    let tap = JtagTap::new(TapState::RunTestIdle);
+    let (cmd, token) = tap.shift_ir(MpsseCmdBuilder::new(), &[0x01]);
+    assert_eq!(token, ReadToken::new(0, 2));
+    assert_eq!(
+        cmd.as_slice(),
+        [0x4B, 0x03, 0x03, 0x3B, 0x06, 0x01, 0x6B, 0x00, 0x01, 0x4B, 0x01, 0x01]
+    );
+}
+
+#[test]
+fn decode_shift() {
+    assert_eq!(jtag_decode_shift(&[0x12, 0x68, 0x80]), vec![0x12, 0xB4]);
+    assert_eq!(jtag_decode_shift(&[0x02, 0x00]), vec![0x01]);
+}
+
+#[test]
+#[should_panic(expected = "idle state must be a stable TAP state")]
+fn unstable_idle() {
+    JtagTap::new(TapState::ShiftDr);
+}
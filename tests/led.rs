@@ -0,0 +1,34 @@
+#![cfg(feature = "std")]
+use ftdi_mpsse::*;
+
+#[test]
+fn led_ramp_linear() {
+    let cmd = MpsseCmdBuilder::new().clock_led_ramp(ClockDataOut::LsbPos, 4, 1.0);
+    assert_eq!(
+        cmd.as_slice(),
+        [ClockDataOut::LsbPos as u8, 3, 0, 0, 85, 170, 255]
+    );
+}
+
+#[test]
+fn led_ramp_edge_cases() {
+    assert!(MpsseCmdBuilder::new()
+        .clock_led_ramp(ClockDataOut::MsbPos, 0, 2.2)
+        .as_slice()
+        .is_empty());
+    assert_eq!(
+        MpsseCmdBuilder::new()
+            .clock_led_ramp(ClockDataOut::MsbPos, 1, 2.2)
+            .as_slice(),
+        [ClockDataOut::MsbPos as u8, 0, 0, 255]
+    );
+}
+
+#[test]
+fn led_ramp_chunked() {
+    let cmd = MpsseCmdBuilder::new().clock_led_ramp(ClockDataOut::MsbPos, 65537, 2.2);
+    let data = cmd.as_slice();
+    assert_eq!(data.len(), 65537 + 6);
+    assert_eq!(data[..3], [ClockDataOut::MsbPos as u8, 0xFF, 0xFF]);
+    assert_eq!(data[65539..65543], [ClockDataOut::MsbPos as u8, 0, 0, 255]);
+}
@@ -0,0 +1,71 @@
+use ftdi_mpsse::*;
+
+#[test]
+fn expected_loopback_response_bytes() {
+    let cmd = MpsseCmdBuilder::new()
+        .enable_loopback()
+        .clock_data(ClockData::MsbPosIn, &[0x12, 0x35])
+        // TDI left high by the last bit of 0x35
+        .clock_data_in(ClockDataIn::MsbPos, 2)
+        .clock_data_out(ClockDataOut::LsbNeg, &[0x7F])
+        // TDI left low by bit 7 of 0x7F
+        .clock_data_in(ClockDataIn::LsbPos, 1)
+        .send_immediate();
+    assert_eq!(
+        cmd.expected_loopback_response(),
+        [0x12, 0x35, 0xFF, 0xFF, 0x00]
+    );
+}
+
+#[test]
+fn expected_loopback_response_bits() {
+    let cmd = MpsseCmdBuilder::new()
+        .clock_bits(ClockBits::MsbPosIn, 0xA0, 3)
+        .clock_bits(ClockBits::LsbPosIn, 0x05, 3)
+        .clock_bits(ClockBits::MsbNegIn, 0xC3, 8)
+        // TDI left high
+        .clock_bits_in(ClockBitsIn::MsbPos, 2)
+        .clock_tms(ClockTMS::NegTMSPosTDO, 0x00, false, 2)
+        .clock_tms(ClockTMS::NegTMSPosTDO, 0x00, true, 4);
+    assert_eq!(
+        cmd.expected_loopback_response(),
+        [0x05, 0xA0, 0xC3, 0x03, 0x00, 0xF0]
+    );
+}
+
+#[test]
+fn expected_loopback_response_bit_length_masked() {
+    // only the low 3 bits of the length are used, 0x0F clocks 8 bits
+    let mut cmd = MpsseCmdBuilder::new();
+    cmd.0
+        .extend_from_slice(&[0x13, 0x0F, 0xAA, 0x33, 0x0F, 0x5A]);
+    assert_eq!(cmd.expected_loopback_response(), [0x5A]);
+    cmd.0[4] = 0x07;
+    assert_eq!(cmd.expected_loopback_response(), [0x5A]);
+}
+
+#[test]
+fn expected_loopback_response_gpio_and_bad_commands() {
+    let mut cmd = MpsseCmdBuilder::new()
+        .gpio_lower()
+        .set_gpio_lower(0xF2, 0x3B)
+        .gpio_lower()
+        .set_gpio_upper(0xFF, 0x0F)
+        .gpio_upper()
+        // TDI set high by the GPIO command
+        .clock_data_in(ClockDataIn::MsbPos, 1);
+    cmd.0.push(0xAB);
+    assert_eq!(
+        cmd.expected_loopback_response(),
+        [0x00, 0x32, 0x0F, 0xFF, 0xFA, 0xAB]
+    );
+}
+
+#[test]
+fn expected_loopback_response_length() {
+    let (cmd, tokens) = MpsseCmdBuilder::new().i2c_scan(1, 0);
+    assert_eq!(
+        cmd.expected_loopback_response().len(),
+        tokens.last().unwrap().range().end
+    );
+}
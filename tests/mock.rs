@@ -0,0 +1,87 @@
+#![cfg(feature = "mock")]
+use ftdi_mpsse::*;
+
+#[test]
+fn records_and_responds() {
+    let mut exec = MockMpsseExecutor::new();
+    assert_eq!(exec.settings(), None);
+    let settings = MpsseSettings {
+        clock_frequency: Some(1_000_000),
+        ..MpsseSettings::default()
+    };
+    exec.init(&settings).unwrap();
+    assert_eq!(exec.settings(), Some(&settings));
+
+    exec.push_response(&[0x12, 0x34]);
+    exec.push_response(&[0x56]);
+    exec.send(&[0x80, 0x08, 0x0B]).unwrap();
+    let mut rx = [0; 2];
+    exec.xfer(&[0x81, 0x83, 0x87], &mut rx).unwrap();
+    assert_eq!(rx, [0x12, 0x34]);
+    assert_eq!(exec.remaining_responses(), 1);
+    assert_eq!(
+        exec.sent(),
+        [vec![0x80, 0x08, 0x0B], vec![0x81, 0x83, 0x87]]
+    );
+    exec.assert_sent(&[0x80, 0x08, 0x0B, 0x81, 0x83, 0x87]);
+}
+
+#[test]
+fn recv_without_responses() {
+    let mut exec = MockMpsseExecutor::new();
+    exec.push_response(&[0x00]);
+    let mut rx = [0; 2];
+    assert_eq!(
+        exec.recv(&mut rx),
+        Err(MockError {
+            requested: 2,
+            available: 1
+        })
+    );
+    assert_eq!(exec.remaining_responses(), 1);
+}
+
+#[test]
+#[should_panic(expected = "sent data does not match")]
+fn assert_sent_mismatch() {
+    let mut exec = MockMpsseExecutor::new();
+    exec.send(&[0x87]).unwrap();
+    exec.assert_sent(&[0x81]);
+}
+
+#[test]
+fn loopback_executor() {
+    let mut exec = LoopbackExecutor::new();
+    assert!(!exec.loopback());
+
+    let cmd = MpsseCmdBuilder::new()
+        .clock_data(ClockData::MsbPosIn, &[0x12])
+        .enable_loopback()
+        .clock_data(ClockData::MsbPosIn, &[0x34, 0x56])
+        .clock_bits(ClockBits::LsbPosIn, 0x05, 3)
+        .set_gpio_lower(0x08, 0x0B)
+        .gpio_lower()
+        .send_immediate();
+    let mut rx = [0; 5];
+    exec.xfer(cmd.as_slice(), &mut rx).unwrap();
+    // not connected before loopback is enabled
+    assert_eq!(rx, [0xFF, 0x34, 0x56, 0xA0, 0x08]);
+    assert!(exec.loopback());
+
+    // command split across sends
+    let cmd = MpsseCmdBuilder::new()
+        .clock_data(ClockData::LsbPosIn, &[0x9A, 0xBC])
+        .disable_loopback()
+        .clock_data_in(ClockDataIn::MsbPos, 1);
+    let (first, second) = cmd.as_slice().split_at(4);
+    exec.send(first).unwrap();
+    assert_eq!(exec.remaining_responses(), 0);
+    exec.send(second).unwrap();
+    let mut rx = [0; 3];
+    exec.recv(&mut rx).unwrap();
+    assert_eq!(rx, [0x9A, 0xBC, 0xFF]);
+    assert!(exec.recv(&mut rx).is_err());
+
+    exec.init(&MpsseSettings::default()).unwrap();
+    assert!(!exec.loopback());
+}
@@ -1,3 +1,5 @@
+#![allow(clippy::unnecessary_cast)]
+
 use ftdi_mpsse::*;
 
 #[test]
@@ -13,6 +15,8 @@ fn all_commands() {
             disable_loopback();
             enable_3phase_data_clocking();
             disable_3phase_data_clocking();
+            enable_drive_only_zero(val1, 0x2);
+            disable_drive_only_zero();
             set_gpio_lower(val1, 0x1);
             set_gpio_upper(val2, 0x2);
             const LOWER_INDEX = gpio_lower();
@@ -28,9 +32,15 @@ fn all_commands() {
             const BITS_INDEX = clock_bits(ClockBits::MsbPosIn, val42, 8);
             clock_tms_out(ClockTMSOut::NegEdge, val42, true, 7);
             const TMS_INDEX = clock_tms(ClockTMS::NegTMSPosTDO, val42, false, 7);
+            clock_cycles_bits(3);
+            clock_cycles_bytes(513);
+            clock_until_io_high();
+            clock_until_io_low();
+            clock_n8_until_io_high(1);
+            clock_n8_until_io_low(0x1234);
         };
     }
-    assert_eq!(data.len(), 46);
+    assert_eq!(data.len(), 65);
     assert_eq!(
         data,
         [
@@ -38,6 +48,12 @@ fn all_commands() {
             MpsseCmd::DisableLoopback as u8,
             MpsseCmd::Enable3PhaseClocking as u8,
             MpsseCmd::Disable3PhaseClocking as u8,
+            MpsseCmd::EnableDriveOnlyZero as u8,
+            val1,
+            0x2,
+            MpsseCmd::EnableDriveOnlyZero as u8,
+            0,
+            0,
             MpsseCmd::SetDataBitsLowbyte as u8,
             val1,
             0x1,
@@ -80,6 +96,19 @@ fn all_commands() {
             ClockTMS::NegTMSPosTDO as u8,
             6 as u8,
             42 as u8,
+            MpsseCmd::ClockNBits as u8,
+            2,
+            MpsseCmd::ClockNx8Bits as u8,
+            0,
+            2,
+            MpsseCmd::ClockUntilIOHigh as u8,
+            MpsseCmd::ClockUntilIOLow as u8,
+            MpsseCmd::ClockNx8BitsUntilIOHigh as u8,
+            0,
+            0,
+            MpsseCmd::ClockNx8BitsUntilIOLow as u8,
+            0x33,
+            0x12,
         ]
     );
     assert_eq!(DATA_READ_LEN, 13);
@@ -92,6 +121,24 @@ fn all_commands() {
     assert_eq!(TMS_INDEX, 12);
 }
 
+#[test]
+fn adaptive_data_clocking() {
+    mpsse! {
+        const DATA = {
+            enable_adaptive_data_clocking();
+            disable_adaptive_data_clocking();
+        };
+    }
+    assert_eq!(DATA, [0x96, 0x97]);
+    assert_eq!(
+        DATA,
+        MpsseCmdBuilder::new()
+            .enable_adaptive_data_clocking()
+            .disable_adaptive_data_clocking()
+            .as_slice()
+    );
+}
+
 #[test]
 fn all_commands_const() {
     mpsse! {
@@ -199,6 +246,26 @@ fn clock_data_assert_upper() {
     }
 }
 
+#[test]
+#[should_panic(expected = "clock length must be in 1..=8")]
+fn clock_cycles_bits_assert_upper() {
+    mpsse! {
+        let (_data, DATA_READ_LEN) = {
+            clock_cycles_bits(9);
+        };
+    }
+}
+
+#[test]
+#[should_panic(expected = "clock length must be in 1..=u16::MAX")]
+fn clock_cycles_bytes_assert_lower() {
+    mpsse! {
+        let (_data, DATA_READ_LEN) = {
+            clock_cycles_bytes(0);
+        };
+    }
+}
+
 #[test]
 #[should_panic(expected = "data length must be in 1..=8")]
 fn clock_bits_out_assert_lower() {
@@ -365,3 +432,113 @@ fn user_abstracted_macro() {
     assert_eq!(READ_LEN, 4);
     assert_eq!(COMMAND_42_RESULT_RANGE, 0..4);
 }
+
+const PAYLOAD: [u8; 4] = [0x11, 0x22, 0x33, 0x44];
+const ADDRESS: [u8; 2] = [0xAB, 0xCD];
+
+#[test]
+fn const_array_data() {
+    mpsse! {
+        const (DATA, DATA_READ_LEN) = {
+            set_gpio_lower(0x0, 0xb);
+            clock_data_out(ClockDataOut::MsbNeg, ADDRESS);
+            clock_data_out(ClockDataOut::MsbNeg, PAYLOAD);
+            const LOWER_INDEX = gpio_lower();
+            const DATA_RANGE = clock_data(ClockData::MsbPosIn, ADDRESS);
+            const LITERAL_RANGE = clock_data(ClockData::MsbPosIn, [0x55]);
+            set_gpio_lower(0x8, 0xb);
+        };
+    }
+    let cmd = MpsseCmdBuilder::new()
+        .set_gpio_lower(0x0, 0xb)
+        .clock_data_out(ClockDataOut::MsbNeg, &ADDRESS)
+        .clock_data_out(ClockDataOut::MsbNeg, &PAYLOAD)
+        .gpio_lower()
+        .clock_data(ClockData::MsbPosIn, &ADDRESS)
+        .clock_data(ClockData::MsbPosIn, &[0x55])
+        .set_gpio_lower(0x8, 0xb);
+    assert_eq!(DATA, cmd.as_slice());
+    assert_eq!(DATA_READ_LEN, 4);
+    assert_eq!(LOWER_INDEX, 0);
+    assert_eq!(DATA_RANGE, 1..3);
+    assert_eq!(LITERAL_RANGE, 3..4);
+}
+
+#[test]
+fn const_array_data_let() {
+    let state = 0x8;
+    mpsse! {
+        let data = {
+            clock_data_out(ClockDataOut::LsbNeg, PAYLOAD);
+            set_gpio_lower(state, 0xb);
+        };
+    }
+    assert_eq!(data, [0x19, 3, 0, 0x11, 0x22, 0x33, 0x44, 0x80, 0x8, 0xb]);
+}
+
+#[test]
+#[should_panic(expected = "data length must be in 1..=(u16::MAX + 1)")]
+fn const_array_data_assert_lower() {
+    const EMPTY: [u8; 0] = [];
+    mpsse! {
+        let _data = {
+            clock_data_out(ClockDataOut::MsbNeg, EMPTY);
+        };
+    }
+}
+
+const REPEATS: usize = 4;
+
+#[test]
+fn repeat() {
+    mpsse! {
+        const (DATA, DATA_READ_LEN) = {
+            gpio_lower();
+            repeat REPEATS {
+                set_gpio_lower(0x01, 0x0B);
+                const DATA_RANGE = clock_data(ClockData::MsbPosIn, ADDRESS);
+                repeat 2 {
+                    clock_data_out(ClockDataOut::MsbNeg, [0x42]);
+                    gpio_upper();
+                }
+            }
+            repeat 0 {
+                gpio_lower();
+            }
+            const UPPER_INDEX = gpio_upper();
+        };
+    }
+    let mut cmd = MpsseCmdBuilder::new().gpio_lower();
+    for _ in 0..REPEATS {
+        cmd = cmd
+            .set_gpio_lower(0x01, 0x0B)
+            .clock_data(ClockData::MsbPosIn, &ADDRESS);
+        for _ in 0..2 {
+            cmd = cmd
+                .clock_data_out(ClockDataOut::MsbNeg, &[0x42])
+                .gpio_upper();
+        }
+    }
+    let cmd = cmd.gpio_upper();
+    assert_eq!(DATA, cmd.as_slice());
+    assert_eq!(DATA_READ_LEN, cmd.read_len());
+    assert_eq!(DATA_READ_LEN, 1 + 4 * (2 + 2) + 1);
+    // first repetition
+    assert_eq!(DATA_RANGE, 1..3);
+    assert_eq!(UPPER_INDEX, 17);
+}
+
+#[test]
+fn repeat_let() {
+    let state = 0x08;
+    mpsse! {
+        let (data, DATA_READ_LEN) = {
+            repeat 3 {
+                set_gpio_lower(state, 0x0B);
+                clock_bits_in(ClockBitsIn::MsbPos, 1);
+            }
+        };
+    }
+    assert_eq!(data, [0x80, 0x08, 0x0B, 0x22, 0x00].repeat(3)[..]);
+    assert_eq!(DATA_READ_LEN, 3);
+}
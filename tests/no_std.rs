@@ -0,0 +1,58 @@
+//! The builder and the `mpsse!` macro without the std prelude.
+#![no_std]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+use ftdi_mpsse::{mpsse, ClockData, ClockDataOut, MpsseCmdBuilder};
+
+#[test]
+fn builder() {
+    let cmd = MpsseCmdBuilder::with_vec(Vec::new())
+        .set_gpio_lower(0x08, 0x0B)
+        .clock_data(ClockData::MsbPosIn, &[0x9F])
+        .send_immediate();
+    assert_eq!(
+        cmd.as_slice(),
+        [0x80, 0x08, 0x0B, 0x31, 0x00, 0x00, 0x9F, 0x87]
+    );
+    assert_eq!(cmd.read_len(), 1);
+}
+
+#[test]
+fn macro_const() {
+    const ID: [u8; 2] = [0x9F, 0x00];
+    mpsse! {
+        const (COMMAND_DATA, READ_LEN) = {
+            set_gpio_lower(0x00, 0x0B);
+            repeat 2 {
+                clock_data_out(ClockDataOut::MsbNeg, [0x06]);
+            }
+            const RANGE = clock_data(ClockData::MsbPosIn, ID);
+            set_gpio_lower(0x08, 0x0B);
+            send_immediate();
+        };
+    }
+    assert_eq!(
+        COMMAND_DATA,
+        [
+            0x80, 0x00, 0x0B, 0x11, 0x00, 0x00, 0x06, 0x11, 0x00, 0x00, 0x06, 0x31, 0x01, 0x00,
+            0x9F, 0x00, 0x80, 0x08, 0x0B, 0x87
+        ]
+    );
+    assert_eq!(READ_LEN, 2);
+    assert_eq!(RANGE, 0..2);
+}
+
+#[test]
+fn macro_let() {
+    mpsse! {
+        let (command_data, READ_LEN) = {
+            const RANGE = clock_data(ClockData::MsbPosIn, [0x12, 0x34]);
+            send_immediate();
+        };
+    }
+    assert_eq!(command_data, [0x31, 0x01, 0x00, 0x12, 0x34, 0x87]);
+    assert_eq!(READ_LEN, 2);
+    assert_eq!(RANGE, 0..2);
+}
@@ -0,0 +1,105 @@
+use ftdi_mpsse::*;
+
+#[test]
+fn onewire_reset() {
+    let (cmd, token) = MpsseCmdBuilder::new()
+        .gpio_upper()
+        .onewire_reset(4, 1_000_000);
+    assert_eq!(
+        cmd.as_slice(),
+        [
+            0x83, // GPIO read
+            0x80, 0x00, 0x10, // drive low
+            0x8F, 59, 0x00, // 480 clocks
+            0x80, 0x00, 0x00, // release
+            0x8F, 7, 0x00, 0x8E, 5,    // 70 clocks
+            0x81, // sample
+            0x8F, 50, 0x00, 0x8E, 1, // 410 clocks
+        ]
+    );
+    assert_eq!(token.range(), 1..2);
+}
+
+#[test]
+fn onewire_reset_rounds_up() {
+    // 480 µs at 30 MHz is 14400 clocks, 70 µs 2100 and 410 µs 12300
+    let (cmd, _) = MpsseCmdBuilder::new().onewire_reset(0, 30_000_000);
+    assert_eq!(&cmd.as_slice()[3..6], [0x8F, 0x07, 0x07]);
+    assert_eq!(&cmd.as_slice()[9..14], [0x8F, 0x05, 0x01, 0x8E, 3]);
+}
+
+#[test]
+fn onewire_write_bit() {
+    assert_eq!(
+        MpsseCmdBuilder::new()
+            .onewire_write_bit(4, true, 1_000_000)
+            .as_slice(),
+        [
+            0x80, 0x00, 0x10, // drive low
+            0x8E, 5, // 6 clocks
+            0x80, 0x00, 0x00, // release
+            0x8F, 7, 0x00, // 64 clocks
+        ]
+    );
+    assert_eq!(
+        MpsseCmdBuilder::new()
+            .onewire_write_bit(4, false, 1_000_000)
+            .as_slice(),
+        [
+            0x80, 0x00, 0x10, // drive low
+            0x8F, 6, 0x00, 0x8E, 3, // 60 clocks
+            0x80, 0x00, 0x00, // release
+            0x8F, 0, 0x00, 0x8E, 1, // 10 clocks
+        ]
+    );
+}
+
+#[test]
+fn onewire_read_bit() {
+    let (cmd, token) = MpsseCmdBuilder::new()
+        .gpio_lower()
+        .onewire_read_bit(2, 1_000_000);
+    assert_eq!(
+        cmd.as_slice(),
+        [
+            0x81, // GPIO read
+            0x80, 0x00, 0x04, // drive low
+            0x8E, 5, // 6 clocks
+            0x80, 0x00, 0x00, // release
+            0x8F, 0, 0x00, 0x8E, 0,    // 9 clocks
+            0x81, // sample
+            0x8F, 5, 0x00, 0x8E, 6, // 55 clocks
+        ]
+    );
+    assert_eq!(token.range(), 1..2);
+}
+
+#[test]
+fn onewire_bytes() {
+    let write_1 = MpsseCmdBuilder::new().onewire_write_bit(0, true, 1_000_000);
+    let write_0 = MpsseCmdBuilder::new().onewire_write_bit(0, false, 1_000_000);
+    let mut expected = Vec::new();
+    for &bit in [true, false, true, true, false, false, false, false].iter() {
+        let slot = if bit { &write_1 } else { &write_0 };
+        expected.extend_from_slice(slot.as_slice());
+    }
+    assert_eq!(
+        MpsseCmdBuilder::new()
+            .onewire_write_byte(0, 0x0D, 1_000_000)
+            .as_slice(),
+        expected.as_slice()
+    );
+
+    let (cmd, token) = MpsseCmdBuilder::new()
+        .gpio_upper()
+        .onewire_read_byte(0, 1_000_000);
+    assert_eq!(token.range(), 1..9);
+    assert_eq!(cmd.opcode_histogram()[1], (0x81, 8));
+}
+
+#[test]
+fn onewire_decode_byte() {
+    let samples = [0xFF, 0xFE, 0x01, 0x00, 0x00, 0x00, 0x00, 0x81];
+    assert_eq!(ftdi_mpsse::onewire_decode_byte(&samples, 0), 0x85);
+    assert_eq!(ftdi_mpsse::onewire_decode_byte(&samples, 7), 0x83);
+}
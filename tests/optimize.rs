@@ -0,0 +1,35 @@
+use ftdi_mpsse::*;
+
+#[test]
+fn dedup_gpio() {
+    let cmd = MpsseCmdBuilder::new()
+        .set_gpio_lower(0x08, 0x0B)
+        .set_gpio_lower(0x08, 0x0B)
+        .gpio_lower()
+        .send_immediate()
+        .set_gpio_lower(0x08, 0x0B)
+        // different direction
+        .set_gpio_lower(0x08, 0x09)
+        .set_gpio_upper(0x01, 0x01)
+        .set_gpio_upper(0x01, 0x01)
+        // clocking drives the lower byte
+        .clock_data_out(ClockDataOut::MsbNeg, &[0x80, 0x08, 0x09])
+        .set_gpio_lower(0x08, 0x09)
+        .set_gpio_upper(0x01, 0x01)
+        .dedup_gpio();
+    assert_eq!(
+        cmd.as_slice(),
+        [
+            0x80, 0x08, 0x0B, 0x81, 0x87, // first set kept
+            0x80, 0x08, 0x09, 0x82, 0x01, 0x01, // changes kept
+            0x11, 0x02, 0x00, 0x80, 0x08, 0x09, // data
+            0x80, 0x08, 0x09, 0x82, 0x01, 0x01, // sets after clocking kept
+        ]
+    );
+}
+
+#[test]
+fn dedup_gpio_truncated() {
+    let cmd = MpsseCmdBuilder::with_vec(vec![0x80, 0x00, 0x01, 0x80, 0x00, 0x01, 0x80, 0x00]);
+    assert_eq!(cmd.dedup_gpio().as_slice(), [0x80, 0x00, 0x01, 0x80, 0x00]);
+}
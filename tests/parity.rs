@@ -0,0 +1,47 @@
+use ftdi_mpsse::*;
+
+#[test]
+fn clock_data_out_with_parity_msb() {
+    // bits clocked out: 101, two ones
+    let cmd =
+        MpsseCmdBuilder::new().clock_data_out_with_parity(ClockBitsOut::MsbNeg, 0xBF, 3, true);
+    assert_eq!(cmd.as_slice(), [0x13, 0x02, 0xBF, 0x13, 0x00, 0x00]);
+    let cmd =
+        MpsseCmdBuilder::new().clock_data_out_with_parity(ClockBitsOut::MsbPos, 0xBF, 3, false);
+    assert_eq!(cmd.as_slice(), [0x12, 0x02, 0xBF, 0x12, 0x00, 0x80]);
+}
+
+#[test]
+fn clock_data_out_with_parity_lsb() {
+    // bits clocked out: 0001, one one
+    let cmd =
+        MpsseCmdBuilder::new().clock_data_out_with_parity(ClockBitsOut::LsbPos, 0xF1, 4, true);
+    assert_eq!(cmd.as_slice(), [0x1A, 0x03, 0xF1, 0x1A, 0x00, 0x01]);
+    let cmd =
+        MpsseCmdBuilder::new().clock_data_out_with_parity(ClockBitsOut::LsbPos, 0xFF, 8, false);
+    assert_eq!(cmd.as_slice(), [0x1A, 0x07, 0xFF, 0x1A, 0x00, 0x01]);
+}
+
+#[test]
+fn clock_data_out_with_parity_no_data() {
+    let cmd =
+        MpsseCmdBuilder::new().clock_data_out_with_parity(ClockBitsOut::MsbNeg, 0xFF, 0, false);
+    assert_eq!(cmd.as_slice(), [0x13, 0x00, 0x80]);
+}
+
+#[test]
+fn check_parity_round_trip() {
+    for data in 0..=255u8 {
+        for &even in [true, false].iter() {
+            let cmd = MpsseCmdBuilder::new().clock_data_out_with_parity(
+                ClockBitsOut::LsbNeg,
+                data,
+                8,
+                even,
+            );
+            let parity = cmd.as_slice()[5] & 0x01 != 0;
+            assert!(check_parity(data, parity, even));
+            assert!(!check_parity(data, !parity, even));
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use ftdi_mpsse::*;
+
+mod common;
+
+use common::Scripted;
+
+#[test]
+fn retries_after_resync() {
+    // spurious echo shifting the trailing echo, stale trailing echo, resync
+    // marker echo, good response
+    let inner = Scripted::with_responses(&[
+        0xFA, 0xA0, 0x12, 0x34, 0xFA, 0xAA, 0xFA, 0xAB, 0x12, 0x34, 0xFA, 0xAA,
+    ]);
+    let mut exec = RetryingExecutor::new(inner, 2, true);
+    let mut buf = [0; 2];
+    exec.xfer(&[0x81, 0x83], &mut buf).unwrap();
+    assert_eq!(buf, [0x12, 0x34]);
+    assert_eq!(
+        exec.into_inner().sent,
+        vec![
+            vec![0x81, 0x83, 0xAA, 0x87],
+            vec![0xAB, 0x87],
+            vec![0x81, 0x83, 0xAA, 0x87]
+        ]
+    );
+}
+
+#[test]
+fn retries_bad_command_echo_in_response() {
+    // echo of a dropped command within the response, good response
+    let inner = Scripted::with_responses(&[0xFA, 0xAA, 0xFA, 0xAA, 0x12, 0x34, 0xFA, 0xAA]);
+    let mut exec = RetryingExecutor::new(inner, 2, false);
+    let mut buf = [0; 2];
+    exec.xfer(&[0x20, 0x01, 0x00, 0x87], &mut buf).unwrap();
+    assert_eq!(buf, [0x12, 0x34]);
+    assert_eq!(exec.inner().sent.len(), 2);
+    assert_eq!(exec.inner().purges, 1);
+}
+
+#[test]
+fn retries_after_executor_error() {
+    let mut inner = Scripted::with_responses(&[0x12, 0x34, 0xFA, 0xAA]);
+    inner.failures = 2;
+    let mut exec = RetryingExecutor::new(inner, 2, false);
+    let mut buf = [0; 2];
+    exec.xfer(&[0x81, 0x83], &mut buf).unwrap();
+    assert_eq!(buf, [0x12, 0x34]);
+    assert_eq!(exec.inner().sent.len(), 3);
+    assert_eq!(exec.inner().purges, 2);
+}
+
+#[test]
+fn retries_exhausted() {
+    let inner = Scripted::with_responses(&[0xFA, 0xAA, 0x00, 0x00, 0xFA, 0xAA, 0x00, 0x00]);
+    let mut exec = RetryingExecutor::new(inner, 1, false);
+    let mut buf = [0; 2];
+    assert_eq!(
+        exec.xfer(&[0x81, 0x83], &mut buf),
+        Err(RetryError::RetriesExhausted { attempts: 2 })
+    );
+}
+
+#[test]
+fn last_executor_error() {
+    let mut exec = RetryingExecutor::new(Scripted::default(), 3, false);
+    let mut buf = [0; 1];
+    assert_eq!(
+        exec.xfer(&[0x81], &mut buf),
+        Err(RetryError::Executor("timeout"))
+    );
+    assert_eq!(exec.inner().sent.len(), 4);
+}
+
+#[test]
+fn passes_through_resync_errors() {
+    let mut exec = RetryingExecutor::new(Scripted::default(), 3, true);
+    let mut buf = [0; 1];
+    assert_eq!(
+        exec.xfer(&[0x81], &mut buf),
+        Err(RetryError::Executor("timeout"))
+    );
+}
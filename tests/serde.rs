@@ -0,0 +1,56 @@
+#![cfg(feature = "serde")]
+use ftdi_mpsse::MpsseSettings;
+use std::time::Duration;
+
+#[test]
+fn settings_round_trip() {
+    let settings = MpsseSettings {
+        reset: false,
+        read_timeout: Duration::from_millis(250),
+        mask: 0x0B,
+        clock_frequency: Some(1_000_000),
+        ..MpsseSettings::default()
+    };
+    let json = serde_json::to_string(&settings).unwrap();
+    assert_eq!(
+        json,
+        "{\"reset\":false,\"in_transfer_size\":4096,\"read_timeout\":250,\
+         \"write_timeout\":1000,\"latency_timer\":16,\"mask\":11,\
+         \"clock_frequency\":1000000}"
+    );
+    assert_eq!(
+        serde_json::from_str::<MpsseSettings>(&json).unwrap(),
+        settings
+    );
+}
+
+#[test]
+fn settings_missing_fields() {
+    let settings: MpsseSettings = serde_json::from_str("{\"latency_timer\":2,\"mask\":3}").unwrap();
+    assert_eq!(
+        settings,
+        MpsseSettings {
+            latency_timer: Duration::from_millis(2),
+            mask: 0x03,
+            ..MpsseSettings::default()
+        }
+    );
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn trace_init_round_trip() {
+    use ftdi_mpsse::{Trace, TraceEvent, TraceOp};
+
+    let trace = Trace {
+        events: vec![TraceEvent {
+            elapsed: Duration::from_millis(1),
+            op: TraceOp::Init(MpsseSettings {
+                mask: 0x0B,
+                ..MpsseSettings::default()
+            }),
+        }],
+    };
+    let json = serde_json::to_string(&trace).unwrap();
+    assert_eq!(serde_json::from_str::<Trace>(&json).unwrap(), trace);
+}
@@ -0,0 +1,363 @@
+use ftdi_mpsse::*;
+
+/// Idle level of SCK, data out and data in opcodes for each mode.
+const MODES: [(SpiMode, u8, ClockDataOut, ClockDataIn, ClockData); 4] = [
+    (
+        SpiMode::Mode0,
+        0x00,
+        ClockDataOut::MsbNeg,
+        ClockDataIn::MsbPos,
+        ClockData::MsbPosIn,
+    ),
+    (
+        SpiMode::Mode1,
+        0x00,
+        ClockDataOut::MsbPos,
+        ClockDataIn::MsbNeg,
+        ClockData::MsbNegIn,
+    ),
+    (
+        SpiMode::Mode2,
+        0x01,
+        ClockDataOut::MsbPos,
+        ClockDataIn::MsbNeg,
+        ClockData::MsbNegIn,
+    ),
+    (
+        SpiMode::Mode3,
+        0x01,
+        ClockDataOut::MsbNeg,
+        ClockDataIn::MsbPos,
+        ClockData::MsbPosIn,
+    ),
+];
+
+#[test]
+fn spi_device_modes() {
+    for &(mode, idle, out, in_, both) in MODES.iter() {
+        let dev = SpiDevice::new(3, mode);
+        assert_eq!(dev.mode(), mode);
+        assert_eq!(mode.idle_clock_high(), idle == 0x01);
+        assert_eq!(mode.data_out(), out, "{:?}", mode);
+        assert_eq!(mode.data_in(), in_, "{:?}", mode);
+        assert_eq!(mode.data(), both, "{:?}", mode);
+
+        let cmd = dev.init(MpsseCmdBuilder::new());
+        assert_eq!(cmd.as_slice(), [0x80, 0x08 | idle, 0x0B], "{:?}", mode);
+
+        let (cmd, token) = dev.transfer(MpsseCmdBuilder::new(), &[0xA5]);
+        assert_eq!(
+            cmd.as_slice(),
+            [
+                0x80,
+                idle,
+                0x0B,
+                both.into(),
+                0x00,
+                0x00,
+                0xA5,
+                0x80,
+                0x08 | idle,
+                0x0B
+            ],
+            "{:?}",
+            mode
+        );
+        assert_eq!(token.range(), 0..1);
+
+        let cmd = dev.write(MpsseCmdBuilder::new(), &[0xA5]);
+        assert_eq!(cmd.as_slice()[3], u8::from(out), "{:?}", mode);
+
+        let (cmd, token) = dev.read(MpsseCmdBuilder::new().gpio_lower(), 2);
+        assert_eq!(cmd.as_slice()[4], u8::from(in_), "{:?}", mode);
+        assert_eq!(token.range(), 1..3);
+    }
+}
+
+#[test]
+fn spi_mode3_init() {
+    assert_eq!(
+        MpsseCmdBuilder::new().spi_mode3_init(4).as_slice(),
+        [0x80, 0x11, 0x13]
+    );
+}
+
+#[test]
+#[should_panic(expected = "pin number cannot exceed 7")]
+fn spi_device_bad_pin() {
+    SpiDevice::new(8, SpiMode::Mode0);
+}
+
+#[test]
+fn spi_burst_read() {
+    let (cmd, token) = MpsseCmdBuilder::new().gpio_lower().spi_burst_read(
+        4,
+        ClockDataOut::MsbNeg,
+        ClockDataIn::MsbPos,
+        0x28,
+        6,
+        0x40,
+    );
+    assert_eq!(
+        cmd.as_slice(),
+        [
+            0x81, // GPIO read
+            0x80, 0x00, 0x13, // CS low
+            0x11, 0x00, 0x00, 0x68, // address with auto-increment
+            0x20, 0x05, 0x00, // 6 bytes in
+            0x80, 0x10, 0x13, // CS high
+        ]
+    );
+    assert_eq!(token.range(), 1..7);
+}
+
+#[test]
+fn spi_burst_read_empty() {
+    let (cmd, token) = MpsseCmdBuilder::new().spi_burst_read(
+        3,
+        ClockDataOut::MsbNeg,
+        ClockDataIn::MsbPos,
+        0x00,
+        0,
+        0x80,
+    );
+    assert_eq!(cmd.as_slice().len(), 10);
+    assert!(token.is_empty());
+}
+
+#[test]
+fn spi_transfer_flow_controlled() {
+    let (cmd, token) = MpsseCmdBuilder::new()
+        .gpio_lower()
+        .spi_transfer_flow_controlled(3, true, ClockData::MsbNegIn, &[0x12, 0x34]);
+    assert_eq!(
+        cmd.as_slice(),
+        [
+            0x81, // GPIO read
+            0x80, 0x00, 0x0B, // CS low
+            0x88, // wait for READY
+            0x34, 0x01, 0x00, 0x12, 0x34, // transfer
+            0x80, 0x08, 0x0B, // CS high
+        ]
+    );
+    assert_eq!(token.range(), 1..3);
+}
+
+#[test]
+#[should_panic(expected = "READY pin must be wired to GPIOL1")]
+fn spi_transfer_flow_controlled_not_gpiol1() {
+    let _ =
+        MpsseCmdBuilder::new().spi_transfer_flow_controlled(3, false, ClockData::MsbPosIn, &[0x00]);
+}
+
+#[test]
+#[should_panic(expected = "chip select cannot be on GPIOL1")]
+fn spi_transfer_flow_controlled_cs_on_gpiol1() {
+    let _ =
+        MpsseCmdBuilder::new().spi_transfer_flow_controlled(5, true, ClockData::MsbPosIn, &[0x00]);
+}
+
+#[test]
+fn probe_miso_idle() {
+    let (cmd, token) = MpsseCmdBuilder::new()
+        .gpio_upper()
+        .probe_miso_idle(2, &[3, 6]);
+    let mut expected = vec![0x83, 0x80, 0x48, 0x4B];
+    expected.extend_from_slice(&[0x81; 16]);
+    assert_eq!(cmd.as_slice(), expected.as_slice());
+    assert_eq!(token.range(), 1..17);
+
+    // MISO on a pin driven by default
+    let (cmd, _) = MpsseCmdBuilder::new().probe_miso_idle(1, &[3]);
+    assert_eq!(&cmd.as_slice()[..3], [0x80, 0x08, 0x09]);
+}
+
+#[test]
+fn miso_idle_from_readings() {
+    assert_eq!(MisoIdle::from_readings(&[0x04; 16], 2), MisoIdle::PullUp);
+    assert_eq!(MisoIdle::from_readings(&[0xFB; 16], 2), MisoIdle::PullDown);
+    assert_eq!(
+        MisoIdle::from_readings(&[0x04, 0x04, 0x00, 0x04], 2),
+        MisoIdle::Floating
+    );
+}
+
+#[test]
+fn spi_adc_read() {
+    let (cmd, token) =
+        MpsseCmdBuilder::new()
+            .gpio_lower()
+            .spi_adc_read(4, 3, ClockDataIn::MsbNeg, 3, 20);
+    assert_eq!(
+        cmd.as_slice(),
+        [
+            0x81, // GPIO read
+            0x80, 0x18, 0x1B, // CONVST high
+            0x80, 0x08, 0x1B, // CONVST low
+            0x8F, 0x01, 0x00, 0x8E, 3, // 20 clocks
+            0x80, 0x00, 0x1B, // CS low
+            0x24, 0x02, 0x00, // 3 bytes in
+            0x80, 0x08, 0x1B, // CS high
+        ]
+    );
+    assert_eq!(token.range(), 1..4);
+}
+
+#[test]
+fn demux_cs_select() {
+    let demux = DemuxCs::new(5, 3, 4);
+    let addresses: Vec<u8> = (0..8)
+        .map(|device| demux.select(MpsseCmdBuilder::new(), device).as_slice()[1])
+        .collect();
+    assert_eq!(addresses, [0x00, 0x20, 0x08, 0x28, 0x10, 0x30, 0x18, 0x38]);
+    assert_eq!(
+        demux.select(MpsseCmdBuilder::new(), 0).as_slice(),
+        [0x80, 0x00, 0x3B]
+    );
+}
+
+#[test]
+#[should_panic(expected = "device number cannot exceed 7")]
+fn demux_cs_bad_device() {
+    let _ = DemuxCs::new(3, 4, 5).select(MpsseCmdBuilder::new(), 8);
+}
+
+#[test]
+fn spi_device_demux() {
+    let demux = DemuxCs::new(3, 4, 5).with_enable(6);
+    let dev = SpiDevice::with_demux(demux, 6, SpiMode::Mode3);
+    let (cmd, token) = dev.transfer(MpsseCmdBuilder::new(), &[0xA5]);
+    assert_eq!(
+        cmd.as_slice(),
+        [
+            0x80, 0x31, 0x7B, // select device 6
+            0x31, 0x00, 0x00, 0xA5, // transfer
+            0x80, 0x71, 0x7B, // disable
+        ]
+    );
+    assert_eq!(token.range(), 0..1);
+
+    // without enable the device stays selected
+    let dev = SpiDevice::with_demux(DemuxCs::new(3, 4, 5), 1, SpiMode::Mode0);
+    assert_eq!(
+        dev.init(MpsseCmdBuilder::new()).as_slice(),
+        [0x80, 0x08, 0x3B]
+    );
+}
+
+#[test]
+fn spi_builder() {
+    let dev = SpiDevice::new(4, SpiMode::Mode3);
+    let (spi, status) = SpiBuilder::with_cmd(dev, MpsseCmdBuilder::new().gpio_lower())
+        .cs_assert()
+        .write(&[0x05])
+        .read(1);
+    let (spi, echo) = spi.transfer(&[0xA5, 0x5A]);
+    let cmd = spi.cs_deassert().cs_assert().cs_deassert().build();
+    assert_eq!(
+        cmd.as_slice(),
+        [
+            0x81, // read
+            0x80, 0x11, 0x13, // init
+            0x80, 0x01, 0x13, // assert
+            0x11, 0x00, 0x00, 0x05, // write
+            0x20, 0x00, 0x00, // read
+            0x31, 0x01, 0x00, 0xA5, 0x5A, // transfer
+            0x80, 0x11, 0x13, // deassert
+            0x80, 0x01, 0x13, 0x80, 0x11, 0x13,
+        ]
+    );
+    assert_eq!(status.range(), 1..2);
+    assert_eq!(echo.range(), 2..4);
+}
+
+#[test]
+fn spi_165_read() {
+    let (cmd, token) = MpsseCmdBuilder::new()
+        .gpio_lower()
+        .spi_165_read(7, ClockDataIn::MsbPos, 3);
+    assert_eq!(
+        cmd.as_slice(),
+        [
+            0x81, // read
+            0x80, 0x80, 0x83, // idle
+            0x80, 0x00, 0x83, // load
+            0x80, 0x80, 0x83, // shift
+            0x20, 0x02, 0x00, // inputs
+        ]
+    );
+    assert_eq!(token.range(), 1..4);
+}
+
+#[test]
+fn clock_data_padded() {
+    let (cmd, token) = MpsseCmdBuilder::new().gpio_lower().clock_data_padded(
+        ClockData::LsbNegIn,
+        &[0x01, 0x02, 0x03],
+        2,
+    );
+    assert_eq!(cmd.as_slice(), [0x81, 0x3C, 0x02, 0x00, 0x01, 0x02, 0x03]);
+    assert_eq!(token.range(), 1..4);
+
+    let (cmd, token) = MpsseCmdBuilder::new().clock_data_padded(ClockData::MsbPosIn, &[], 2);
+    assert_eq!(
+        cmd.as_slice(),
+        [0x31, 0x01, 0x00, SPI_PAD_BYTE, SPI_PAD_BYTE]
+    );
+    assert_eq!(token.range(), 0..2);
+}
+
+#[test]
+fn spi_transaction() {
+    for (mode, idle, out, in_, both) in MODES {
+        let (spi, data) = SpiTransaction::new(0x08, false, mode)
+            .write(&[0x0B])
+            .read(2);
+        let (spi, echo) = spi.transfer(&[0x55]);
+        let cmd = spi.finish();
+        assert_eq!(
+            cmd.as_slice(),
+            [
+                0x80,
+                0x08 | idle,
+                0x0B,
+                0x80,
+                idle,
+                0x0B,
+                out.into(),
+                0x00,
+                0x00,
+                0x0B,
+                in_.into(),
+                0x01,
+                0x00,
+                both.into(),
+                0x00,
+                0x00,
+                0x55,
+                0x80,
+                0x08 | idle,
+                0x0B,
+            ]
+        );
+        assert_eq!((data.offset(), data.len()), (0, 2));
+        assert_eq!((echo.offset(), echo.len()), (2, 1));
+    }
+}
+
+#[test]
+fn spi_transaction_active_high() {
+    let cmd = SpiTransaction::new(0x30, true, SpiMode::Mode3)
+        .write(&[0x06])
+        .finish();
+    assert_eq!(
+        cmd.as_slice(),
+        [0x80, 0x01, 0x33, 0x80, 0x31, 0x33, 0x11, 0x00, 0x00, 0x06, 0x80, 0x01, 0x33]
+    );
+}
+
+#[test]
+#[should_panic(expected = "chip select cannot use the SCK, MOSI or MISO pins")]
+fn spi_transaction_cs_on_sck() {
+    SpiTransaction::new(0x01, false, SpiMode::Mode0);
+}
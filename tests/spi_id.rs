@@ -0,0 +1,67 @@
+use ftdi_mpsse::*;
+
+mod common;
+use common::Scripted;
+
+#[test]
+fn verify_spi_id_match() {
+    let mut exec = Scripted::with_responses(&[0xFF, 0xEF, 0x40, 0x18]);
+    verify_spi_id(
+        &mut exec,
+        &SpiDevice::new(3, SpiMode::Mode0),
+        &[0x9F],
+        &[0xEF, 0x40, 0x18],
+    )
+    .unwrap();
+    let expected = MpsseCmdBuilder::new()
+        .set_gpio_lower(0x08, 0x0B)
+        .set_gpio_lower(0x00, 0x0B)
+        .clock_data(ClockData::MsbPosIn, &[0x9F, 0xFF, 0xFF, 0xFF])
+        .set_gpio_lower(0x08, 0x0B)
+        .send_immediate();
+    assert_eq!(exec.sent, [expected.as_slice()]);
+}
+
+#[test]
+fn verify_spi_id_mismatch() {
+    let mut exec = Scripted::with_responses(&[0xFF; 4]);
+    let err = verify_spi_id(
+        &mut exec,
+        &SpiDevice::new(4, SpiMode::Mode3),
+        &[0x9F],
+        &[0xEF, 0x40, 0x18],
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        SpiIdError::MismatchedId {
+            expected: vec![0xEF, 0x40, 0x18],
+            found: vec![0xFF; 3],
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "expected device ID [EF, 40, 18], found [FF, FF, FF]"
+    );
+    // SCK idles high in mode 3
+    let expected = MpsseCmdBuilder::new()
+        .set_gpio_lower(0x11, 0x13)
+        .set_gpio_lower(0x01, 0x13)
+        .clock_data(ClockData::MsbPosIn, &[0x9F, 0xFF, 0xFF, 0xFF])
+        .set_gpio_lower(0x11, 0x13)
+        .send_immediate();
+    assert_eq!(exec.sent, [expected.as_slice()]);
+}
+
+#[test]
+fn verify_spi_id_executor_error() {
+    let mut exec = Scripted::default();
+    let err = verify_spi_id(
+        &mut exec,
+        &SpiDevice::new(3, SpiMode::Mode0),
+        &[0x9F],
+        &[0xEF],
+    )
+    .unwrap_err();
+    assert_eq!(err, SpiIdError::Executor("timeout"));
+}
@@ -0,0 +1,52 @@
+use ftdi_mpsse::*;
+
+#[test]
+fn capture_state_shadow() {
+    let cmd = MpsseCmdBuilder::new()
+        .enable_loopback()
+        .set_clock(0x1234, Some(true))
+        .enable_3phase_data_clocking()
+        .disable_adaptive_data_clocking()
+        .enable_drive_only_zero(0x06, 0x00)
+        .set_gpio_upper(0x01, 0x03)
+        .set_gpio_lower(0x00, 0x0B)
+        .disable_loopback()
+        // data that looks like commands
+        .clock_data_out(ClockDataOut::MsbNeg, &[0x84, 0x8C, 0x80, 0x00, 0xFF]);
+    let (cmd, token) = cmd.gpio_lower().capture_state();
+    assert_eq!(cmd.as_slice()[cmd.as_slice().len() - 2..], [0x81, 0x83]);
+
+    let state = token.decode(&[0xAA, 0xF1, 0xFD]);
+    assert_eq!(
+        state,
+        DeviceState {
+            lower: 0xF1,
+            upper: 0xFD,
+            lower_direction: Some(0x0B),
+            upper_direction: Some(0x03),
+            clock_divisor: Some(0x1234),
+            clock_divide: Some(true),
+            three_phase: Some(true),
+            adaptive: Some(false),
+            loopback: Some(false),
+            drive_only_zero: Some((0x06, 0x00)),
+        }
+    );
+
+    assert_eq!(
+        MpsseCmdBuilder::new().restore(&state).as_slice(),
+        [
+            0x85, 0x8B, 0x8C, 0x97, 0x9E, 0x06, 0x00, 0x86, 0x34, 0x12, 0x80, 0x01, 0x0B, 0x82,
+            0x01, 0x03
+        ]
+    );
+}
+
+#[test]
+fn restore_unknown_settings() {
+    let (_, token) = MpsseCmdBuilder::new().capture_state();
+    let state = token.decode(&[0x12, 0x34]);
+    assert_eq!(state.lower, 0x12);
+    assert_eq!(state.upper, 0x34);
+    assert!(MpsseCmdBuilder::new().restore(&state).as_slice().is_empty());
+}
@@ -0,0 +1,70 @@
+use ftdi_mpsse::*;
+
+fn states(cmd: &MpsseCmdBuilder) -> Vec<u8> {
+    cmd.as_slice()
+        .chunks(3)
+        .filter(|chunk| chunk[0] == 0x80)
+        .map(|chunk| chunk[1])
+        .collect()
+}
+
+#[test]
+fn sequences() {
+    for mode in [StepMode::Wave, StepMode::Full, StepMode::Half].iter() {
+        let sequence = mode.sequence();
+        // adjacent positions differ by a single coil in half steps
+        for (i, coils) in sequence.iter().enumerate() {
+            let next = sequence[(i + 1) % sequence.len()];
+            let changed = (coils ^ next).count_ones();
+            match mode {
+                StepMode::Half => assert_eq!(changed, 1),
+                _ => assert_eq!(changed, 2),
+            }
+        }
+    }
+}
+
+#[test]
+fn full_steps() {
+    let mut stepper = Stepper::new([0, 1, 2, 3], StepMode::Full);
+    assert_eq!(stepper.mode(), StepMode::Full);
+    let cmd = stepper.steps(MpsseCmdBuilder::new(), 5, 16);
+    assert_eq!(
+        cmd.as_slice()[..10],
+        [0x80, 0x06, 0x0F, 0x8F, 0x01, 0x00, 0x80, 0x0C, 0x0F, 0x8F]
+    );
+    assert_eq!(states(&cmd), [0x06, 0x0C, 0x09, 0x03, 0x06]);
+    assert_eq!(stepper.position(), 5);
+
+    let cmd = stepper.steps(MpsseCmdBuilder::new(), -6, 16);
+    assert_eq!(states(&cmd), [0x03, 0x09, 0x0C, 0x06, 0x03, 0x09]);
+    assert_eq!(stepper.position(), -1);
+    assert_eq!(
+        stepper.hold(MpsseCmdBuilder::new()).as_slice(),
+        [0x80, 0x09, 0x0F]
+    );
+    assert_eq!(
+        stepper.release(MpsseCmdBuilder::new()).as_slice(),
+        [0x80, 0x00, 0x0F]
+    );
+}
+
+#[test]
+fn half_steps_pin_order() {
+    let mut stepper = Stepper::new([7, 5, 6, 4], StepMode::Half);
+    let cmd = stepper.steps(MpsseCmdBuilder::new(), 3, 0);
+    assert_eq!(
+        cmd.as_slice(),
+        [0x80, 0xA0, 0xF0, 0x80, 0x20, 0xF0, 0x80, 0x60, 0xF0]
+    );
+    assert!(stepper
+        .steps(MpsseCmdBuilder::new(), 0, 8)
+        .as_slice()
+        .is_empty());
+}
+
+#[test]
+fn step_clocks() {
+    assert_eq!(Stepper::step_clocks(200, 1_000_000), 5000);
+    assert_eq!(Stepper::step_clocks(3, 1_000_000), 333_334);
+}
@@ -0,0 +1,86 @@
+use ftdi_mpsse::*;
+
+#[test]
+fn read_transaction() {
+    let (cmd, ack) = MpsseCmdBuilder::new()
+        .swd_request(false, true, 0x0)
+        .swd_read_ack();
+    let (cmd, data) = cmd.swd_read_data();
+    assert_eq!(
+        cmd.as_slice(),
+        [
+            ClockBitsOut::LsbNeg as u8,
+            7,
+            0xA5,
+            MpsseCmd::SetDataBitsLowbyte as u8,
+            0x02,
+            0x01,
+            ClockBitsIn::LsbPos as u8,
+            3,
+            ClockDataIn::LsbPos as u8,
+            3,
+            0,
+            ClockBitsIn::LsbPos as u8,
+            0,
+            ClockBitsOut::LsbNeg as u8,
+            0,
+            0,
+            MpsseCmd::SetDataBitsLowbyte as u8,
+            0x02,
+            0x03,
+        ]
+    );
+    assert_eq!(ack, ReadToken::new(0, 1));
+    assert_eq!(data, ReadToken::new(1, 5));
+}
+
+#[test]
+fn write_transaction() {
+    let (cmd, ack) = MpsseCmdBuilder::new()
+        .swd_request(true, false, 0xC)
+        .swd_read_ack();
+    let cmd = cmd.swd_write_data(0x0000_0007);
+    assert_eq!(
+        cmd.as_slice(),
+        [
+            ClockBitsOut::LsbNeg as u8,
+            7,
+            0xBB,
+            MpsseCmd::SetDataBitsLowbyte as u8,
+            0x02,
+            0x01,
+            ClockBitsIn::LsbPos as u8,
+            3,
+            ClockBitsOut::LsbNeg as u8,
+            0,
+            0,
+            MpsseCmd::SetDataBitsLowbyte as u8,
+            0x02,
+            0x03,
+            ClockDataOut::LsbNeg as u8,
+            3,
+            0,
+            0x07,
+            0x00,
+            0x00,
+            0x00,
+            ClockBitsOut::LsbNeg as u8,
+            0,
+            1,
+        ]
+    );
+    assert_eq!(ack, ReadToken::new(0, 1));
+}
+
+#[test]
+fn decode_responses() {
+    assert_eq!(SwdAck::from_response(0x30), Some(SwdAck::Ok));
+    assert_eq!(SwdAck::from_response(0x40), Some(SwdAck::Wait));
+    assert_eq!(SwdAck::from_response(0x80), Some(SwdAck::Fault));
+    assert_eq!(SwdAck::from_response(0xE0), None);
+    assert_eq!(
+        swd_decode_data(&[0x77, 0x14, 0xA0, 0x2B, 0x00]),
+        Some(0x2BA0_1477)
+    );
+    assert_eq!(swd_decode_data(&[0x77, 0x14, 0xA0, 0x2B, 0x80]), None);
+}
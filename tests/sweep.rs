@@ -0,0 +1,46 @@
+mod common;
+
+use common::Scripted;
+use ftdi_mpsse::*;
+
+#[test]
+fn spi_clock_sweep_results() {
+    let mut exec = Scripted::with_responses(&[0x55, 0xAA, 0x55, 0xAA, 0x55, 0xAB]);
+    let results = spi_clock_sweep(
+        &mut exec,
+        60_000_000,
+        &[1_000_000, 7_000_000, 30_000_000],
+        &[0x55, 0xAA],
+    )
+    .unwrap();
+    assert_eq!(
+        results,
+        [(1_000_000, true), (7_000_000, true), (30_000_000, false)]
+    );
+    let divisors: Vec<&[u8]> = exec.sent.iter().map(|sent| &sent[..3]).collect();
+    assert_eq!(
+        divisors,
+        [[0x86, 29, 0x00], [0x86, 4, 0x00], [0x86, 0, 0x00]]
+    );
+    assert_eq!(
+        exec.sent[0][3..],
+        [0x84, 0x31, 0x01, 0x00, 0x55, 0xAA, 0x85, 0x87]
+    );
+}
+
+#[test]
+fn spi_clock_sweep_slowest() {
+    let mut exec = Scripted::with_responses(&[0x00]);
+    let results = spi_clock_sweep(&mut exec, 12_000_000, &[10], &[0x00]).unwrap();
+    assert_eq!(results, [(10, true)]);
+    assert_eq!(exec.sent[0][..3], [0x86, 0xFF, 0xFF]);
+}
+
+#[test]
+fn spi_clock_sweep_error() {
+    let mut exec = Scripted::default();
+    assert_eq!(
+        spi_clock_sweep(&mut exec, 60_000_000, &[1_000_000], &[0x00]),
+        Err("timeout")
+    );
+}
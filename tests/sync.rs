@@ -0,0 +1,74 @@
+use ftdi_mpsse::*;
+
+#[test]
+fn sync_marker() {
+    let (cmd, marker) = MpsseCmdBuilder::new()
+        .clock_data_in(ClockDataIn::MsbPos, 3)
+        .sync_marker();
+    assert_eq!(cmd.as_slice(), [0x20, 0x02, 0x00, 0xAB]);
+    assert_eq!(marker.range(), 3..5);
+}
+
+#[test]
+fn sync_bad_command() {
+    let cmd = MpsseCmdBuilder::new().sync_bad_command().send_immediate();
+    assert_eq!(cmd.as_slice(), [0xAA, 0x87]);
+    assert_eq!(cmd.read_len(), 2);
+
+    assert_eq!(verify_sync_response(&[0xFA, 0xAA]), Ok(()));
+    assert_eq!(
+        verify_sync_response(&[0xFA, 0xAB]),
+        Err(SyncError::UnexpectedResponse {
+            found: [0xFA, 0xAB]
+        })
+    );
+    assert_eq!(
+        verify_sync_response(&[0x00, 0xFA, 0xAA]),
+        Err(SyncError::WrongLength { len: 3 })
+    );
+    assert_eq!(
+        verify_sync_response(&[]).unwrap_err().to_string(),
+        "expected a 2 byte bad command echo, got 0 bytes"
+    );
+}
+
+#[test]
+fn clock_data_out_with_sync() {
+    let data: Vec<u8> = (0..5).collect();
+    let (cmd, markers) = MpsseCmdBuilder::new()
+        .gpio_lower()
+        .clock_data_out_with_sync(ClockDataOut::LsbNeg, &data, 2);
+    assert_eq!(
+        cmd.as_slice(),
+        [
+            0x81, // read
+            0x19, 0x01, 0x00, 0x00, 0x01, 0xAB, // first chunk
+            0x19, 0x01, 0x00, 0x02, 0x03, 0xAB, // second chunk
+            0x19, 0x00, 0x00, 0x04, 0xAB, // last chunk
+        ]
+    );
+    let ranges: Vec<_> = markers.iter().map(|marker| marker.range()).collect();
+    assert_eq!(ranges, [1..3, 3..5, 5..7]);
+    assert_eq!(cmd.expected_loopback_response().len(), 7);
+}
+
+#[test]
+fn clock_data_out_with_sync_long_interval() {
+    let (cmd, markers) = MpsseCmdBuilder::new().clock_data_out_with_sync(
+        ClockDataOut::MsbNeg,
+        &[0x00; 65537],
+        70000,
+    );
+    assert_eq!(markers.len(), 1);
+    assert_eq!(cmd.as_slice().len(), 3 + 65536 + 3 + 1 + 1);
+    assert!(MpsseCmdBuilder::new()
+        .clock_data_out_with_sync(ClockDataOut::MsbNeg, &[], 1)
+        .1
+        .is_empty());
+}
+
+#[test]
+#[should_panic(expected = "marker interval cannot be zero")]
+fn clock_data_out_with_sync_zero_interval() {
+    let _ = MpsseCmdBuilder::new().clock_data_out_with_sync(ClockDataOut::MsbNeg, &[0x00], 0);
+}
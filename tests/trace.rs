@@ -0,0 +1,68 @@
+#![cfg(feature = "std")]
+use ftdi_mpsse::*;
+
+mod common;
+
+use common::Scripted;
+
+fn record() -> Trace {
+    let mut exec = RecordingExecutor::new(Scripted::with_responses(&[0x12, 0x34, 0x56]));
+    exec.init(&MpsseSettings::default()).unwrap();
+    exec.send(&[0x84]).unwrap();
+    let mut buf = [0; 1];
+    exec.xfer(&[0x81, 0x87], &mut buf).unwrap();
+    let mut buf = [0; 2];
+    exec.recv(&mut buf).unwrap();
+    // failed operations are not recorded
+    assert!(exec.recv(&mut buf).is_err());
+    exec.into_trace()
+}
+
+#[test]
+fn recording() {
+    let trace = record();
+    let ops: Vec<TraceOp> = trace.events.iter().map(|e| e.op.clone()).collect();
+    assert_eq!(
+        ops,
+        vec![
+            TraceOp::Init(MpsseSettings::default()),
+            TraceOp::Send(vec![0x84]),
+            TraceOp::Xfer {
+                tx: vec![0x81, 0x87],
+                rx: vec![0x12]
+            },
+            TraceOp::Recv(vec![0x34, 0x56]),
+        ]
+    );
+    assert!(trace
+        .events
+        .windows(2)
+        .all(|w| w[0].elapsed <= w[1].elapsed));
+}
+
+#[test]
+fn replay_executor() {
+    let mut exec = ReplayExecutor::new(record());
+    exec.init(&MpsseSettings::default()).unwrap();
+    exec.send(&[0x84]).unwrap();
+    let mut buf = [0; 1];
+    exec.xfer(&[0x81, 0x87], &mut buf).unwrap();
+    assert_eq!(buf, [0x12]);
+    let mut buf = [0; 2];
+    exec.recv(&mut buf).unwrap();
+    assert_eq!(buf, [0x34, 0x56]);
+    assert_eq!(exec.remaining(), 0);
+    assert_eq!(exec.send(&[0x84]), Err(ReplayError::Exhausted));
+
+    let mut exec = ReplayExecutor::new(record());
+    assert_eq!(exec.send(&[0x84]), Err(ReplayError::Mismatch { index: 0 }));
+}
+
+#[test]
+fn replay_on_other_executor() {
+    let trace = record();
+    let mut other = Scripted::with_responses(&[0xAA, 0xBB, 0xCC]);
+    let replayed = trace.replay(&mut other).unwrap();
+    assert_eq!(other.sent, vec![vec![0x84], vec![0x81, 0x87]]);
+    assert_eq!(replayed.events[3].op, TraceOp::Recv(vec![0xBB, 0xCC]));
+}
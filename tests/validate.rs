@@ -0,0 +1,146 @@
+use ftdi_mpsse::*;
+
+#[test]
+fn validate_consistent_transaction() {
+    let cmd = MpsseCmdBuilder::new()
+        .set_gpio_lower(0x08, 0x0B)
+        .set_gpio_lower(0x00, 0x0B)
+        .clock_data_out(ClockDataOut::MsbNeg, &[0x9F])
+        .clock_data_in(ClockDataIn::MsbPos, 3)
+        .clock_data(ClockData::MsbPosIn, &[0x00])
+        .set_gpio_lower(0x08, 0x0B)
+        .send_immediate();
+    assert!(cmd.validate(0x08).is_empty());
+}
+
+#[test]
+fn validate_mixed_edges() {
+    let cmd = MpsseCmdBuilder::new()
+        .set_gpio_lower(0x00, 0x0B)
+        .clock_data_out(ClockDataOut::MsbNeg, &[0x9F])
+        .clock_data_out(ClockDataOut::MsbPos, &[0x00])
+        .clock_data_in(ClockDataIn::MsbPos, 1)
+        .clock_data_in(ClockDataIn::MsbNeg, 1)
+        .set_gpio_lower(0x08, 0x0B);
+    assert_eq!(
+        cmd.validate(0x08),
+        vec![
+            ValidationWarning::MixedEdges {
+                previous: 3,
+                offset: 7
+            },
+            ValidationWarning::MixedEdges {
+                previous: 11,
+                offset: 14
+            },
+        ]
+    );
+}
+
+#[test]
+fn validate_ignores_deasserted_and_separate_regions() {
+    let cmd = MpsseCmdBuilder::new()
+        // chip select deasserted
+        .clock_data_out(ClockDataOut::LsbNeg, &[0x00])
+        .set_gpio_lower(0x00, 0x0B)
+        .clock_data_out(ClockDataOut::MsbNeg, &[0x00])
+        .set_gpio_lower(0x08, 0x0B)
+        .clock_bits_out(ClockBitsOut::LsbPos, 0x00, 8)
+        // new transaction
+        .set_gpio_lower(0x00, 0x0B)
+        .clock_data_out(ClockDataOut::LsbPos, &[0x00])
+        .clock_tms_out(ClockTMSOut::NegEdge, 0x00, false, 1)
+        .set_gpio_lower(0x08, 0x0B);
+    assert!(cmd.validate(0x08).is_empty());
+}
+
+#[test]
+fn validate_unclocked_data_bits() {
+    // MSB first clocks out bits 7, 6 and 5, LSB first bits 0, 1 and 2
+    let cmd = MpsseCmdBuilder::new()
+        .clock_bits_out(ClockBitsOut::MsbPos, 0xFF, 3)
+        .clock_bits(ClockBits::LsbPosIn, 0xFF, 3)
+        .clock_bits_out(ClockBitsOut::MsbPos, 0xE0, 3)
+        .clock_bits_out(ClockBitsOut::LsbPos, 0x07, 3)
+        .clock_bits_out(ClockBitsOut::LsbNeg, 0xFF, 8);
+    assert_eq!(
+        cmd.as_slice()[..6],
+        [
+            ClockBitsOut::MsbPos.into(),
+            0x02,
+            0xFF,
+            ClockBits::LsbPosIn.into(),
+            0x02,
+            0xFF
+        ]
+    );
+    assert_eq!(
+        cmd.validate(0x08),
+        vec![
+            ValidationWarning::UnclockedDataBits {
+                offset: 0,
+                bits: 0x1F
+            },
+            ValidationWarning::UnclockedDataBits {
+                offset: 3,
+                bits: 0xF8
+            },
+        ]
+    );
+}
+
+#[test]
+fn bit_length_too_long() {
+    for len in [0x08, 0x0F, 0xFF] {
+        let cmd = MpsseCmdBuilder::with_vec(vec![0x12, len, 0x00]);
+        assert_eq!(
+            cmd.validate(0x08),
+            vec![ValidationWarning::BitLengthTooLong {
+                offset: 0,
+                len: u16::from(len) + 1
+            }]
+        );
+    }
+    let cmd = MpsseCmdBuilder::with_vec(vec![0x4B, 0x0F, 0x00]);
+    assert_eq!(
+        cmd.validate(0x08),
+        vec![ValidationWarning::BitLengthTooLong { offset: 0, len: 16 }]
+    );
+}
+
+#[test]
+fn max_burst_len() {
+    assert_eq!(MpsseCmdBuilder::new().max_burst_len(), 0);
+    let cmd = MpsseCmdBuilder::new()
+        .set_gpio_lower(0x00, 0x0B)
+        .send_immediate()
+        // 0x87 in data is not a send immediate
+        .clock_data_out(ClockDataOut::MsbNeg, &[0x87; 8])
+        .send_immediate()
+        .gpio_lower();
+    assert_eq!(cmd.max_burst_len(), 12);
+    assert_eq!(cmd.send_immediate().max_burst_len(), 12);
+}
+
+#[test]
+fn check_command_buffer() {
+    let cmd = MpsseCmdBuilder::new()
+        .set_gpio_lower(0x00, 0x0B)
+        .send_immediate()
+        .clock_data_out(ClockDataOut::MsbNeg, &[0x00; 8])
+        .gpio_lower();
+    assert_eq!(cmd.check_command_buffer(12), Ok(()));
+    let err = cmd.check_command_buffer(11).unwrap_err();
+    assert_eq!(
+        err,
+        CommandBufferError {
+            offset: 4,
+            len: 12,
+            buffer_size: 11
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "burst of 12 bytes at offset 4 exceeds the 11 byte command buffer"
+    );
+}
@@ -0,0 +1,130 @@
+use ftdi_mpsse::*;
+
+#[test]
+fn square_wave() {
+    let cmd = MpsseCmdBuilder::new().square_wave(2, 20, 2, 0x01);
+    let period = [
+        MpsseCmd::SetDataBitsLowbyte as u8,
+        0x04,
+        0x05,
+        MpsseCmd::ClockNx8Bits as u8,
+        1,
+        0,
+        MpsseCmd::ClockNBits as u8,
+        3,
+        MpsseCmd::SetDataBitsLowbyte as u8,
+        0x00,
+        0x05,
+        MpsseCmd::ClockNx8Bits as u8,
+        1,
+        0,
+        MpsseCmd::ClockNBits as u8,
+        3,
+    ];
+    assert_eq!(cmd.as_slice(), [period, period].concat());
+}
+
+#[test]
+fn square_wave_long_delay() {
+    let cmd = MpsseCmdBuilder::new().square_wave(0, 65536 * 8 + 8, 1, 0x00);
+    assert_eq!(
+        &cmd.as_slice()[3..9],
+        [
+            MpsseCmd::ClockNx8Bits as u8,
+            0xFF,
+            0xFF,
+            MpsseCmd::ClockNx8Bits as u8,
+            0,
+            0,
+        ]
+    );
+}
+
+#[test]
+#[should_panic(expected = "pin number cannot exceed 7")]
+fn square_wave_pin_assert() {
+    MpsseCmdBuilder::new().square_wave(8, 1, 1, 0xFF);
+}
+
+#[test]
+fn set_gpio_timed() {
+    // 1 us setup at 10 MHz is 10 clocks, no hold
+    let cmd = MpsseCmdBuilder::new().set_gpio_timed(0xFF, 0x0F, 1000, 0, 10_000_000);
+    assert_eq!(
+        cmd.as_slice(),
+        [
+            MpsseCmd::ClockNx8Bits as u8,
+            0,
+            0,
+            MpsseCmd::ClockNBits as u8,
+            1,
+            MpsseCmd::SetDataBitsLowbyte as u8,
+            0xFF,
+            0x0F,
+        ]
+    );
+
+    // partial cycles round up
+    let cmd = MpsseCmdBuilder::new().set_gpio_timed(0x00, 0x00, 1, 1, 6_000_000);
+    assert_eq!(
+        cmd.as_slice(),
+        [
+            MpsseCmd::ClockNBits as u8,
+            0,
+            MpsseCmd::SetDataBitsLowbyte as u8,
+            0x00,
+            0x00,
+            MpsseCmd::ClockNBits as u8,
+            0,
+        ]
+    );
+}
+
+#[test]
+fn gpio_waveform() {
+    use std::time::Duration;
+
+    let steps = [
+        (0x01, Duration::from_nanos(100)),
+        (0x03, Duration::ZERO),
+        (0x00, Duration::from_micros(20)),
+    ];
+    assert_eq!(
+        MpsseCmdBuilder::new()
+            .gpio_waveform(&steps, 0x03, 30_000_000)
+            .as_slice(),
+        [
+            0x80, 0x01, 0x03, 0x8E, 2, // 3 clocks
+            0x80, 0x03, 0x03, // no delay
+            0x80, 0x00, 0x03, 0x8F, 74, 0x00, // 600 clocks
+        ]
+    );
+    assert!(MpsseCmdBuilder::new()
+        .gpio_waveform(&[], 0x03, 30_000_000)
+        .as_slice()
+        .is_empty());
+}
+
+#[test]
+fn gpio_pwm() {
+    let cmd = MpsseCmdBuilder::new().gpio_pwm(4, 0.25, 16, 2, 0x01);
+    let period = [
+        0x80, 0x10, 0x11, 0x8E, 0x03, 0x80, 0x00, 0x11, 0x8F, 0x00, 0x00, 0x8E, 0x03,
+    ];
+    assert_eq!(cmd.as_slice(), [period, period].concat().as_slice());
+
+    // no glitches at 0% and 100%
+    let cmd = MpsseCmdBuilder::new().gpio_pwm(4, 1.0, 7, 2, 0x00);
+    assert_eq!(
+        cmd.as_slice(),
+        [0x80, 0x10, 0x10, 0x8E, 0x06].repeat(2).as_slice()
+    );
+    let cmd = MpsseCmdBuilder::new().gpio_pwm(4, 0.0, 7, 1, 0x00);
+    assert_eq!(cmd.as_slice(), [0x80, 0x00, 0x10, 0x8E, 0x06]);
+}
+
+#[test]
+#[should_panic(expected = "duty cycle must be between 0.0 and 1.0")]
+fn gpio_pwm_duty_assert() {
+    let _ = MpsseCmdBuilder::new().gpio_pwm(4, 1.5, 8, 1, 0x00);
+}